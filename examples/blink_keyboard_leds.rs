@@ -1,4 +1,4 @@
-use evdev::{LedCode, LedEvent};
+use evdev::{AttributeSet, LedCode};
 
 mod _pick_device;
 
@@ -6,14 +6,15 @@ fn main() {
     let mut d = _pick_device::pick_device();
     println!("{d}");
     println!("Blinking the Keyboard LEDS...");
+    let leds: AttributeSet<LedCode> = d
+        .supported_leds()
+        .into_iter()
+        .flat_map(|l| l.iter())
+        .collect();
+    let off = AttributeSet::<LedCode>::new();
     for i in 0..5 {
         let on = i % 2 != 0;
-        d.send_events(&[
-            *LedEvent::new(LedCode::LED_CAPSL, if on { i32::MAX } else { 0 }),
-            *LedEvent::new(LedCode::LED_NUML, if on { i32::MAX } else { 0 }),
-            *LedEvent::new(LedCode::LED_SCROLLL, if on { i32::MAX } else { 0 }),
-        ])
-        .unwrap();
+        d.set_leds(if on { &leds } else { &off }).unwrap();
         std::thread::sleep(std::time::Duration::from_secs(1));
     }
 }
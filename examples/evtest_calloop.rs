@@ -0,0 +1,31 @@
+//! Demonstrating how to monitor events with evdev + calloop
+
+// cli/"tui" shared between the evtest examples
+mod _pick_device;
+
+use calloop::EventLoop;
+use evdev::calloop::FrameSource;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let d = _pick_device::pick_device();
+    println!("{}", d);
+    println!("Events:");
+
+    let mut event_loop = EventLoop::<bool>::try_new()?;
+    let source = FrameSource::new(d)?;
+    event_loop
+        .handle()
+        .insert_source(source, |frame, _device, running| match frame {
+            Ok(frame) => println!("{:?}", frame.events()),
+            Err(e) => {
+                eprintln!("{}", e);
+                *running = false;
+            }
+        })?;
+
+    let mut running = true;
+    while running {
+        event_loop.dispatch(None, &mut running)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! Demonstrating how to monitor events with evdev + mio
+
+// cli/"tui" shared between the evtest examples
+mod _pick_device;
+
+use mio::{Events, Interest, Poll, Token};
+
+fn main() -> std::io::Result<()> {
+    let mut d = _pick_device::pick_device();
+    println!("{}", d);
+
+    // registering puts the device into nonblocking mode
+    let mut poll = Poll::new()?;
+    poll.registry()
+        .register(&mut d, Token(0), Interest::READABLE)?;
+    let mut events = Events::with_capacity(1);
+
+    println!("Events:");
+    loop {
+        poll.poll(&mut events, None)?;
+        // mio is edge-triggered, so read everything there is
+        loop {
+            match d.fetch_events() {
+                Ok(iterator) => {
+                    for ev in iterator {
+                        println!("{:?}", ev);
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
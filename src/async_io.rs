@@ -0,0 +1,25 @@
+//! Event streams for the [`async-io`](https://docs.rs/async-io) reactor, as used by `smol` and
+//! `async-std`.
+//!
+//! These offer the same API as the streams for tokio that `into_event_stream` returns, and share
+//! their implementation, including the resynchronization after a `SYN_DROPPED` and the handling
+//! of force feedback requests to virtual devices.
+//!
+//! ```no_run
+//! use evdev::{async_io::EventStream, Device};
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let device = Device::open("/dev/input/event0")?;
+//! let mut events = EventStream::new(device)?;
+//! async_io::block_on(async {
+//!     loop {
+//!         let ev = events.next_event().await?;
+//!         println!("{ev:?}");
+//!     }
+//! })
+//! # }
+//! ```
+
+pub use crate::raw_stream::async_io_stream::EventStream as RawEventStream;
+pub use crate::sync_stream::async_io_stream::EventStream;
+pub use crate::uinput::async_io_stream::VirtualEventStream;
@@ -0,0 +1,108 @@
+//! The part of the async event streams that depends on the runtime.
+//!
+//! The streams themselves are written once, generic over an [`AsyncSource`], and each runtime
+//! only provides the public wrapper types around them.
+
+use std::io;
+use std::os::fd::AsFd;
+use std::task::{Context, Poll};
+
+/// A device registered with an async runtime, so that reads can wait for it to become readable.
+pub(crate) trait AsyncSource: Sized {
+    type Device: AsFd;
+
+    /// Registers `device` with the runtime, after putting it into nonblocking mode.
+    fn new(device: Self::Device) -> io::Result<Self>;
+
+    fn get_ref(&self) -> &Self::Device;
+
+    fn get_mut(&mut self) -> &mut Self::Device;
+
+    /// Attempts `op` until it doesn't fail with `WouldBlock`, waiting for the device to become
+    /// readable in between.
+    fn poll_read_with<R>(
+        &mut self,
+        cx: &mut Context<'_>,
+        op: impl FnMut(&mut Self::Device) -> io::Result<R>,
+    ) -> Poll<io::Result<R>>;
+}
+
+#[cfg(feature = "tokio")]
+impl<D: AsFd + std::os::fd::AsRawFd> AsyncSource for tokio::io::unix::AsyncFd<D> {
+    type Device = D;
+
+    fn new(device: D) -> io::Result<Self> {
+        use nix::fcntl;
+        let fd = device.as_raw_fd();
+        let mut flags = fcntl::OFlag::from_bits_retain(fcntl::fcntl(fd, fcntl::F_GETFL)?);
+        flags.insert(fcntl::OFlag::O_NONBLOCK);
+        fcntl::fcntl(fd, fcntl::F_SETFL(flags))?;
+        tokio::io::unix::AsyncFd::new(device)
+    }
+
+    fn get_ref(&self) -> &D {
+        self.get_ref()
+    }
+
+    fn get_mut(&mut self) -> &mut D {
+        self.get_mut()
+    }
+
+    fn poll_read_with<R>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut op: impl FnMut(&mut D) -> io::Result<R>,
+    ) -> Poll<io::Result<R>> {
+        loop {
+            let mut guard = std::task::ready!(self.poll_read_ready_mut(cx))?;
+            match guard.try_io(|device| op(device.get_mut())) {
+                Ok(res) => return Poll::Ready(res),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+/// A device registered with the `async-io` reactor.
+///
+/// `async_io::Async` doesn't allow handing out mutable references to what it wraps, as replacing
+/// it would drop a file descriptor that is still registered. Instead, a duplicate of the device's
+/// file descriptor is registered, and the device itself is kept next to it.
+#[cfg(feature = "async-io")]
+pub(crate) struct AsyncIo<D> {
+    device: D,
+    fd: ::async_io::Async<std::os::fd::OwnedFd>,
+}
+
+#[cfg(feature = "async-io")]
+impl<D: AsFd> AsyncSource for AsyncIo<D> {
+    type Device = D;
+
+    fn new(device: D) -> io::Result<Self> {
+        // the duplicate shares the file status flags, so this also makes `device` nonblocking
+        let fd = ::async_io::Async::new(device.as_fd().try_clone_to_owned()?)?;
+        Ok(AsyncIo { device, fd })
+    }
+
+    fn get_ref(&self) -> &D {
+        &self.device
+    }
+
+    fn get_mut(&mut self) -> &mut D {
+        &mut self.device
+    }
+
+    fn poll_read_with<R>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut op: impl FnMut(&mut D) -> io::Result<R>,
+    ) -> Poll<io::Result<R>> {
+        loop {
+            match op(&mut self.device) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                res => return Poll::Ready(res),
+            }
+            std::task::ready!(self.fd.poll_readable(cx))?;
+        }
+    }
+}
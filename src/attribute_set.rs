@@ -141,6 +141,11 @@ impl<T: ArrayedEvdevEnum> AttributeSet<T> {
         T::array_as_slice_mut(&mut self.container)
     }
 
+    #[inline]
+    pub(crate) fn as_raw_slice(&self) -> &[u8] {
+        T::array_as_bytes(&self.container)
+    }
+
     #[inline]
     pub(crate) fn as_mut_raw_slice(&mut self) -> &mut [u8] {
         T::array_as_buf(&mut self.container)
@@ -218,6 +223,7 @@ pub trait ArrayedEvdevEnum: EvdevEnum {
     type Array;
     fn array_as_slice(arr: &Self::Array) -> &BitSlice<u8>;
     fn array_as_slice_mut(arr: &mut Self::Array) -> &mut BitSlice<u8>;
+    fn array_as_bytes(arr: &Self::Array) -> &[u8];
     fn array_as_buf(arr: &mut Self::Array) -> &mut [u8];
     fn zeroed_array() -> Self::Array;
 }
@@ -227,6 +233,7 @@ macro_rules! evdev_enum {
         evdev_enum!(
             $t,
             Array: bitvec::BitArr!(for <$t>::COUNT, in u8),
+            bitvec::array::BitArray::as_raw_slice,
             bitvec::array::BitArray::as_raw_mut_slice,
             bitvec::array::BitArray::ZERO,
             $($(#[$attr])* $c = $val,)*
@@ -236,6 +243,7 @@ macro_rules! evdev_enum {
         evdev_enum!(
             $t,
             Array: Box<bitvec::BitArr!(for <$t>::COUNT, in u8)>,
+            bitvec::array::BitArray::as_raw_slice,
             bitvec::array::BitArray::as_raw_mut_slice,
             Box::new(bitvec::array::BitArray::ZERO),
             $($(#[$attr])* $c = $val,)*
@@ -243,7 +251,7 @@ macro_rules! evdev_enum {
     };
     (
         $t:ty,
-        Array: $Array:ty, $arr_as_buf:expr, $zero:expr,
+        Array: $Array:ty, $arr_as_bytes:expr, $arr_as_buf:expr, $zero:expr,
         $($(#[$attr:meta])* $c:ident = $val:expr,)*
     ) => {
         impl $crate::attribute_set::ArrayedEvdevEnum for $t {
@@ -254,6 +262,9 @@ macro_rules! evdev_enum {
             fn array_as_slice_mut(arr: &mut Self::Array) -> &mut bitvec::slice::BitSlice<u8> {
                 arr
             }
+            fn array_as_bytes(arr: &Self::Array) -> &[u8] {
+                $arr_as_bytes(arr)
+            }
             fn array_as_buf(arr: &mut Self::Array) -> &mut [u8] {
                 $arr_as_buf(arr)
             }
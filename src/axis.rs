@@ -0,0 +1,390 @@
+//! Normalizing the values of absolute axes.
+//!
+//! An [`AxisNormalizer`] maps the raw values of an axis, between the `minimum` and `maximum` of
+//! its [`AbsInfo`], to an `f32` in [-1, 1] for sticks or in [0, 1] for triggers and pedals. On
+//! the way it applies a deadzone and a [`ResponseCurve`]:
+//!
+//! - The deadzone is the larger one of the axis' `flat`, which the driver reports for the noise
+//!   around the center of a stick, and the one set with [`AxisNormalizer::deadzone`]. Values
+//!   inside it become 0, and the rest of the range is stretched so that the output still starts
+//!   at 0 and reaches 1.
+//! - The response curve maps the magnitude of the value past the deadzone, e.g. to give finer
+//!   control around the center of a stick.
+//!
+//! A deadzone on each axis of a stick is square, and makes it hard to move the stick along a
+//! diagonal close to the center. A [`StickNormalizer`] combines two axes to apply a
+//! [radial](Deadzone::Radial) deadzone and the response curve to the distance from the center
+//! instead.
+//!
+//! Axes that report a `resolution` can also be converted to physical units with
+//! [`AxisNormalizer::physical`]: millimetres for most axes, and radians for `ABS_RX`, `ABS_RY`
+//! and `ABS_RZ` when they are rotations.
+//!
+//! ```no_run
+//! use evdev::axis::{AxisNormalizer, AxisRange, Deadzone, ResponseCurve, StickNormalizer};
+//! use evdev::{AbsoluteAxisCode, Device, EventSummary};
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let mut device = Device::open("/dev/input/event0")?;
+//! let (mut x, mut y) = (0, 0);
+//! let stick = StickNormalizer::new(
+//!     AxisNormalizer::from_device(&device, AbsoluteAxisCode::ABS_X)?,
+//!     AxisNormalizer::from_device(&device, AbsoluteAxisCode::ABS_Y)?,
+//! )
+//! .deadzone(Deadzone::Radial(0.1))
+//! .curve(ResponseCurve::Exponential(2.0));
+//! let trigger = AxisNormalizer::from_device(&device, AbsoluteAxisCode::ABS_Z)?
+//!     .range(AxisRange::Unipolar)
+//!     .deadzone(0.05);
+//! loop {
+//!     for event in device.fetch_events()? {
+//!         match event.destructure() {
+//!             EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_X, value) => x = value,
+//!             EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_Y, value) => y = value,
+//!             EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_Z, value) => {
+//!                 println!("trigger: {}", trigger.normalize(value));
+//!             }
+//!             _ => {}
+//!         }
+//!     }
+//!     println!("stick: {:?}", stick.normalize(x, y));
+//! }
+//! # }
+//! ```
+
+use std::io;
+
+use crate::{AbsInfo, AbsoluteAxisCode, Device};
+
+/// The range an [`AxisNormalizer`] maps the values of an axis to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AxisRange {
+    /// [-1, 1], with 0 halfway between the minimum and the maximum, for sticks.
+    #[default]
+    Bipolar,
+    /// [0, 1], with 0 at the minimum, for triggers, pedals and throttles.
+    Unipolar,
+}
+
+/// How the magnitude of a normalized value past the deadzone is mapped to the output.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ResponseCurve {
+    /// The output is the input.
+    #[default]
+    Linear,
+    /// The magnitude is raised to this power, so exponents above 1 give finer control around the
+    /// center and exponents below 1 a quicker response. Exponents that aren't positive are
+    /// treated as 1.
+    Exponential(f32),
+    /// The output is looked up in a table.
+    Table(ResponseTable),
+}
+
+impl ResponseCurve {
+    /// Returns the output for the magnitude `v` in [0, 1].
+    pub fn apply(&self, v: f32) -> f32 {
+        match self {
+            ResponseCurve::Linear => v,
+            ResponseCurve::Exponential(exp) if *exp > 0.0 => v.powf(*exp),
+            ResponseCurve::Exponential(_) => v,
+            ResponseCurve::Table(table) => table.output(v),
+        }
+    }
+}
+
+/// The table of a [custom](ResponseCurve::Table) response curve.
+///
+/// The points are the outputs for inputs spread evenly from 0 to 1: with 5 points, `points[1]` is
+/// the output for 0.25. Between points the curve is interpolated linearly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseTable {
+    points: Vec<f32>,
+}
+
+impl ResponseTable {
+    /// Creates a table from the outputs for evenly spread inputs.
+    ///
+    /// Fails with `InvalidInput` unless there are between 2 and 256 points, and all of them are
+    /// in [0, 1].
+    pub fn new(points: Vec<f32>) -> io::Result<Self> {
+        if !(2..=256).contains(&points.len()) || !points.iter().all(|p| (0.0..=1.0).contains(p)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a response table needs 2 to 256 points between 0 and 1",
+            ));
+        }
+        Ok(ResponseTable { points })
+    }
+
+    /// Returns the outputs of the table.
+    pub fn points(&self) -> &[f32] {
+        &self.points
+    }
+
+    /// Returns the output for the input `v` in [0, 1].
+    pub fn output(&self, v: f32) -> f32 {
+        let last = self.points.len() - 1;
+        let pos = v.clamp(0.0, 1.0) * last as f32;
+        let i = (pos.floor() as usize).min(last - 1);
+        let (a, b) = (self.points[i], self.points[i + 1]);
+        a + (b - a) * (pos - i as f32)
+    }
+}
+
+/// Maps the raw values of an absolute axis to a normalized range.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisNormalizer {
+    info: AbsInfo,
+    range: AxisRange,
+    deadzone: f32,
+    curve: ResponseCurve,
+}
+
+impl AxisNormalizer {
+    /// Creates a normalizer for an axis with the minimum, maximum, flat and resolution of `info`,
+    /// mapping to [-1, 1] without a deadzone besides the flat, and with a linear response.
+    pub fn new(info: AbsInfo) -> Self {
+        AxisNormalizer {
+            info,
+            range: AxisRange::default(),
+            deadzone: 0.0,
+            curve: ResponseCurve::default(),
+        }
+    }
+
+    /// Creates a normalizer for the `axis` of `device`, see [`new`](Self::new).
+    ///
+    /// Fails with `NotFound` if the device doesn't have the axis.
+    pub fn from_device(device: &Device, axis: AbsoluteAxisCode) -> io::Result<Self> {
+        device
+            .get_absinfo()?
+            .find(|(code, _)| *code == axis)
+            .map(|(_, info)| Self::new(info))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("the device doesn't have {axis:?}"),
+                )
+            })
+    }
+
+    /// Sets the range the values are mapped to.
+    pub fn range(mut self, range: AxisRange) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// Sets the deadzone, as a fraction of the distance from the center to the end of the axis,
+    /// or from the minimum to the maximum for [unipolar](AxisRange::Unipolar) axes. It is
+    /// clamped to [0, 1].
+    ///
+    /// The flat of the axis applies if it is larger.
+    pub fn deadzone(mut self, deadzone: f32) -> Self {
+        self.deadzone = clamp_unit(deadzone);
+        self
+    }
+
+    /// Sets the response curve.
+    pub fn curve(mut self, curve: ResponseCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Returns the `AbsInfo` of the axis.
+    pub fn info(&self) -> &AbsInfo {
+        &self.info
+    }
+
+    /// Returns the range the values are mapped to.
+    pub fn get_range(&self) -> AxisRange {
+        self.range
+    }
+
+    /// Returns the deadzone that applies, the larger one of the flat of the axis and the
+    /// configured one.
+    pub fn effective_deadzone(&self) -> f32 {
+        self.flat().max(self.deadzone)
+    }
+
+    /// Returns the response curve.
+    pub fn get_curve(&self) -> &ResponseCurve {
+        &self.curve
+    }
+
+    /// Returns `value` mapped to the range of the normalizer, with the deadzone and the response
+    /// curve applied. Values outside of the minimum and maximum are clamped.
+    pub fn normalize(&self, value: i32) -> f32 {
+        self.shape(value, self.effective_deadzone(), &self.curve)
+    }
+
+    /// Returns `value` in physical units, millimetres or radians, using the resolution of the
+    /// axis, or `None` if it doesn't report one.
+    ///
+    /// The value keeps the origin of the axis: a value of 0 is at 0 mm.
+    pub fn physical(&self, value: i32) -> Option<f32> {
+        match self.info.resolution() {
+            res if res > 0 => Some(value as f32 / res as f32),
+            _ => None,
+        }
+    }
+
+    /// Returns the distance from the minimum to the maximum of the axis in physical units, or
+    /// `None` if it doesn't report a resolution.
+    pub fn physical_size(&self) -> Option<f32> {
+        match self.info.resolution() {
+            res if res > 0 => Some(self.span() / res as f32),
+            _ => None,
+        }
+    }
+
+    /// Returns `value` normalized with the given deadzone and curve instead of the configured
+    /// ones.
+    fn shape(&self, value: i32, deadzone: f32, curve: &ResponseCurve) -> f32 {
+        let v = self.scale(value);
+        match past_deadzone(v.abs(), deadzone) {
+            m if m > 0.0 => v.signum() * curve.apply(m),
+            _ => 0.0,
+        }
+    }
+
+    /// Returns the flat of the axis as a fraction of the range, like the deadzone.
+    fn flat(&self) -> f32 {
+        let span = self.span();
+        let flat = match self.range {
+            _ if span <= 0.0 => 0.0,
+            AxisRange::Bipolar => self.info.flat() as f32 / (span / 2.0),
+            AxisRange::Unipolar => self.info.flat() as f32 / span,
+        };
+        clamp_unit(flat)
+    }
+
+    /// Returns `value` mapped linearly to the range of the normalizer.
+    fn scale(&self, value: i32) -> f32 {
+        let span = self.span();
+        if span <= 0.0 {
+            return 0.0;
+        }
+        let offset = (value as f64 - self.info.minimum() as f64) as f32;
+        match self.range {
+            AxisRange::Bipolar => (offset / span * 2.0 - 1.0).clamp(-1.0, 1.0),
+            AxisRange::Unipolar => (offset / span).clamp(0.0, 1.0),
+        }
+    }
+
+    fn span(&self) -> f32 {
+        (self.info.maximum() as f64 - self.info.minimum() as f64) as f32
+    }
+}
+
+/// How a [`StickNormalizer`] applies its deadzone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Deadzone {
+    /// Each axis has its own deadzone of this size, and its own response curve, as with
+    /// [`AxisNormalizer::deadzone`]. The deadzone is a square around the center.
+    Axial(f32),
+    /// Positions closer to the center than this are 0, and the response curve applies to the
+    /// distance from the center. The deadzone is a circle.
+    Radial(f32),
+}
+
+/// Maps the raw values of the two axes of a stick to [-1, 1].
+///
+/// The deadzone and the response curve of the stick replace those of its axes, but the larger
+/// flat of the two axes still applies. See the [module-level documentation](self) for an example.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StickNormalizer {
+    x: AxisNormalizer,
+    y: AxisNormalizer,
+    deadzone: Deadzone,
+    curve: ResponseCurve,
+}
+
+impl StickNormalizer {
+    /// Creates a normalizer for the stick with the axes `x` and `y`, with a radial deadzone of
+    /// just their flat and a linear response.
+    pub fn new(x: AxisNormalizer, y: AxisNormalizer) -> Self {
+        StickNormalizer {
+            x: x.range(AxisRange::Bipolar),
+            y: y.range(AxisRange::Bipolar),
+            deadzone: Deadzone::Radial(0.0),
+            curve: ResponseCurve::default(),
+        }
+    }
+
+    /// Sets the deadzone. Its size is clamped to [0, 1].
+    pub fn deadzone(mut self, deadzone: Deadzone) -> Self {
+        self.deadzone = match deadzone {
+            Deadzone::Axial(size) => Deadzone::Axial(clamp_unit(size)),
+            Deadzone::Radial(size) => Deadzone::Radial(clamp_unit(size)),
+        };
+        self
+    }
+
+    /// Sets the response curve.
+    pub fn curve(mut self, curve: ResponseCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Returns the normalizers of the x and y axes.
+    pub fn axes(&self) -> (&AxisNormalizer, &AxisNormalizer) {
+        (&self.x, &self.y)
+    }
+
+    /// Returns the deadzone.
+    pub fn get_deadzone(&self) -> Deadzone {
+        self.deadzone
+    }
+
+    /// Returns the response curve.
+    pub fn get_curve(&self) -> &ResponseCurve {
+        &self.curve
+    }
+
+    /// Returns the raw values `x` and `y` mapped to [-1, 1], with the deadzone and the response
+    /// curve applied. With a radial deadzone, the result is within the unit circle.
+    pub fn normalize(&self, x: i32, y: i32) -> (f32, f32) {
+        let size = match self.deadzone {
+            Deadzone::Axial(size) => {
+                return (
+                    self.x.shape(x, self.x.flat().max(size), &self.curve),
+                    self.y.shape(y, self.y.flat().max(size), &self.curve),
+                );
+            }
+            Deadzone::Radial(size) => size.max(self.x.flat()).max(self.y.flat()),
+        };
+        radial_deadzone(self.x.scale(x), self.y.scale(y), size, &self.curve)
+    }
+}
+
+/// Applies a radial deadzone of `size` and the response curve to the distance of the position
+/// `x` and `y`, both in [-1, 1], from the center.
+pub(crate) fn radial_deadzone(x: f32, y: f32, size: f32, curve: &ResponseCurve) -> (f32, f32) {
+    let distance = x.hypot(y);
+    match past_deadzone(distance.min(1.0), size) {
+        m if m > 0.0 => {
+            let output = curve.apply(m);
+            (x / distance * output, y / distance * output)
+        }
+        _ => (0.0, 0.0),
+    }
+}
+
+/// Returns the magnitude `v` in [0, 1] as the fraction of the range past the `deadzone` in [0, 1].
+fn past_deadzone(v: f32, deadzone: f32) -> f32 {
+    if v <= deadzone {
+        0.0
+    } else {
+        (v - deadzone) / (1.0 - deadzone)
+    }
+}
+
+fn clamp_unit(v: f32) -> f32 {
+    if v.is_nan() {
+        0.0
+    } else {
+        v.clamp(0.0, 1.0)
+    }
+}
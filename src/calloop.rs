@@ -0,0 +1,304 @@
+//! Event sources for the [`calloop`](https://docs.rs/calloop) event loop.
+//!
+//! A [`DeviceSource`] delivers the synchronized events of a [`Device`] to its callback, and a
+//! [`FrameSource`] delivers them grouped into frames. Both take care of reading until the kernel
+//! buffer is empty and of the resynchronization after a `SYN_DROPPED`, and hand the device to the
+//! callback as the metadata, e.g. to look at its [cached state](Device::cached_state).
+//!
+//! Errors are passed to the callback as well. Once the device is unplugged or revoked, the
+//! callback receives an `ENODEV` error and the source removes itself from the event loop.
+//!
+//! ```no_run
+//! use calloop::EventLoop;
+//! use evdev::calloop::DeviceSource;
+//! use evdev::Device;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut event_loop = EventLoop::<()>::try_new()?;
+//! let source = DeviceSource::new(Device::open("/dev/input/event0")?)?;
+//! event_loop
+//!     .handle()
+//!     .insert_source(source, |event, _device, _| match event {
+//!         Ok(ev) => println!("{ev:?}"),
+//!         Err(e) => eprintln!("{e}"),
+//!     })?;
+//! event_loop.run(None, &mut (), |_| {})?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io;
+use std::os::fd::{AsFd, OwnedFd};
+use std::time::{Duration, SystemTime};
+
+use ::calloop::generic::Generic;
+use ::calloop::{EventSource, Interest, Mode, Poll, PostAction, Readiness, Token, TokenFactory};
+
+use crate::{Device, Frame, InputEvent};
+
+/// The part of the sources that waits for the device to become readable.
+///
+/// A duplicate of the device's file descriptor is registered with the event loop, so that the
+/// device itself can be handed out mutably without the risk of closing a registered descriptor.
+struct Readable {
+    device: Device,
+    fd: Generic<OwnedFd>,
+}
+
+impl Readable {
+    fn new(device: Device) -> io::Result<Self> {
+        device.set_nonblocking(true)?;
+        let fd = device.as_fd().try_clone_to_owned()?;
+        Ok(Readable {
+            device,
+            fd: Generic::new(fd, Interest::READ, Mode::Level),
+        })
+    }
+
+    /// Calls `read` until the device has no more events, handing what it put into `buf` to
+    /// `callback` after every read. Errors go to `callback` as well.
+    fn process_events<T>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        buf: &mut Vec<T>,
+        mut read: impl FnMut(&mut Device, &mut Vec<T>) -> io::Result<()>,
+        mut callback: impl FnMut(io::Result<T>, &mut Device),
+    ) -> io::Result<PostAction> {
+        let device = &mut self.device;
+        self.fd.process_events(readiness, token, |_, _| loop {
+            match read(device, buf) {
+                Ok(()) => buf.drain(..).for_each(|item| callback(Ok(item), device)),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(PostAction::Continue),
+                Err(e) => {
+                    let gone = crate::is_device_gone(&e);
+                    callback(Err(e), device);
+                    return Ok(if gone {
+                        PostAction::Remove
+                    } else {
+                        PostAction::Continue
+                    });
+                }
+            }
+        })
+    }
+}
+
+/// A calloop event source that delivers the synchronized events of a [`Device`].
+///
+/// The events are delivered after each read from the device, so the device's cached state
+/// already includes all events that were read along with the one being delivered.
+///
+/// See the [module-level documentation](self) for an example.
+pub struct DeviceSource {
+    inner: Readable,
+    events: Vec<InputEvent>,
+}
+
+impl DeviceSource {
+    /// Puts `device` into nonblocking mode and creates a source for its events.
+    pub fn new(device: Device) -> io::Result<Self> {
+        Ok(DeviceSource {
+            inner: Readable::new(device)?,
+            events: Vec::new(),
+        })
+    }
+
+    /// Returns a reference to the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.inner.device
+    }
+
+    /// Returns a mutable reference to the underlying device.
+    pub fn device_mut(&mut self) -> &mut Device {
+        &mut self.inner.device
+    }
+
+    /// Returns the underlying device, which is left in nonblocking mode.
+    pub fn into_device(self) -> Device {
+        self.inner.device
+    }
+}
+
+impl EventSource for DeviceSource {
+    type Event = io::Result<InputEvent>;
+    type Metadata = Device;
+    type Ret = ();
+    type Error = io::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        callback: F,
+    ) -> io::Result<PostAction>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata),
+    {
+        self.inner.process_events(
+            readiness,
+            token,
+            &mut self.events,
+            |device, events| {
+                events.extend(device.fetch_events()?);
+                Ok(())
+            },
+            callback,
+        )
+    }
+
+    fn register(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> ::calloop::Result<()> {
+        self.inner.fd.register(poll, token_factory)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> ::calloop::Result<()> {
+        self.inner.fd.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> ::calloop::Result<()> {
+        self.inner.fd.unregister(poll)
+    }
+}
+
+/// A calloop event source that delivers the synchronized events of a [`Device`] grouped into
+/// frames, as with [`Device::fetch_frames`].
+pub struct FrameSource {
+    inner: Readable,
+    frames: Vec<OwnedFrame>,
+}
+
+impl FrameSource {
+    /// Puts `device` into nonblocking mode and creates a source for its frames.
+    pub fn new(device: Device) -> io::Result<Self> {
+        Ok(FrameSource {
+            inner: Readable::new(device)?,
+            frames: Vec::new(),
+        })
+    }
+
+    /// Returns a reference to the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.inner.device
+    }
+
+    /// Returns a mutable reference to the underlying device.
+    pub fn device_mut(&mut self) -> &mut Device {
+        &mut self.inner.device
+    }
+
+    /// Returns the underlying device, which is left in nonblocking mode.
+    pub fn into_device(self) -> Device {
+        self.inner.device
+    }
+}
+
+impl EventSource for FrameSource {
+    type Event = io::Result<OwnedFrame>;
+    type Metadata = Device;
+    type Ret = ();
+    type Error = io::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        callback: F,
+    ) -> io::Result<PostAction>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata),
+    {
+        self.inner.process_events(
+            readiness,
+            token,
+            &mut self.frames,
+            |device, frames| {
+                let mut fetched = device.fetch_frames()?;
+                while let Some(frame) = fetched.next_frame() {
+                    frames.push(OwnedFrame::from(frame));
+                }
+                Ok(())
+            },
+            callback,
+        )
+    }
+
+    fn register(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> ::calloop::Result<()> {
+        self.inner.fd.register(poll, token_factory)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> ::calloop::Result<()> {
+        self.inner.fd.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> ::calloop::Result<()> {
+        self.inner.fd.unregister(poll)
+    }
+}
+
+/// A [`Frame`] that owns its events, as delivered by a [`FrameSource`].
+#[derive(Debug, Clone)]
+pub struct OwnedFrame {
+    events: Vec<InputEvent>,
+    time: libc::timeval,
+    synthetic: bool,
+}
+
+impl OwnedFrame {
+    /// Borrows this as a [`Frame`].
+    pub fn as_frame(&self) -> Frame<'_> {
+        Frame::new(&self.events, self.time, self.synthetic)
+    }
+
+    /// Returns the events of this frame, without the terminating `SYN_REPORT`.
+    pub fn events(&self) -> &[InputEvent] {
+        &self.events
+    }
+
+    /// Returns the timestamp of the `SYN_REPORT` that terminated this frame.
+    pub fn timestamp(&self) -> SystemTime {
+        self.as_frame().timestamp()
+    }
+
+    /// Returns the timestamp of this frame as a duration in the device's clock, see
+    /// [`Frame::clock_time`].
+    pub fn clock_time(&self) -> Duration {
+        self.as_frame().clock_time()
+    }
+
+    /// Returns `true` if the frame was made up to compensate for dropped events, see
+    /// [`Frame::is_synthetic`].
+    pub fn is_synthetic(&self) -> bool {
+        self.synthetic
+    }
+
+    /// Returns the events of this frame.
+    pub fn into_events(self) -> Vec<InputEvent> {
+        self.events
+    }
+}
+
+impl From<Frame<'_>> for OwnedFrame {
+    fn from(frame: Frame<'_>) -> Self {
+        OwnedFrame {
+            events: frame.events().to_vec(),
+            time: frame.time(),
+            synthetic: frame.is_synthetic(),
+        }
+    }
+}
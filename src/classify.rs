@@ -0,0 +1,461 @@
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Sub, SubAssign};
+
+use crate::constants::*;
+use crate::{AttributeSetRef, BusType, KeyCode};
+
+/// The kinds of input device that a device looks like, as returned by
+/// [`Device::classify`](crate::Device::classify).
+///
+/// A device can be several things at once, e.g. a wireless keyboard with a touchpad is often a
+/// single device that is both a [`KEYBOARD`](Self::KEYBOARD) and a
+/// [`TOUCHPAD`](Self::TOUCHPAD). The flags correspond to the `ID_INPUT_*` properties set by
+/// udev.
+///
+/// ```
+/// use evdev::DeviceClasses;
+///
+/// let classes = DeviceClasses::KEY | DeviceClasses::KEYBOARD;
+/// assert!(classes.contains(DeviceClasses::KEYBOARD));
+/// assert!(!classes.intersects(DeviceClasses::MOUSE | DeviceClasses::TOUCHPAD));
+/// assert_eq!(format!("{:?}", classes), "KEY | KEYBOARD");
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct DeviceClasses(u16);
+
+macro_rules! device_classes {
+    ($($(#[$attr:meta])* $c:ident = $val:expr,)*) => {
+        impl DeviceClasses {
+            $($(#[$attr])* pub const $c: Self = Self(1 << $val);)*
+
+            const NAME_MAP: &'static [(&'static str, DeviceClasses)] = &[
+                $((stringify!($c), Self::$c),)*
+            ];
+        }
+    };
+}
+
+device_classes!(
+    /// Has keys, e.g. a keyboard, a power button or a remote control (`ID_INPUT_KEY`).
+    KEY = 0,
+    /// Is a full keyboard (`ID_INPUT_KEYBOARD`).
+    KEYBOARD = 1,
+    /// Is a mouse, or something that behaves like one (`ID_INPUT_MOUSE`).
+    MOUSE = 2,
+    /// Is a pointing stick, the nub in the middle of some laptop keyboards
+    /// (`ID_INPUT_POINTINGSTICK`).
+    POINTING_STICK = 3,
+    /// Is a touchpad (`ID_INPUT_TOUCHPAD`).
+    TOUCHPAD = 4,
+    /// Is a touchscreen (`ID_INPUT_TOUCHSCREEN`).
+    TOUCHSCREEN = 5,
+    /// Is a joystick or gamepad (`ID_INPUT_JOYSTICK`).
+    JOYSTICK = 6,
+    /// Is a graphics tablet, or the stylus of a touchscreen (`ID_INPUT_TABLET`).
+    TABLET = 7,
+    /// Is the button pad of a graphics tablet (`ID_INPUT_TABLET_PAD`).
+    TABLET_PAD = 8,
+    /// Has switches, e.g. a laptop lid (`ID_INPUT_SWITCH`).
+    SWITCH = 9,
+    /// Is an accelerometer (`ID_INPUT_ACCELEROMETER`).
+    ACCELEROMETER = 10,
+);
+
+impl DeviceClasses {
+    /// Returns a set without any classes.
+    pub const fn empty() -> Self {
+        DeviceClasses(0)
+    }
+
+    /// Returns a set with all classes.
+    pub fn all() -> Self {
+        Self::NAME_MAP
+            .iter()
+            .fold(Self::empty(), |all, &(_, class)| all | class)
+    }
+
+    /// Returns `true` if there are no classes in the set.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if all classes in `other` are in this set.
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if any class in `other` is in this set.
+    pub const fn intersects(&self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Adds the classes in `other` to this set.
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Removes the classes in `other` from this set.
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    /// Adds or removes the classes in `other`, depending on `value`.
+    pub fn set(&mut self, other: Self, value: bool) {
+        if value {
+            self.insert(other);
+        } else {
+            self.remove(other);
+        }
+    }
+
+    /// Returns an iterator over the individual classes in this set.
+    pub fn iter(&self) -> impl Iterator<Item = DeviceClasses> {
+        let classes = *self;
+        Self::NAME_MAP
+            .iter()
+            .map(|&(_, class)| class)
+            .filter(move |&class| classes.contains(class))
+    }
+}
+
+impl BitOr for DeviceClasses {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        DeviceClasses(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for DeviceClasses {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for DeviceClasses {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self {
+        DeviceClasses(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for DeviceClasses {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl Sub for DeviceClasses {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        DeviceClasses(self.0 & !rhs.0)
+    }
+}
+
+impl SubAssign for DeviceClasses {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 &= !rhs.0;
+    }
+}
+
+impl FromIterator<DeviceClasses> for DeviceClasses {
+    fn from_iter<I: IntoIterator<Item = DeviceClasses>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(Self::empty(), |all, class| all | class)
+    }
+}
+
+impl std::str::FromStr for DeviceClasses {
+    type Err = crate::EnumParseError;
+
+    /// Parses the name of a single class, e.g. `"TOUCHPAD"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::NAME_MAP.iter().find(|e| e.0 == s) {
+            Some(e) => Ok(e.1),
+            None => Err(crate::EnumParseError(())),
+        }
+    }
+}
+
+impl fmt::Debug for DeviceClasses {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("(empty)");
+        }
+        let names = Self::NAME_MAP
+            .iter()
+            .filter(|&&(_, class)| self.contains(class))
+            .map(|&(name, _)| name);
+        for (i, name) in names.enumerate() {
+            if i > 0 {
+                f.write_str(" | ")?;
+            }
+            f.write_str(name)?;
+        }
+        Ok(())
+    }
+}
+
+/// The capabilities of a device that its classification is based on.
+pub(crate) struct Capabilities<'a> {
+    pub events: &'a AttributeSetRef<EventType>,
+    pub keys: Option<&'a AttributeSetRef<KeyCode>>,
+    pub relative_axes: Option<&'a AttributeSetRef<RelativeAxisCode>>,
+    pub absolute_axes: Option<&'a AttributeSetRef<AbsoluteAxisCode>>,
+    pub properties: &'a AttributeSetRef<PropType>,
+    pub bus_type: BusType,
+}
+
+impl Capabilities<'_> {
+    fn has_key(&self, key: KeyCode) -> bool {
+        self.keys.map_or(false, |keys| keys.contains(key))
+    }
+
+    fn count_keys(&self, range: std::ops::RangeInclusive<u16>) -> usize {
+        self.keys.map_or(0, |keys| {
+            keys.iter()
+                .filter(|key| range.contains(&key.code()))
+                .count()
+        })
+    }
+
+    fn has_rel(&self, axis: RelativeAxisCode) -> bool {
+        self.events.contains(EventType::RELATIVE)
+            && self.relative_axes.map_or(false, |axes| axes.contains(axis))
+    }
+
+    fn has_abs(&self, axis: AbsoluteAxisCode) -> bool {
+        self.absolute_axes.map_or(false, |axes| axes.contains(axis))
+    }
+}
+
+// BTN_MOUSE, BTN_JOYSTICK, BTN_MISC and BTN_DIGI from the kernel headers are aliases of these
+const BTN_MOUSE: KeyCode = KeyCode::BTN_LEFT;
+const BTN_JOYSTICK: KeyCode = KeyCode::BTN_TRIGGER;
+const BTN_MISC: KeyCode = KeyCode::BTN_0;
+const BTN_DIGI: KeyCode = KeyCode::BTN_TOOL_PEN;
+
+/// Classifies a device by its capabilities, following the rules of the `input_id` builtin of
+/// systemd's udev.
+pub(crate) fn classify(caps: &Capabilities) -> DeviceClasses {
+    let mut classes = classify_pointer(caps);
+    let is_pointer = !classes.is_empty();
+    classes |= classify_keys(caps);
+
+    // some devices only have a scroll wheel
+    let has_wheel =
+        caps.has_rel(RelativeAxisCode::REL_WHEEL) || caps.has_rel(RelativeAxisCode::REL_HWHEEL);
+    if !is_pointer && !classes.contains(DeviceClasses::KEY) && has_wheel {
+        classes |= DeviceClasses::KEY;
+    }
+    if caps.events.contains(EventType::SWITCH) {
+        classes |= DeviceClasses::SWITCH;
+    }
+    classes
+}
+
+fn classify_pointer(caps: &Capabilities) -> DeviceClasses {
+    let has_keys = caps.events.contains(EventType::KEY);
+    let has_abs_coordinates =
+        caps.has_abs(AbsoluteAxisCode::ABS_X) && caps.has_abs(AbsoluteAxisCode::ABS_Y);
+    let has_3d_coordinates = has_abs_coordinates && caps.has_abs(AbsoluteAxisCode::ABS_Z);
+
+    if caps.properties.contains(PropType::ACCELEROMETER) || (!has_keys && has_3d_coordinates) {
+        return DeviceClasses::ACCELEROMETER;
+    }
+
+    let mut is_pointing_stick = caps.properties.contains(PropType::POINTING_STICK);
+    let stylus_or_pen = caps.has_key(KeyCode::BTN_STYLUS) || caps.has_key(KeyCode::BTN_TOOL_PEN);
+    let finger_but_no_pen =
+        caps.has_key(KeyCode::BTN_TOOL_FINGER) && !caps.has_key(KeyCode::BTN_TOOL_PEN);
+    let has_mouse_button = caps.count_keys(BTN_MOUSE.0..=BTN_JOYSTICK.0 - 1) > 0;
+    let has_rel_coordinates =
+        caps.has_rel(RelativeAxisCode::REL_X) && caps.has_rel(RelativeAxisCode::REL_Y);
+    let mut has_mt_coordinates = caps.has_abs(AbsoluteAxisCode::ABS_MT_POSITION_X)
+        && caps.has_abs(AbsoluteAxisCode::ABS_MT_POSITION_Y);
+    // devices that claim to have every absolute axis don't really have multi-touch
+    if has_mt_coordinates
+        && caps.has_abs(AbsoluteAxisCode::ABS_MT_SLOT)
+        && caps.has_abs(AbsoluteAxisCode(AbsoluteAxisCode::ABS_MT_SLOT.0 - 1))
+    {
+        has_mt_coordinates = false;
+    }
+    let is_direct = caps.properties.contains(PropType::DIRECT);
+    let has_touch = caps.has_key(KeyCode::BTN_TOUCH);
+    let has_pad_buttons =
+        caps.has_key(KeyCode::BTN_0) && caps.has_key(KeyCode::BTN_1) && !has_rel_coordinates;
+    let has_wheel =
+        caps.has_rel(RelativeAxisCode::REL_WHEEL) || caps.has_rel(RelativeAxisCode::REL_HWHEEL);
+
+    // Mice with more than 16 buttons run into the joystick range, so their buttons don't count.
+    let num_joystick_buttons = if caps.has_key(KeyCode(BTN_JOYSTICK.0 - 1)) {
+        0
+    } else {
+        caps.count_keys(BTN_JOYSTICK.0..=BTN_DIGI.0 - 1)
+            + caps.count_keys(KeyCode::BTN_TRIGGER_HAPPY1.0..=KeyCode::BTN_TRIGGER_HAPPY40.0)
+            + caps.count_keys(KeyCode::BTN_DPAD_UP.0..=KeyCode::BTN_DPAD_RIGHT.0)
+    };
+    let num_joystick_axes = caps.absolute_axes.map_or(0, |axes| {
+        axes.iter()
+            .filter(|axis| {
+                (AbsoluteAxisCode::ABS_RX.0..AbsoluteAxisCode::ABS_PRESSURE.0).contains(&axis.0)
+            })
+            .count()
+    });
+    let has_joystick_controls = num_joystick_buttons > 0 || num_joystick_axes > 0;
+
+    let mut is_tablet = false;
+    let mut is_tablet_pad = false;
+    let mut is_touchpad = false;
+    let mut is_touchscreen = false;
+    let mut is_joystick = false;
+    let mut is_abs_mouse = false;
+
+    if has_abs_coordinates {
+        if stylus_or_pen {
+            is_tablet = true;
+        } else if finger_but_no_pen && !is_direct {
+            is_touchpad = true;
+        } else if has_mouse_button {
+            // e.g. the mouse of virtual machines, which has absolute axes but no touch button
+            is_abs_mouse = true;
+        } else if has_touch || is_direct {
+            is_touchscreen = true;
+        } else if has_joystick_controls {
+            is_joystick = true;
+        }
+    } else if has_joystick_controls {
+        is_joystick = true;
+    }
+
+    if has_mt_coordinates {
+        if stylus_or_pen {
+            is_tablet = true;
+        } else if finger_but_no_pen && !is_direct {
+            is_touchpad = true;
+        } else if has_touch || is_direct {
+            is_touchscreen = true;
+        }
+    }
+
+    if is_tablet && has_pad_buttons {
+        is_tablet_pad = true;
+    }
+    if has_pad_buttons && has_wheel && !has_rel_coordinates {
+        is_tablet = true;
+        is_tablet_pad = true;
+    }
+
+    // mouse buttons and relative axes, or mouse buttons and no axes at all
+    let is_mouse = !is_tablet
+        && !is_touchpad
+        && !is_joystick
+        && has_mouse_button
+        && (has_rel_coordinates || !has_abs_coordinates);
+
+    // there is no such thing as an I2C mouse
+    if is_mouse && caps.bus_type == BusType::BUS_I2C {
+        is_pointing_stick = true;
+    }
+
+    // Some keyboards have random joystick buttons. A joystick may have one of these
+    // (arbitrarily picked) keys but probably not several, and it has at least two controls.
+    if is_joystick {
+        const WELL_KNOWN_KEYBOARD_KEYS: [KeyCode; 10] = [
+            KeyCode::KEY_LEFTCTRL,
+            KeyCode::KEY_CAPSLOCK,
+            KeyCode::KEY_NUMLOCK,
+            KeyCode::KEY_INSERT,
+            KeyCode::KEY_MUTE,
+            KeyCode::KEY_CALC,
+            KeyCode::KEY_FILE,
+            KeyCode::KEY_MAIL,
+            KeyCode::KEY_PLAYPAUSE,
+            KeyCode::KEY_BRIGHTNESSDOWN,
+        ];
+        let num_well_known_keys = if has_keys {
+            WELL_KNOWN_KEYBOARD_KEYS
+                .iter()
+                .filter(|&&key| caps.has_key(key))
+                .count()
+        } else {
+            0
+        };
+        if num_well_known_keys >= 4 || num_joystick_buttons + num_joystick_axes < 2 {
+            is_joystick = false;
+        }
+        if has_wheel && has_pad_buttons {
+            is_joystick = false;
+        }
+    }
+
+    let mut classes = DeviceClasses::empty();
+    classes.set(DeviceClasses::POINTING_STICK, is_pointing_stick);
+    classes.set(DeviceClasses::MOUSE, is_mouse || is_abs_mouse);
+    classes.set(DeviceClasses::TOUCHPAD, is_touchpad);
+    classes.set(DeviceClasses::TOUCHSCREEN, is_touchscreen);
+    classes.set(DeviceClasses::JOYSTICK, is_joystick);
+    classes.set(DeviceClasses::TABLET, is_tablet);
+    classes.set(DeviceClasses::TABLET_PAD, is_tablet_pad);
+    classes
+}
+
+/// Returns `true` for the `KEY_*` codes, and `false` for the `BTN_*` ones.
+pub(crate) fn is_key(key: KeyCode) -> bool {
+    key.code() < BTN_MISC.code()
+        || (KeyCode::KEY_OK.code()..KeyCode::BTN_DPAD_UP.code()).contains(&key.code())
+        || (KeyCode::KEY_ALS_TOGGLE.code()..KeyCode::BTN_TRIGGER_HAPPY1.code())
+            .contains(&key.code())
+}
+
+fn classify_keys(caps: &Capabilities) -> DeviceClasses {
+    let keys = match caps.keys {
+        Some(keys) if caps.events.contains(EventType::KEY) => keys,
+        _ => return DeviceClasses::empty(),
+    };
+
+    // only KEY_* count here, not BTN_*
+    let has_key = keys.iter().any(is_key);
+    // ESC, the numbers and Q to D make a full keyboard
+    let is_keyboard = (1..32).all(|code| keys.contains(KeyCode(code)));
+
+    let mut classes = DeviceClasses::empty();
+    classes.set(DeviceClasses::KEY, has_key);
+    classes.set(DeviceClasses::KEYBOARD, is_keyboard);
+    classes
+}
+
+// serialized as a list of class names, e.g. `["KEY", "KEYBOARD"]`
+#[cfg(feature = "serde")]
+impl serde::Serialize for DeviceClasses {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        let names = Self::NAME_MAP
+            .iter()
+            .filter(|&&(_, class)| self.contains(class))
+            .map(|&(name, _)| name);
+        serializer.collect_seq(names)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DeviceClasses {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let names = Vec::<std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        names
+            .iter()
+            .map(|name| {
+                name.parse().map_err(|_| {
+                    serde::de::Error::invalid_value(
+                        serde::de::Unexpected::Str(name),
+                        &"the name of a device class",
+                    )
+                })
+            })
+            .collect()
+    }
+}
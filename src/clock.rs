@@ -0,0 +1,74 @@
+use std::time::{Duration, Instant};
+
+/// The clock the kernel uses to timestamp the events of a device.
+///
+/// Set with [`Device::set_clock`](crate::Device::set_clock). Devices start out using
+/// [`Clock::Realtime`], which is the only clock whose timestamps are meaningful as a
+/// [`SystemTime`](std::time::SystemTime).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Clock {
+    /// The wall clock, `CLOCK_REALTIME`. It may jump when the system time is adjusted.
+    #[default]
+    Realtime,
+    /// `CLOCK_MONOTONIC`, which never jumps but doesn't advance while the system is suspended.
+    ///
+    /// This is the clock [`Instant`] uses, see [`Clock::to_instant`].
+    Monotonic,
+    /// `CLOCK_BOOTTIME`, which is like [`Clock::Monotonic`] but includes time spent suspended.
+    Boottime,
+}
+
+impl Clock {
+    pub(crate) fn clockid(self) -> libc::clockid_t {
+        match self {
+            Clock::Realtime => libc::CLOCK_REALTIME,
+            Clock::Monotonic => libc::CLOCK_MONOTONIC,
+            Clock::Boottime => libc::CLOCK_BOOTTIME,
+        }
+    }
+
+    /// Returns the current time of this clock, as the duration since its epoch.
+    pub fn now(self) -> Duration {
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: ts is a valid timespec and all of our clock ids are supported
+        let ret = unsafe { libc::clock_gettime(self.clockid(), &mut ts) };
+        assert_eq!(ret, 0, "clock_gettime failed");
+        Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+    }
+
+    /// Converts a timestamp of this clock, e.g. from [`InputEvent::clock_time`], to an
+    /// [`Instant`].
+    ///
+    /// Returns `None` unless this is [`Clock::Monotonic`], since an `Instant` can't be compared
+    /// to the other clocks.
+    ///
+    /// [`InputEvent::clock_time`]: crate::InputEvent::clock_time
+    pub fn to_instant(self, time: Duration) -> Option<Instant> {
+        if self != Clock::Monotonic {
+            return None;
+        }
+        let instant_now = Instant::now();
+        let clock_now = self.now();
+        match clock_now.checked_sub(time) {
+            Some(elapsed) => instant_now.checked_sub(elapsed),
+            None => instant_now.checked_add(time - clock_now),
+        }
+    }
+}
+
+pub(crate) fn timeval_to_duration(tv: &libc::timeval) -> Duration {
+    if tv.tv_sec < 0 {
+        return Duration::ZERO;
+    }
+    Duration::new(tv.tv_sec as u64, tv.tv_usec as u32 * 1000)
+}
+
+pub(crate) fn duration_to_timeval(dur: Duration) -> libc::timeval {
+    libc::timeval {
+        tv_sec: dur.as_secs() as libc::time_t,
+        tv_usec: dur.subsec_micros() as libc::suseconds_t,
+    }
+}
@@ -191,6 +191,17 @@ evdev_enum!(
 
 impl AbsoluteAxisCode {
     pub(crate) const COUNT: usize = ABS_CNT;
+
+    /// The range of `ABS_MT_*` axes that carry one value per multi-touch slot.
+    pub(crate) const MT_SLOT_AXES: std::ops::RangeInclusive<u16> =
+        Self::ABS_MT_TOUCH_MAJOR.0..=Self::ABS_MT_TOOL_Y.0;
+
+    /// Returns `true` for `ABS_MT_*` axes whose values are tracked per slot, i.e. every
+    /// multi-touch axis except `ABS_MT_SLOT` itself.
+    #[inline]
+    pub(crate) fn is_mt_slot_axis(self) -> bool {
+        Self::MT_SLOT_AXES.contains(&self.0)
+    }
 }
 
 /// An event type corresponding to a physical or virtual switch.
@@ -0,0 +1,440 @@
+use std::io;
+use std::ops::Range;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd};
+use std::time::Duration;
+
+use nix::fcntl;
+use nix::sys::epoll::{Epoll, EpollCreateFlags, EpollEvent, EpollFlags, EpollTimeout};
+
+use crate::clock::duration_to_timeval;
+use crate::raw_stream::RawDevice;
+use crate::{Device, Frame, InputEvent};
+
+/// Identifies a device in a [`DeviceSet`].
+///
+/// Keys are not reused, so the key of a device that was removed doesn't refer to a device that
+/// is inserted later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DeviceKey {
+    index: u32,
+    generation: u32,
+}
+
+impl DeviceKey {
+    fn to_data(self) -> u64 {
+        (self.generation as u64) << 32 | self.index as u64
+    }
+
+    fn from_data(data: u64) -> Self {
+        DeviceKey {
+            index: data as u32,
+            generation: (data >> 32) as u32,
+        }
+    }
+}
+
+/// A device handle that can be put into a [`DeviceSet`], i.e. a [`Device`] or a [`RawDevice`].
+pub trait SetMember: AsFd + sealed::Sealed {}
+
+impl SetMember for Device {}
+impl SetMember for RawDevice {}
+
+mod sealed {
+    use super::*;
+
+    pub trait Sealed {
+        fn read_events(&mut self, f: &mut dyn FnMut(InputEvent)) -> io::Result<()>;
+        fn read_frames(&mut self, f: &mut dyn FnMut(Frame<'_>)) -> io::Result<()>;
+    }
+
+    impl Sealed for Device {
+        fn read_events(&mut self, f: &mut dyn FnMut(InputEvent)) -> io::Result<()> {
+            self.fetch_events()?.for_each(f);
+            Ok(())
+        }
+
+        fn read_frames(&mut self, f: &mut dyn FnMut(Frame<'_>)) -> io::Result<()> {
+            let mut frames = self.fetch_frames()?;
+            while let Some(frame) = frames.next_frame() {
+                f(frame);
+            }
+            Ok(())
+        }
+    }
+
+    impl Sealed for RawDevice {
+        fn read_events(&mut self, f: &mut dyn FnMut(InputEvent)) -> io::Result<()> {
+            self.fetch_events()?.for_each(f);
+            Ok(())
+        }
+
+        fn read_frames(&mut self, f: &mut dyn FnMut(Frame<'_>)) -> io::Result<()> {
+            let mut frames = self.fetch_frames()?;
+            while let Some(frame) = frames.next_frame() {
+                f(frame);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Something that happened to a device in a [`DeviceSet`], as returned by
+/// [`DeviceSet::fetch_events`].
+#[derive(Debug)]
+pub enum SetEvent<D> {
+    /// The device reported an event.
+    Event(DeviceKey, InputEvent),
+    /// Reading from the device failed. It stays in the set.
+    Error(DeviceKey, io::Error),
+    /// The device is gone, i.e. it was unplugged or revoked, and was removed from the set.
+    Removed(DeviceKey, D),
+}
+
+/// Something that happened to a device in a [`DeviceSet`], as returned by
+/// [`DeviceSet::fetch_frames`].
+#[derive(Debug)]
+pub enum SetFrame<'a, D> {
+    /// The device reported a frame of events.
+    Frame(DeviceKey, Frame<'a>),
+    /// Reading from the device failed. It stays in the set.
+    Error(DeviceKey, io::Error),
+    /// The device is gone, i.e. it was unplugged or revoked, and was removed from the set.
+    Removed(DeviceKey, D),
+}
+
+struct Slot<D> {
+    generation: u32,
+    device: Option<D>,
+}
+
+/// A frame that was copied out of a device, with its events in `DeviceSet::frame_events`.
+struct FrameRecord {
+    key: DeviceKey,
+    events: Range<usize>,
+    time: libc::timeval,
+    synthetic: bool,
+}
+
+/// Reads from many devices at once on a single thread.
+///
+/// The set owns its devices, which are either all [`Device`]s or all [`RawDevice`]s, and waits
+/// for any of them to have events with `epoll`. Devices that are gone, e.g. because they were
+/// unplugged, are removed from the set and handed back as [`SetEvent::Removed`]. Other errors
+/// from reading a device are reported as [`SetEvent::Error`] without affecting the other devices.
+///
+/// The set's own file descriptor becomes readable whenever one of its devices has events, so it
+/// can be added to a larger event loop. Call [`fetch_events`](Self::fetch_events) or
+/// [`fetch_frames`](Self::fetch_frames) with a timeout of [`Duration::ZERO`] once it's readable.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use evdev::{DeviceSet, SetEvent};
+///
+/// let mut set = DeviceSet::new()?;
+/// for (_, device) in evdev::enumerate() {
+///     set.insert(device)?;
+/// }
+/// loop {
+///     for event in set.fetch_events(None)? {
+///         match event {
+///             SetEvent::Event(key, event) => println!("{:?}: {:?}", key, event),
+///             SetEvent::Error(key, err) => eprintln!("{:?}: {}", key, err),
+///             SetEvent::Removed(_, device) => println!("{:?} is gone", device.name()),
+///         }
+///     }
+/// }
+/// # }
+/// ```
+pub struct DeviceSet<D: SetMember = Device> {
+    epoll: Epoll,
+    slots: Vec<Slot<D>>,
+    len: usize,
+    ready: Vec<EpollEvent>,
+    events: Vec<(DeviceKey, InputEvent)>,
+    frames: Vec<FrameRecord>,
+    frame_events: Vec<InputEvent>,
+    errors: Vec<(DeviceKey, io::Error)>,
+    removed: Vec<(DeviceKey, D)>,
+}
+
+impl<D: SetMember> DeviceSet<D> {
+    /// Creates an empty set.
+    pub fn new() -> io::Result<Self> {
+        Ok(DeviceSet {
+            epoll: Epoll::new(EpollCreateFlags::EPOLL_CLOEXEC)?,
+            slots: Vec::new(),
+            len: 0,
+            ready: Vec::new(),
+            events: Vec::new(),
+            frames: Vec::new(),
+            frame_events: Vec::new(),
+            errors: Vec::new(),
+            removed: Vec::new(),
+        })
+    }
+
+    /// Adds a device to the set and returns its key.
+    ///
+    /// This sets `O_NONBLOCK` on the device, which [`remove`](Self::remove) undoes. The device is
+    /// consumed even if this fails, in which case it's closed and the set is left unchanged.
+    pub fn insert(&mut self, device: D) -> io::Result<DeviceKey> {
+        let index = self
+            .slots
+            .iter()
+            .position(|slot| slot.device.is_none())
+            .unwrap_or(self.slots.len());
+        let key = DeviceKey {
+            index: index as u32,
+            generation: self.slots.get(index).map_or(0, |slot| slot.generation),
+        };
+
+        set_nonblocking(device.as_fd(), true)?;
+        self.epoll.add(
+            device.as_fd(),
+            EpollEvent::new(EpollFlags::EPOLLIN, key.to_data()),
+        )?;
+        if index == self.slots.len() {
+            self.slots.push(Slot {
+                generation: 0,
+                device: None,
+            });
+        }
+        self.slots[index].device = Some(device);
+        self.len += 1;
+        Ok(key)
+    }
+
+    /// Removes a device from the set and returns it, or `None` if it isn't in the set (anymore).
+    pub fn remove(&mut self, key: DeviceKey) -> Option<D> {
+        let device = self.take(key)?;
+        // the device is still usable on its own, as it was before it was inserted
+        let _ = set_nonblocking(device.as_fd(), false);
+        Some(device)
+    }
+
+    /// Returns a reference to the device with the given key.
+    pub fn get(&self, key: DeviceKey) -> Option<&D> {
+        let slot = self.slots.get(key.index as usize)?;
+        if slot.generation != key.generation {
+            return None;
+        }
+        slot.device.as_ref()
+    }
+
+    /// Returns a mutable reference to the device with the given key.
+    ///
+    /// Don't read events from the device directly while it's in the set.
+    pub fn get_mut(&mut self, key: DeviceKey) -> Option<&mut D> {
+        let slot = self.slots.get_mut(key.index as usize)?;
+        if slot.generation != key.generation {
+            return None;
+        }
+        slot.device.as_mut()
+    }
+
+    /// Returns the number of devices in the set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no devices in the set.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over the devices in the set and their keys.
+    pub fn iter(&self) -> impl Iterator<Item = (DeviceKey, &D)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let key = DeviceKey {
+                index: index as u32,
+                generation: slot.generation,
+            };
+            slot.device.as_ref().map(|device| (key, device))
+        })
+    }
+
+    /// Waits for events from any of the devices and returns them.
+    ///
+    /// With a `timeout` of `None`, this waits until there are events. Otherwise it returns no
+    /// events if none arrived within the timeout; with [`Duration::ZERO`] it doesn't wait at all.
+    pub fn fetch_events(&mut self, timeout: Option<Duration>) -> io::Result<FetchSetEvents<'_, D>> {
+        let mut events = std::mem::take(&mut self.events);
+        events.clear();
+        let res = self.read_ready(timeout, |key, device| {
+            device.read_events(&mut |event| events.push((key, event)))
+        });
+        self.events = events;
+        res?;
+        Ok(FetchSetEvents {
+            events: self.events.drain(..),
+            errors: self.errors.drain(..),
+            removed: self.removed.drain(..),
+        })
+    }
+
+    /// Waits for events from any of the devices and returns them grouped into [`Frame`]s.
+    ///
+    /// See [`fetch_events`](Self::fetch_events) for the `timeout`. Incomplete frames stay buffered
+    /// in their device until the rest of the frame arrives.
+    pub fn fetch_frames(&mut self, timeout: Option<Duration>) -> io::Result<FetchSetFrames<'_, D>> {
+        let mut frames = std::mem::take(&mut self.frames);
+        let mut frame_events = std::mem::take(&mut self.frame_events);
+        frames.clear();
+        frame_events.clear();
+        let res = self.read_ready(timeout, |key, device| {
+            device.read_frames(&mut |frame| {
+                let start = frame_events.len();
+                frame_events.extend_from_slice(frame.events());
+                frames.push(FrameRecord {
+                    key,
+                    events: start..frame_events.len(),
+                    time: duration_to_timeval(frame.clock_time()),
+                    synthetic: frame.is_synthetic(),
+                });
+            })
+        });
+        self.frames = frames;
+        self.frame_events = frame_events;
+        res?;
+        Ok(FetchSetFrames {
+            frames: self.frames.iter(),
+            frame_events: &self.frame_events,
+            errors: self.errors.drain(..),
+            removed: self.removed.drain(..),
+        })
+    }
+
+    /// Waits for devices to become readable and calls `read` for each of them. Devices that are
+    /// gone are moved to `removed`, and other errors are collected in `errors`.
+    fn read_ready(
+        &mut self,
+        timeout: Option<Duration>,
+        mut read: impl FnMut(DeviceKey, &mut D) -> io::Result<()>,
+    ) -> io::Result<()> {
+        self.errors.clear();
+        self.removed.clear();
+        self.ready.resize(self.len.max(1), EpollEvent::empty());
+        let n = match self.epoll.wait(&mut self.ready, to_epoll_timeout(timeout)) {
+            Ok(n) => n,
+            // interrupted by a signal, which is like a timeout to the caller
+            Err(nix::errno::Errno::EINTR) => 0,
+            Err(e) => return Err(e.into()),
+        };
+
+        for i in 0..n {
+            let key = DeviceKey::from_data(self.ready[i].data());
+            let device = match self.get_mut(key) {
+                Some(device) => device,
+                None => continue,
+            };
+            match read(key, device) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) if e.raw_os_error() == Some(libc::ENODEV) => {
+                    if let Some(device) = self.take(key) {
+                        self.removed.push((key, device));
+                    }
+                }
+                // the events read from the other devices must still be delivered
+                Err(e) => self.errors.push((key, e)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes a device from the set without touching its flags.
+    fn take(&mut self, key: DeviceKey) -> Option<D> {
+        let slot = self.slots.get_mut(key.index as usize)?;
+        if slot.generation != key.generation {
+            return None;
+        }
+        let device = slot.device.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.len -= 1;
+        let _ = self.epoll.delete(device.as_fd());
+        Some(device)
+    }
+}
+
+impl<D: SetMember> AsFd for DeviceSet<D> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.epoll.0.as_fd()
+    }
+}
+
+impl<D: SetMember> AsRawFd for DeviceSet<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.epoll.0.as_raw_fd()
+    }
+}
+
+/// An iterator over the events of a [`DeviceSet`].
+///
+/// This struct is returned from [`DeviceSet::fetch_events`]. Errors are reported after all the
+/// events, followed by the devices that were removed.
+pub struct FetchSetEvents<'a, D> {
+    events: std::vec::Drain<'a, (DeviceKey, InputEvent)>,
+    errors: std::vec::Drain<'a, (DeviceKey, io::Error)>,
+    removed: std::vec::Drain<'a, (DeviceKey, D)>,
+}
+
+impl<D> Iterator for FetchSetEvents<'_, D> {
+    type Item = SetEvent<D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((key, event)) = self.events.next() {
+            return Some(SetEvent::Event(key, event));
+        }
+        if let Some((key, err)) = self.errors.next() {
+            return Some(SetEvent::Error(key, err));
+        }
+        let (key, device) = self.removed.next()?;
+        Some(SetEvent::Removed(key, device))
+    }
+}
+
+/// An iterator over the frames of a [`DeviceSet`].
+///
+/// This struct is returned from [`DeviceSet::fetch_frames`]. Errors are reported after all the
+/// frames, followed by the devices that were removed.
+pub struct FetchSetFrames<'a, D> {
+    frames: std::slice::Iter<'a, FrameRecord>,
+    frame_events: &'a [InputEvent],
+    errors: std::vec::Drain<'a, (DeviceKey, io::Error)>,
+    removed: std::vec::Drain<'a, (DeviceKey, D)>,
+}
+
+impl<'a, D> Iterator for FetchSetFrames<'a, D> {
+    type Item = SetFrame<'a, D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(record) = self.frames.next() {
+            let events = &self.frame_events[record.events.clone()];
+            let frame = Frame::new(events, record.time, record.synthetic);
+            return Some(SetFrame::Frame(record.key, frame));
+        }
+        if let Some((key, err)) = self.errors.next() {
+            return Some(SetFrame::Error(key, err));
+        }
+        let (key, device) = self.removed.next()?;
+        Some(SetFrame::Removed(key, device))
+    }
+}
+
+fn set_nonblocking(fd: BorrowedFd<'_>, nonblocking: bool) -> io::Result<()> {
+    let mut flags = fcntl::OFlag::from_bits_retain(fcntl::fcntl(fd.as_raw_fd(), fcntl::F_GETFL)?);
+    flags.set(fcntl::OFlag::O_NONBLOCK, nonblocking);
+    fcntl::fcntl(fd.as_raw_fd(), fcntl::F_SETFL(flags))?;
+    Ok(())
+}
+
+/// Converts a timeout to milliseconds, rounding up so that short timeouts still wait.
+fn to_epoll_timeout(timeout: Option<Duration>) -> EpollTimeout {
+    match timeout {
+        Some(timeout) => {
+            let millis = (timeout.as_nanos() + 999_999) / 1_000_000;
+            EpollTimeout::try_from(millis).unwrap_or(EpollTimeout::MAX)
+        }
+        None => EpollTimeout::NONE,
+    }
+}
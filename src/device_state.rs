@@ -1,20 +1,28 @@
+use crate::clock::timeval_to_duration;
 use crate::compat::input_absinfo;
 use crate::{constants::*, raw_stream::RawDevice};
 use crate::{AttributeSet, AttributeSetRef, EventSummary, InputEvent, KeyCode};
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
+
+const MT_SLOT_AXES_COUNT: usize =
+    (AbsoluteAxisCode::ABS_MT_TOOL_Y.0 - AbsoluteAxisCode::ABS_MT_TOUCH_MAJOR.0 + 1) as usize;
 
 /// A **cached** representation of device state at a certain time.
 #[derive(Debug)]
 pub struct DeviceState {
-    /// The state corresponds to kernel state at this timestamp.
-    pub(crate) timestamp: SystemTime,
+    /// The state corresponds to kernel state at this timestamp, in the device's clock.
+    pub(crate) timestamp: libc::timeval,
     /// Set = key pressed
     pub(crate) key_vals: Option<AttributeSet<KeyCode>>,
     pub(crate) abs_vals: Option<Box<[input_absinfo; AbsoluteAxisCode::COUNT]>>,
+    /// Per-slot values of the `ABS_MT_*` axes, for devices with `ABS_MT_SLOT`
+    pub(crate) mt_vals: Option<MtSlots>,
     /// Set = switch enabled (closed)
     pub(crate) switch_vals: Option<AttributeSet<SwitchCode>>,
     /// Set = LED lit
     pub(crate) led_vals: Option<AttributeSet<LedCode>>,
+    /// Set = sound playing
+    pub(crate) snd_vals: Option<AttributeSet<SoundCode>>,
 }
 
 // manual Clone impl for clone_from optimization
@@ -24,16 +32,20 @@ impl Clone for DeviceState {
             timestamp: self.timestamp,
             key_vals: self.key_vals.clone(),
             abs_vals: self.abs_vals.clone(),
+            mt_vals: self.mt_vals.clone(),
             switch_vals: self.switch_vals.clone(),
             led_vals: self.led_vals.clone(),
+            snd_vals: self.snd_vals.clone(),
         }
     }
     fn clone_from(&mut self, other: &Self) {
         self.timestamp.clone_from(&other.timestamp);
         self.key_vals.clone_from(&other.key_vals);
         self.abs_vals.clone_from(&other.abs_vals);
+        self.mt_vals.clone_from(&other.mt_vals);
         self.switch_vals.clone_from(&other.switch_vals);
         self.led_vals.clone_from(&other.led_vals);
+        self.snd_vals.clone_from(&other.snd_vals);
     }
 }
 
@@ -53,6 +65,14 @@ impl DeviceState {
         } else {
             None
         };
+        let mt_vals = match device.supported_absolute_axes() {
+            Some(axes) if axes.contains(AbsoluteAxisCode::ABS_MT_SLOT) => device
+                .mt_slot_count()
+                .ok()
+                .filter(|&num_slots| num_slots > 0)
+                .map(MtSlots::new),
+            _ => None,
+        };
         let switch_vals = if supports.contains(EventType::SWITCH) {
             Some(AttributeSet::new())
         } else {
@@ -63,18 +83,37 @@ impl DeviceState {
         } else {
             None
         };
+        let snd_vals = if supports.contains(EventType::SOUND) {
+            Some(AttributeSet::new())
+        } else {
+            None
+        };
 
         DeviceState {
-            timestamp: std::time::UNIX_EPOCH,
+            timestamp: libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
             key_vals,
             abs_vals,
+            mt_vals,
             switch_vals,
             led_vals,
+            snd_vals,
         }
     }
     /// Returns the time when this snapshot was taken.
+    ///
+    /// Like [`InputEvent::timestamp`], this is only meaningful as a `SystemTime` if the device
+    /// uses [`Clock::Realtime`](crate::Clock::Realtime).
     pub fn timestamp(&self) -> SystemTime {
-        self.timestamp
+        crate::timeval_to_systime(&self.timestamp)
+    }
+
+    /// Returns the time when this snapshot was taken as the duration since the epoch of the
+    /// device's [`Clock`](crate::Clock).
+    pub fn clock_time(&self) -> Duration {
+        timeval_to_duration(&self.timestamp)
     }
 
     /// Returns the set of keys pressed when the snapshot was taken.
@@ -91,6 +130,14 @@ impl DeviceState {
         self.abs_vals.as_deref().map(|v| &v[..])
     }
 
+    /// Returns the per-slot values of the multi-touch axes when the snapshot was taken.
+    ///
+    /// Returns `None` if the device does not support `ABS_MT_SLOT`, i.e. it does not implement
+    /// the Type B multi-touch protocol.
+    pub fn mt_vals(&self) -> Option<&MtSlots> {
+        self.mt_vals.as_ref()
+    }
+
     /// Returns the set of switches triggered when the snapshot was taken.
     ///
     /// Returns `None` if switches are not supported by this device.
@@ -105,6 +152,13 @@ impl DeviceState {
         self.led_vals.as_deref()
     }
 
+    /// Returns the set of sounds playing when the snapshot was taken.
+    ///
+    /// Returns `None` if sounds are not supported by this device.
+    pub fn snd_vals(&self) -> Option<&AttributeSetRef<SoundCode>> {
+        self.snd_vals.as_deref()
+    }
+
     #[inline]
     pub(crate) fn process_event(&mut self, ev: InputEvent) {
         match ev.destructure() {
@@ -121,8 +175,156 @@ impl DeviceState {
                     .as_deref_mut()
                     .expect("got an abs event despite not supporting absolute axes");
                 axes[axis.0 as usize].value = ev.value();
+                if let Some(mt_vals) = self.mt_vals.as_mut() {
+                    mt_vals.apply(axis, ev.value());
+                }
+            }
+            EventSummary::Switch(_, code, _) => {
+                let switches = self
+                    .switch_vals
+                    .as_deref_mut()
+                    .expect("got a switch event despite not supporting switches");
+                switches.set(code, ev.value() != 0);
+            }
+            EventSummary::Led(_, code, _) => {
+                let leds = self
+                    .led_vals
+                    .as_deref_mut()
+                    .expect("got an LED event despite not supporting LEDs");
+                leds.set(code, ev.value() != 0);
+            }
+            EventSummary::Sound(_, code, _) => {
+                let sounds = self
+                    .snd_vals
+                    .as_deref_mut()
+                    .expect("got a sound event despite not supporting sounds");
+                sounds.set(code, ev.value() != 0);
             }
             _ => {}
         }
     }
 }
+
+/// The per-slot values of the `ABS_MT_*` axes of a Type B multi-touch device.
+///
+/// Type B devices report each contact in its own slot, selected with `ABS_MT_SLOT`. All other
+/// `ABS_MT_*` events apply to the currently selected slot only. A slot is in use while its
+/// `ABS_MT_TRACKING_ID` is not `-1`.
+///
+/// See <https://www.kernel.org/doc/Documentation/input/multi-touch-protocol.txt>.
+#[derive(Debug)]
+pub struct MtSlots {
+    /// The slot that `ABS_MT_*` events currently apply to.
+    current: usize,
+    num_slots: usize,
+    /// One run of `num_slots` values per `ABS_MT_*` axis, in axis code order.
+    values: Box<[i32]>,
+}
+
+// manual Clone impl for clone_from optimization
+impl Clone for MtSlots {
+    fn clone(&self) -> Self {
+        Self {
+            current: self.current,
+            num_slots: self.num_slots,
+            values: self.values.clone(),
+        }
+    }
+    fn clone_from(&mut self, other: &Self) {
+        self.current = other.current;
+        self.num_slots = other.num_slots;
+        if self.values.len() == other.values.len() {
+            self.values.copy_from_slice(&other.values);
+        } else {
+            self.values = other.values.clone();
+        }
+    }
+}
+
+impl MtSlots {
+    /// Create the state of `num_slots` unused slots.
+    pub(crate) fn new(num_slots: usize) -> Self {
+        let mut slots = MtSlots {
+            current: 0,
+            num_slots,
+            values: vec![0; num_slots * MT_SLOT_AXES_COUNT].into_boxed_slice(),
+        };
+        slots
+            .axis_vals_mut(AbsoluteAxisCode::ABS_MT_TRACKING_ID)
+            .fill(-1);
+        slots
+    }
+
+    /// Returns the number of slots the device supports, i.e. the maximum number of
+    /// simultaneous contacts.
+    pub fn num_slots(&self) -> usize {
+        self.num_slots
+    }
+
+    /// Returns the slot that `ABS_MT_*` events currently apply to.
+    pub fn current_slot(&self) -> usize {
+        self.current
+    }
+
+    /// Returns the value of `axis` in `slot`.
+    ///
+    /// Returns `None` if `slot` is out of range or `axis` is not a per-slot `ABS_MT_*` axis.
+    pub fn get(&self, slot: usize, axis: AbsoluteAxisCode) -> Option<i32> {
+        if !axis.is_mt_slot_axis() {
+            return None;
+        }
+        self.axis_vals(axis).get(slot).copied()
+    }
+
+    /// Returns the tracking ID of the contact in `slot`, or `None` if the slot is unused.
+    pub fn tracking_id(&self, slot: usize) -> Option<i32> {
+        self.get(slot, AbsoluteAxisCode::ABS_MT_TRACKING_ID)
+            .filter(|&id| id != -1)
+    }
+
+    /// Returns an iterator over the slots that currently hold a contact.
+    pub fn active_slots(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.num_slots).filter(move |&slot| self.tracking_id(slot).is_some())
+    }
+
+    /// Select the slot that following `ABS_MT_*` values apply to. Out-of-range slots are ignored,
+    /// just like the kernel does.
+    #[inline]
+    pub(crate) fn set_current_slot(&mut self, slot: usize) {
+        if slot < self.num_slots {
+            self.current = slot;
+        }
+    }
+
+    /// Applies a value of an `ABS_MT_*` axis, where `ABS_MT_SLOT` selects the slot that the
+    /// other axes apply to. Values of other axes are ignored.
+    pub(crate) fn apply(&mut self, axis: AbsoluteAxisCode, value: i32) {
+        if axis == AbsoluteAxisCode::ABS_MT_SLOT {
+            if let Ok(slot) = usize::try_from(value) {
+                self.set_current_slot(slot);
+            }
+        } else if axis.is_mt_slot_axis() {
+            let slot = self.current;
+            self.axis_vals_mut(axis)[slot] = value;
+        }
+    }
+
+    #[inline]
+    fn axis_range(&self, axis: AbsoluteAxisCode) -> std::ops::Range<usize> {
+        debug_assert!(axis.is_mt_slot_axis());
+        let start = (axis.0 - AbsoluteAxisCode::ABS_MT_TOUCH_MAJOR.0) as usize * self.num_slots;
+        start..start + self.num_slots
+    }
+
+    #[inline]
+    fn axis_vals(&self, axis: AbsoluteAxisCode) -> &[i32] {
+        &self.values[self.axis_range(axis)]
+    }
+
+    /// The values of `axis` for every slot, e.g. to be filled in by `EVIOCGMTSLOTS`.
+    #[inline]
+    pub(crate) fn axis_vals_mut(&mut self, axis: AbsoluteAxisCode) -> &mut [i32] {
+        let range = self.axis_range(axis);
+        &mut self.values[range]
+    }
+}
@@ -0,0 +1,216 @@
+use crate::attribute_set::ArrayedEvdevEnum;
+use crate::constants::*;
+use crate::{AttributeSet, AttributeSetRef, KeyCode};
+
+/// Selects which events the kernel delivers to a device handle.
+///
+/// Masked events are dropped by the kernel before they reach the handle's buffer, so ignoring
+/// them costs nothing. `SYN_*` events are never masked. Apply a mask with
+/// [`Device::set_event_mask`](crate::Device::set_event_mask) and read it back with
+/// [`Device::get_event_mask`](crate::Device::get_event_mask).
+///
+/// ```
+/// use evdev::{AttributeSet, EventMask, EventType, KeyCode};
+///
+/// // only deliver the escape key and relative motion
+/// let keys = AttributeSet::from_iter([KeyCode::KEY_ESC]);
+/// let mask = EventMask::none()
+///     .with_keys(&keys)
+///     .with_type(EventType::RELATIVE);
+///
+/// assert!(mask.allows(EventType::KEY, KeyCode::KEY_ESC.0));
+/// assert!(!mask.allows(EventType::KEY, KeyCode::KEY_A.0));
+/// assert!(mask.allows(EventType::RELATIVE, 0));
+/// assert!(!mask.allows(EventType::ABSOLUTE, 0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventMask {
+    types: AttributeSet<EventType>,
+    // for each of these, None means that all codes of the type are allowed
+    keys: Option<AttributeSet<KeyCode>>,
+    relative_axes: Option<AttributeSet<RelativeAxisCode>>,
+    absolute_axes: Option<AttributeSet<AbsoluteAxisCode>>,
+    misc: Option<AttributeSet<MiscCode>>,
+    switches: Option<AttributeSet<SwitchCode>>,
+    leds: Option<AttributeSet<LedCode>>,
+    sounds: Option<AttributeSet<SoundCode>>,
+    ff: Option<AttributeSet<FFEffectCode>>,
+}
+
+/// Calls `$m!(field, EventType, Code, with_fn, accessor_fn)` for every event type whose codes can
+/// be masked individually.
+macro_rules! for_each_code_mask {
+    ($m:ident) => {
+        $m!(keys, KEY, KeyCode, with_keys, keys);
+        $m!(
+            relative_axes,
+            RELATIVE,
+            RelativeAxisCode,
+            with_relative_axes,
+            relative_axes
+        );
+        $m!(
+            absolute_axes,
+            ABSOLUTE,
+            AbsoluteAxisCode,
+            with_absolute_axes,
+            absolute_axes
+        );
+        $m!(misc, MISC, MiscCode, with_msc, misc);
+        $m!(switches, SWITCH, SwitchCode, with_switches, switches);
+        $m!(leds, LED, LedCode, with_leds, leds);
+        $m!(sounds, SOUND, SoundCode, with_sounds, sounds);
+        $m!(ff, FORCEFEEDBACK, FFEffectCode, with_ff, ff);
+    };
+}
+
+macro_rules! code_mask_methods {
+    ($field:ident, $evtype:ident, $code:ident, $with:ident, $get:ident) => {
+        #[doc = concat!("Allows `EventType::", stringify!($evtype), "` events, but only with")]
+        /// the given codes.
+        pub fn $with(mut self, codes: &AttributeSetRef<$code>) -> Self {
+            self.types.insert(EventType::$evtype);
+            self.$field = Some(codes.iter().collect());
+            self
+        }
+
+        #[doc = concat!("Returns the codes of `EventType::", stringify!($evtype), "` events")]
+        /// that are allowed, or `None` if all of them are.
+        ///
+        /// Whether the event type itself is allowed is reported by [`types`](Self::types).
+        pub fn $get(&self) -> Option<&AttributeSetRef<$code>> {
+            self.$field.as_deref()
+        }
+    };
+}
+
+impl EventMask {
+    /// Creates a mask that blocks all events, except for the `SYN_*` events.
+    pub fn none() -> Self {
+        EventMask {
+            types: AttributeSet::new(),
+            keys: None,
+            relative_axes: None,
+            absolute_axes: None,
+            misc: None,
+            switches: None,
+            leds: None,
+            sounds: None,
+            ff: None,
+        }
+    }
+
+    /// Creates a mask that allows all events, which is what a device handle starts out with.
+    pub fn all() -> Self {
+        let mut mask = Self::none();
+        for i in 0..EventType::COUNT {
+            mask.types.insert(EventType(i as u16));
+        }
+        mask
+    }
+
+    /// Allows all events of the given type.
+    pub fn with_type(mut self, event_type: EventType) -> Self {
+        self.types.insert(event_type);
+        macro_rules! reset {
+            ($field:ident, $evtype:ident, $($_:tt)*) => {
+                if event_type == EventType::$evtype {
+                    self.$field = None;
+                }
+            };
+        }
+        for_each_code_mask!(reset);
+        self
+    }
+
+    /// Blocks all events of the given type.
+    pub fn without_type(mut self, event_type: EventType) -> Self {
+        self.types.remove(event_type);
+        self
+    }
+
+    for_each_code_mask!(code_mask_methods);
+
+    /// Returns the event types that are allowed.
+    pub fn types(&self) -> &AttributeSetRef<EventType> {
+        &self.types
+    }
+
+    /// Returns `true` if an event with the given type and code passes this mask.
+    pub fn allows(&self, event_type: EventType, code: u16) -> bool {
+        // same rules as the kernel: SYN_* and unknown types or codes are never masked
+        if event_type == EventType::SYNCHRONIZATION || event_type.0 as usize >= EventType::COUNT {
+            return true;
+        }
+        if !self.types.contains(event_type) {
+            return false;
+        }
+        macro_rules! check_code {
+            ($field:ident, $evtype:ident, $code:ident, $($_:tt)*) => {
+                if event_type == EventType::$evtype {
+                    return match &self.$field {
+                        Some(codes) if (code as usize) < $code::COUNT => {
+                            codes.contains($code(code))
+                        }
+                        _ => true,
+                    };
+                }
+            };
+        }
+        for_each_code_mask!(check_code);
+        true
+    }
+
+    /// Calls `f` with the raw bitmask for the event types (as type 0) and then for each event type
+    /// whose codes can be masked, in the form `EVIOCSMASK` expects.
+    pub(crate) fn for_each_raw(
+        &self,
+        mut f: impl FnMut(u16, &[u8]) -> std::io::Result<()>,
+    ) -> std::io::Result<()> {
+        f(0, self.types.as_raw_slice())?;
+        macro_rules! raw_codes {
+            ($field:ident, $evtype:ident, $code:ident, $($_:tt)*) => {
+                match &self.$field {
+                    Some(codes) => f(EventType::$evtype.0, codes.as_raw_slice())?,
+                    None => f(EventType::$evtype.0, &all_codes::<$code>())?,
+                }
+            };
+        }
+        for_each_code_mask!(raw_codes);
+        Ok(())
+    }
+
+    /// Builds a mask from the raw bitmasks read with `EVIOCGMASK`. `read` is called with the
+    /// event type (0 for the event types themselves) and a zeroed buffer to fill in.
+    pub(crate) fn from_raw(
+        mut read: impl FnMut(u16, &mut [u8]) -> std::io::Result<()>,
+    ) -> std::io::Result<Self> {
+        fn read_codes<T: ArrayedEvdevEnum>(
+            read: &mut impl FnMut(u16, &mut [u8]) -> std::io::Result<()>,
+            event_type: EventType,
+        ) -> std::io::Result<Option<AttributeSet<T>>> {
+            let mut codes = AttributeSet::<T>::new();
+            read(event_type.0, codes.as_mut_raw_slice())?;
+            // the kernel reports all bits set if the codes aren't masked
+            let all_set = codes.as_raw_slice().iter().all(|&b| b == 0xff);
+            Ok(if all_set { None } else { Some(codes) })
+        }
+
+        let mut mask = Self::none();
+        read(0, mask.types.as_mut_raw_slice())?;
+        macro_rules! raw_codes {
+            ($field:ident, $evtype:ident, $($_:tt)*) => {
+                mask.$field = read_codes(&mut read, EventType::$evtype)?;
+            };
+        }
+        for_each_code_mask!(raw_codes);
+        Ok(mask)
+    }
+}
+
+/// A bitmask with every code of `T` set.
+fn all_codes<T: ArrayedEvdevEnum>() -> Vec<u8> {
+    let mut codes = AttributeSet::<T>::new();
+    codes.as_mut_raw_slice().fill(0xff);
+    codes.as_raw_slice().to_vec()
+}
@@ -0,0 +1,323 @@
+use std::io;
+use std::path::PathBuf;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::classify::{classify, Capabilities};
+use crate::constants::*;
+use crate::raw_stream::is_event_node;
+use crate::{BusType, Device, DeviceClasses, InputId, KeyCode};
+
+/// A description of the devices to look for, to be used with [`enumerate_matching`].
+///
+/// An empty filter matches every device, and each criterion that is added narrows it down. With
+/// the `serde` feature, filters can be (de)serialized, so that they can be put into configuration
+/// files.
+///
+/// Names, physical paths and unique names are matched as globs, where `*` matches any number of
+/// characters and `?` matches exactly one.
+///
+/// ```
+/// use evdev::{DeviceClasses, DeviceFilter, RelativeAxisCode};
+///
+/// // the Logitech mouse with a horizontal scroll wheel
+/// let filter = DeviceFilter::new()
+///     .name("Logitech*")
+///     .vendor(0x046d)
+///     .with_class(DeviceClasses::MOUSE)
+///     .with_relative_axis(RelativeAxisCode::REL_HWHEEL);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DeviceFilter {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    phys: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    uniq: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    bus_type: Option<BusType>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    vendor: Option<u16>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    product: Option<u16>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    event_types: Vec<EventType>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    keys: Vec<KeyCode>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    relative_axes: Vec<RelativeAxisCode>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    absolute_axes: Vec<AbsoluteAxisCode>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "DeviceClasses::is_empty")
+    )]
+    classes: DeviceClasses,
+}
+
+/// What a device looks like to a [`DeviceFilter`].
+pub(crate) struct DeviceInfo<'a> {
+    pub name: Option<&'a str>,
+    pub phys: Option<&'a str>,
+    pub uniq: Option<&'a str>,
+    pub input_id: InputId,
+    pub caps: Capabilities<'a>,
+}
+
+impl DeviceFilter {
+    /// Creates a filter that matches every device.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only matches devices whose name matches the glob `pattern`.
+    pub fn name(mut self, pattern: impl Into<String>) -> Self {
+        self.name = Some(pattern.into());
+        self
+    }
+
+    /// Only matches devices whose physical path matches the glob `pattern`.
+    pub fn physical_path(mut self, pattern: impl Into<String>) -> Self {
+        self.phys = Some(pattern.into());
+        self
+    }
+
+    /// Only matches devices whose unique name matches the glob `pattern`.
+    pub fn unique_name(mut self, pattern: impl Into<String>) -> Self {
+        self.uniq = Some(pattern.into());
+        self
+    }
+
+    /// Only matches devices connected through the given bus.
+    pub fn bus_type(mut self, bus_type: BusType) -> Self {
+        self.bus_type = Some(bus_type);
+        self
+    }
+
+    /// Only matches devices with the given vendor id.
+    pub fn vendor(mut self, vendor: u16) -> Self {
+        self.vendor = Some(vendor);
+        self
+    }
+
+    /// Only matches devices with the given product id.
+    pub fn product(mut self, product: u16) -> Self {
+        self.product = Some(product);
+        self
+    }
+
+    /// Only matches devices that support the given event type.
+    pub fn with_event_type(mut self, event_type: EventType) -> Self {
+        self.event_types.push(event_type);
+        self
+    }
+
+    /// Only matches devices that have the given key or button.
+    pub fn with_key(mut self, key: KeyCode) -> Self {
+        self.keys.push(key);
+        self
+    }
+
+    /// Only matches devices that have the given relative axis.
+    pub fn with_relative_axis(mut self, axis: RelativeAxisCode) -> Self {
+        self.relative_axes.push(axis);
+        self
+    }
+
+    /// Only matches devices that have the given absolute axis.
+    pub fn with_absolute_axis(mut self, axis: AbsoluteAxisCode) -> Self {
+        self.absolute_axes.push(axis);
+        self
+    }
+
+    /// Only matches devices that are classified as all of the given classes, see
+    /// [`Device::classify`].
+    pub fn with_class(mut self, classes: DeviceClasses) -> Self {
+        self.classes |= classes;
+        self
+    }
+
+    /// Returns `true` if `device` matches this filter.
+    pub fn matches(&self, device: &Device) -> bool {
+        self.matches_info(&DeviceInfo {
+            name: device.name(),
+            phys: device.physical_path(),
+            uniq: device.unique_name(),
+            input_id: device.input_id(),
+            caps: Capabilities {
+                events: device.supported_events(),
+                keys: device.supported_keys(),
+                relative_axes: device.supported_relative_axes(),
+                absolute_axes: device.supported_absolute_axes(),
+                properties: device.properties(),
+                bus_type: device.input_id().bus_type(),
+            },
+        })
+    }
+
+    pub(crate) fn matches_info(&self, info: &DeviceInfo) -> bool {
+        fn glob_matches(pattern: &Option<String>, value: Option<&str>) -> bool {
+            match pattern {
+                Some(pattern) => glob_match(pattern.as_bytes(), value.unwrap_or("").as_bytes()),
+                None => true,
+            }
+        }
+        fn has_all<T: Copy>(required: &[T], has: impl Fn(T) -> bool) -> bool {
+            required.iter().all(|&code| has(code))
+        }
+
+        let caps = &info.caps;
+        glob_matches(&self.name, info.name)
+            && glob_matches(&self.phys, info.phys)
+            && glob_matches(&self.uniq, info.uniq)
+            && self
+                .bus_type
+                .map_or(true, |bus| bus == info.input_id.bus_type())
+            && self.vendor.map_or(true, |id| id == info.input_id.vendor())
+            && self
+                .product
+                .map_or(true, |id| id == info.input_id.product())
+            && has_all(&self.event_types, |ty| caps.events.contains(ty))
+            && has_all(&self.keys, |key| {
+                caps.keys.map_or(false, |s| s.contains(key))
+            })
+            && has_all(&self.relative_axes, |axis| {
+                caps.relative_axes.map_or(false, |s| s.contains(axis))
+            })
+            && has_all(&self.absolute_axes, |axis| {
+                caps.absolute_axes.map_or(false, |s| s.contains(axis))
+            })
+            && (self.classes.is_empty() || classify(caps).contains(self.classes))
+    }
+
+    /// Checks the device node at `path` against this filter with what sysfs says about it,
+    /// without opening it.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn matches_sysfs(&self, path: &std::path::Path) -> io::Result<bool> {
+        let info = crate::SysfsInfo::from_path(path)?;
+        let name = info.name()?;
+        let phys = info.physical_path()?;
+        let uniq = info.unique_name()?;
+        let input_id = info.input_id()?;
+        let events = info.capabilities_ev()?;
+        let keys = info.capabilities_key()?;
+        let relative_axes = info.capabilities_rel()?;
+        let absolute_axes = info.capabilities_abs()?;
+        let properties = info.properties()?;
+        Ok(self.matches_info(&DeviceInfo {
+            name: name.as_deref(),
+            phys: phys.as_deref(),
+            uniq: uniq.as_deref(),
+            caps: Capabilities {
+                events: &events,
+                keys: Some(&keys),
+                relative_axes: Some(&relative_axes),
+                absolute_axes: Some(&absolute_axes),
+                properties: &properties,
+                bus_type: input_id.bus_type(),
+            },
+            input_id,
+        }))
+    }
+}
+
+/// Matches `value` against a glob `pattern` with `*` and `?` wildcards.
+pub(crate) fn glob_match(pattern: &[u8], value: &[u8]) -> bool {
+    // where to resume if what follows the last `*` doesn't match
+    let mut backtrack = None;
+    let (mut p, mut v) = (0, 0);
+    while v < value.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, v));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == value[v] => {
+                p += 1;
+                v += 1;
+            }
+            _ => match backtrack {
+                // let the `*` eat one more character
+                Some((star, consumed)) => {
+                    backtrack = Some((star, consumed + 1));
+                    p = star + 1;
+                    v = consumed + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Crawls `/dev/input` for evdev devices that match `filter`.
+///
+/// Unlike [`enumerate`](crate::enumerate), this reports the devices that match but can't be
+/// opened, e.g. because of missing permissions, along with the error. On Linux, devices are
+/// matched against what sysfs says about them before they are opened, so only devices that are
+/// of interest are opened at all. Errors traversing the directory still result in an empty
+/// iterator.
+///
+/// ```no_run
+/// use evdev::{DeviceClasses, DeviceFilter};
+///
+/// let filter = DeviceFilter::new().with_class(DeviceClasses::KEYBOARD);
+/// for (path, device) in evdev::enumerate_matching(&filter) {
+///     match device {
+///         Ok(device) => println!("{}: {:?}", path.display(), device.name()),
+///         Err(e) => eprintln!("{}: {}", path.display(), e),
+///     }
+/// }
+/// ```
+pub fn enumerate_matching(filter: &DeviceFilter) -> EnumerateMatching {
+    EnumerateMatching {
+        readdir: std::fs::read_dir("/dev/input").ok(),
+        filter: filter.clone(),
+    }
+}
+
+/// An iterator over the currently connected evdev devices that match a [`DeviceFilter`].
+///
+/// This struct is returned from the [`enumerate_matching`] function.
+pub struct EnumerateMatching {
+    readdir: Option<std::fs::ReadDir>,
+    filter: DeviceFilter,
+}
+
+impl Iterator for EnumerateMatching {
+    type Item = (PathBuf, io::Result<Device>);
+    fn next(&mut self) -> Option<Self::Item> {
+        let readdir = self.readdir.as_mut()?;
+        loop {
+            let path = match readdir.next()? {
+                Ok(entry) => entry.path(),
+                Err(_) => continue,
+            };
+            if !is_event_node(&path) {
+                continue;
+            }
+
+            // without sysfs, devices have to be opened to tell whether they match
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            let known_match = match self.filter.matches_sysfs(&path) {
+                Ok(false) => continue,
+                Ok(true) => true,
+                Err(_) => false,
+            };
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            let known_match = false;
+
+            match Device::open(&path) {
+                // check again, the node might belong to a different device by now
+                Ok(device) if self.filter.matches(&device) => return Some((path, Ok(device))),
+                Ok(_) => {}
+                Err(e) if known_match => return Some((path, Err(e))),
+                Err(_) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::clock::timeval_to_duration;
+use crate::compat::input_event;
+use crate::{timeval_to_systime, EventType, InputEvent, SynchronizationCode};
+use std::time::{Duration, SystemTime};
+
+/// A group of events that the device reported at once, i.e. all the events between two
+/// `SYN_REPORT`s.
+///
+/// For example a mouse moving diagonally reports a `REL_X` and a `REL_Y` event in the same frame,
+/// and a multi-touch device reports all the contacts that changed in the same frame. The
+/// terminating `SYN_REPORT` itself is not part of [`events`](Self::events), but its timestamp is
+/// available through [`timestamp`](Self::timestamp).
+///
+/// Frames are produced by [`Device::fetch_frames`](crate::Device::fetch_frames) and
+/// [`RawDevice::fetch_frames`](crate::raw_stream::RawDevice::fetch_frames) and borrow from the
+/// device's internal buffer.
+#[derive(Debug, Clone, Copy)]
+pub struct Frame<'a> {
+    events: &'a [InputEvent],
+    time: libc::timeval,
+    synthetic: bool,
+}
+
+impl<'a> Frame<'a> {
+    #[inline]
+    pub(crate) fn new(events: &'a [InputEvent], time: libc::timeval, synthetic: bool) -> Self {
+        Frame {
+            events,
+            time,
+            synthetic,
+        }
+    }
+
+    /// Creates a frame from a block of raw events that ends with its `SYN_REPORT`.
+    #[inline]
+    pub(crate) fn from_block(block: &'a [input_event], synthetic: bool) -> Self {
+        let (report, events) = block
+            .split_last()
+            .expect("a block always ends with a SYN_REPORT");
+        // SAFETY: InputEvent is repr(transparent) over input_event
+        let events = unsafe {
+            std::slice::from_raw_parts(events.as_ptr() as *const InputEvent, events.len())
+        };
+        Frame::new(events, report.time, synthetic)
+    }
+
+    /// Returns the events of this frame, without the terminating `SYN_REPORT`.
+    #[inline]
+    pub fn events(&self) -> &'a [InputEvent] {
+        self.events
+    }
+
+    /// Returns an iterator over the events of this frame.
+    #[inline]
+    pub fn iter(&self) -> std::iter::Copied<std::slice::Iter<'a, InputEvent>> {
+        self.events.iter().copied()
+    }
+
+    /// Returns the number of events in this frame.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if this frame only consists of its `SYN_REPORT`.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns the timestamp of the `SYN_REPORT` that terminated this frame.
+    #[inline]
+    pub fn timestamp(&self) -> SystemTime {
+        timeval_to_systime(&self.time)
+    }
+
+    /// Returns the timestamp of the `SYN_REPORT` that terminated this frame as the duration since
+    /// the epoch of the device's [`Clock`](crate::Clock).
+    #[inline]
+    pub fn clock_time(&self) -> Duration {
+        timeval_to_duration(&self.time)
+    }
+
+    #[inline]
+    pub(crate) fn time(&self) -> libc::timeval {
+        self.time
+    }
+
+    /// Returns `true` if this frame was not read from the device, but synthesized to bring the
+    /// consumer back in sync with the device after the kernel dropped events.
+    ///
+    /// See the crate documentation on synchronizing versus raw modes.
+    #[inline]
+    pub fn is_synthetic(&self) -> bool {
+        self.synthetic
+    }
+}
+
+impl<'a> IntoIterator for Frame<'a> {
+    type Item = InputEvent;
+    type IntoIter = std::iter::Copied<std::slice::Iter<'a, InputEvent>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[inline]
+pub(crate) fn is_syn_report(ev: &input_event) -> bool {
+    ev.type_ == EventType::SYNCHRONIZATION.0 && ev.code == SynchronizationCode::SYN_REPORT.0
+}
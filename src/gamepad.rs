@@ -0,0 +1,935 @@
+//! Gamepads with standardized buttons and axes.
+//!
+//! Every gamepad driver reports a different mix of `BTN_*` and `ABS_*` codes: the bottom face
+//! button may be `BTN_SOUTH` on one pad and `BTN_THUMB2` on another, and the d-pad may be a hat
+//! or four buttons. A [`Gamepad`] hides that behind the layout of an Xbox controller, with
+//! [`GamepadButton`]s named by their position and [`GamepadAxis`]es normalized to [-1, 1] for
+//! the sticks and [0, 1] for the triggers.
+//!
+//! How the codes of a device map to that layout is looked up in a [`MappingDb`], which reads
+//! the mapping strings of SDL's `gamecontrollerdb.txt`, the community database that SDL games
+//! use. Mappings are found by the [`GamepadGuid`] SDL derives from a device's [`InputId`] and
+//! refer to the buttons, axes and hats of the device by SDL's numbering of its codes, so that
+//! they work the same here as in SDL. Devices without a mapping that follow the kernel's
+//! [gamepad specification](https://docs.kernel.org/input/gamepad.html) use that layout instead.
+//!
+//! ```no_run
+//! use evdev::gamepad::{Gamepad, GamepadButton, GamepadEvent, MappingDb};
+//! use evdev::Device;
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let db = MappingDb::load("gamecontrollerdb.txt")?;
+//! let mut device = Device::open("/dev/input/event0")?;
+//! let mut gamepad = Gamepad::new(&device, &db)?.deadzone(0.1);
+//! println!("{} ({})", gamepad.name(), gamepad.guid());
+//! loop {
+//!     let mut frames = device.fetch_frames()?;
+//!     while let Some(frame) = frames.next_frame() {
+//!         for event in gamepad.process_frame(frame) {
+//!             match event {
+//!                 GamepadEvent::Button {
+//!                     button: GamepadButton::South,
+//!                     pressed: true,
+//!                 } => println!("jump"),
+//!                 GamepadEvent::Axis { axis, value } => println!("{axis:?}: {value}"),
+//!                 _ => {}
+//!             }
+//!         }
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::axis::{self, AxisNormalizer, ResponseCurve};
+use crate::{
+    AbsInfo, AbsoluteAxisCode, AttributeSet, AttributeSetRef, Device, EventType, Frame, InputEvent,
+    InputId, KeyCode,
+};
+
+/// The buttons, by their name in SDL's mapping strings.
+const BUTTONS: [(&str, GamepadButton); 21] = [
+    ("a", GamepadButton::South),
+    ("b", GamepadButton::East),
+    ("x", GamepadButton::West),
+    ("y", GamepadButton::North),
+    ("back", GamepadButton::Back),
+    ("guide", GamepadButton::Guide),
+    ("start", GamepadButton::Start),
+    ("leftstick", GamepadButton::LeftStick),
+    ("rightstick", GamepadButton::RightStick),
+    ("leftshoulder", GamepadButton::LeftShoulder),
+    ("rightshoulder", GamepadButton::RightShoulder),
+    ("dpup", GamepadButton::DPadUp),
+    ("dpdown", GamepadButton::DPadDown),
+    ("dpleft", GamepadButton::DPadLeft),
+    ("dpright", GamepadButton::DPadRight),
+    ("misc1", GamepadButton::Misc1),
+    ("paddle1", GamepadButton::Paddle1),
+    ("paddle2", GamepadButton::Paddle2),
+    ("paddle3", GamepadButton::Paddle3),
+    ("paddle4", GamepadButton::Paddle4),
+    ("touchpad", GamepadButton::Touchpad),
+];
+
+/// The axes, by their name in SDL's mapping strings.
+const AXES: [(&str, GamepadAxis); 6] = [
+    ("leftx", GamepadAxis::LeftX),
+    ("lefty", GamepadAxis::LeftY),
+    ("rightx", GamepadAxis::RightX),
+    ("righty", GamepadAxis::RightY),
+    ("lefttrigger", GamepadAxis::LeftTrigger),
+    ("righttrigger", GamepadAxis::RightTrigger),
+];
+
+/// The buttons of the kernel's gamepad specification, for devices without a mapping.
+const KERNEL_BUTTONS: [(KeyCode, GamepadButton); 15] = [
+    (KeyCode::BTN_SOUTH, GamepadButton::South),
+    (KeyCode::BTN_EAST, GamepadButton::East),
+    (KeyCode::BTN_WEST, GamepadButton::West),
+    (KeyCode::BTN_NORTH, GamepadButton::North),
+    (KeyCode::BTN_SELECT, GamepadButton::Back),
+    (KeyCode::BTN_MODE, GamepadButton::Guide),
+    (KeyCode::BTN_START, GamepadButton::Start),
+    (KeyCode::BTN_THUMBL, GamepadButton::LeftStick),
+    (KeyCode::BTN_THUMBR, GamepadButton::RightStick),
+    (KeyCode::BTN_TL, GamepadButton::LeftShoulder),
+    (KeyCode::BTN_TR, GamepadButton::RightShoulder),
+    (KeyCode::BTN_DPAD_UP, GamepadButton::DPadUp),
+    (KeyCode::BTN_DPAD_DOWN, GamepadButton::DPadDown),
+    (KeyCode::BTN_DPAD_LEFT, GamepadButton::DPadLeft),
+    (KeyCode::BTN_DPAD_RIGHT, GamepadButton::DPadRight),
+];
+
+/// The digital triggers of the kernel's gamepad specification.
+const KERNEL_TRIGGERS: [(KeyCode, GamepadAxis); 2] = [
+    (KeyCode::BTN_TL2, GamepadAxis::LeftTrigger),
+    (KeyCode::BTN_TR2, GamepadAxis::RightTrigger),
+];
+
+/// The axes of the kernel's gamepad specification, for devices without a mapping.
+const KERNEL_AXES: [(AbsoluteAxisCode, GamepadAxis); 6] = [
+    (AbsoluteAxisCode::ABS_X, GamepadAxis::LeftX),
+    (AbsoluteAxisCode::ABS_Y, GamepadAxis::LeftY),
+    (AbsoluteAxisCode::ABS_RX, GamepadAxis::RightX),
+    (AbsoluteAxisCode::ABS_RY, GamepadAxis::RightY),
+    (AbsoluteAxisCode::ABS_Z, GamepadAxis::LeftTrigger),
+    (AbsoluteAxisCode::ABS_RZ, GamepadAxis::RightTrigger),
+];
+
+/// The directions of a hat, by their bit in SDL's hat masks.
+const HAT_DIRECTIONS: [(u8, GamepadButton); 4] = [
+    (1, GamepadButton::DPadUp),
+    (2, GamepadButton::DPadRight),
+    (4, GamepadButton::DPadDown),
+    (8, GamepadButton::DPadLeft),
+];
+
+/// A button of a [`Gamepad`], named by its position on an Xbox controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    /// The bottom face button, A on an Xbox controller and Cross on a PlayStation one.
+    South,
+    /// The right face button, B or Circle.
+    East,
+    /// The left face button, X or Square.
+    West,
+    /// The top face button, Y or Triangle.
+    North,
+    /// The button left of the center, Back, View, Select or Share.
+    Back,
+    /// The button in the center, with the logo of the vendor.
+    Guide,
+    /// The button right of the center, Start, Menu or Options.
+    Start,
+    /// Pressing the left stick.
+    LeftStick,
+    /// Pressing the right stick.
+    RightStick,
+    /// The left bumper.
+    LeftShoulder,
+    /// The right bumper.
+    RightShoulder,
+    /// Up on the d-pad.
+    DPadUp,
+    /// Down on the d-pad.
+    DPadDown,
+    /// Left on the d-pad.
+    DPadLeft,
+    /// Right on the d-pad.
+    DPadRight,
+    /// An additional button, e.g. Share on an Xbox Series controller or the microphone button on
+    /// a PlayStation 5 one.
+    Misc1,
+    /// The upper right paddle on the back.
+    Paddle1,
+    /// The upper left paddle on the back.
+    Paddle2,
+    /// The lower right paddle on the back.
+    Paddle3,
+    /// The lower left paddle on the back.
+    Paddle4,
+    /// Pressing the touchpad of a PlayStation controller.
+    Touchpad,
+}
+
+/// An axis of a [`Gamepad`].
+///
+/// The sticks are in [-1, 1], with positive values to the right and down like the `ABS_X` and
+/// `ABS_Y` axes, and the triggers are in [0, 1].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    /// The left stick, horizontally.
+    LeftX,
+    /// The left stick, vertically.
+    LeftY,
+    /// The right stick, horizontally.
+    RightX,
+    /// The right stick, vertically.
+    RightY,
+    /// The left trigger.
+    LeftTrigger,
+    /// The right trigger.
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    fn is_trigger(self) -> bool {
+        matches!(self, GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger)
+    }
+}
+
+/// A change of the state of a [`Gamepad`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GamepadEvent {
+    /// A button was pressed or released.
+    Button {
+        button: GamepadButton,
+        pressed: bool,
+    },
+    /// An axis moved to `value`.
+    Axis { axis: GamepadAxis, value: f32 },
+}
+
+/// The identifier SDL gives a joystick, which mappings are looked up by.
+///
+/// It is made of the bus type, vendor, product and version of the device's [`InputId`], as 16
+/// bytes written in hexadecimal. Devices without a vendor and product have their name in place
+/// of those.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GamepadGuid([u8; 16]);
+
+impl GamepadGuid {
+    /// Returns the GUID of a device with the ids `id`.
+    pub fn from_input_id(id: InputId) -> Self {
+        Self::new(id, "")
+    }
+
+    /// Returns the GUID of `device`.
+    pub fn from_device(device: &Device) -> Self {
+        Self::new(device.input_id(), device.name().unwrap_or(""))
+    }
+
+    fn new(id: InputId, name: &str) -> Self {
+        let mut bytes = [0; 16];
+        bytes[0..2].copy_from_slice(&id.bus_type().0.to_le_bytes());
+        if id.vendor() != 0 && id.product() != 0 {
+            bytes[4..6].copy_from_slice(&id.vendor().to_le_bytes());
+            bytes[8..10].copy_from_slice(&id.product().to_le_bytes());
+            bytes[12..14].copy_from_slice(&id.version().to_le_bytes());
+        } else {
+            // SDL copies as much of the name as fits, followed by a 0
+            let name = &name.as_bytes()[..name.len().min(11)];
+            bytes[4..4 + name.len()].copy_from_slice(name);
+        }
+        GamepadGuid(bytes)
+    }
+
+    /// Returns the bytes of the GUID.
+    pub fn bytes(&self) -> [u8; 16] {
+        self.0
+    }
+
+    /// Returns `true` if the GUIDs are the same but for the version and the CRC of the name,
+    /// which newer versions of SDL put into the GUID.
+    fn matches_loosely(&self, other: &GamepadGuid) -> bool {
+        let strip = |guid: &GamepadGuid| {
+            let mut bytes = guid.0;
+            bytes[2..4].fill(0);
+            bytes[12..14].fill(0);
+            bytes
+        };
+        strip(self) == strip(other)
+    }
+}
+
+impl fmt::Display for GamepadGuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GamepadGuid {
+    type Err = io::Error;
+
+    /// Parses the 32 hexadecimal digits of a GUID.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("`{s}` is not a joystick GUID"),
+            )
+        };
+        if s.len() != 32 || !s.is_ascii() {
+            return Err(invalid());
+        }
+        let mut bytes = [0; 16];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(GamepadGuid(bytes))
+    }
+}
+
+/// Which part of an axis a mapping element refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Half {
+    Full,
+    Positive,
+    Negative,
+}
+
+/// The button, axis or hat of a joystick that an element of a mapping string refers to, by SDL's
+/// numbering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Input {
+    Button(usize),
+    Axis {
+        index: usize,
+        half: Half,
+        invert: bool,
+    },
+    Hat {
+        index: usize,
+        mask: u8,
+    },
+}
+
+/// What an element of a mapping string maps to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Button(GamepadButton),
+    Axis(GamepadAxis, Half),
+}
+
+/// The code of a device that a binding reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Key(KeyCode),
+    Axis {
+        axis: AbsoluteAxisCode,
+        half: Half,
+        invert: bool,
+    },
+    Hat {
+        x: AbsoluteAxisCode,
+        y: AbsoluteAxisCode,
+        mask: u8,
+    },
+}
+
+/// An SDL mapping string, which maps the buttons, axes and hats of one kind of joystick to the
+/// buttons and axes of a [`Gamepad`].
+///
+/// A mapping string is the GUID of the joystick, a name and a list of elements separated by
+/// commas, e.g. `a:b0` for the first button of the joystick being the South button, `lefttrigger:a2`
+/// for its third axis being the left trigger or `dpup:h0.1` for up on its first hat being up on
+/// the d-pad. Half axes are written with `+` or `-`, and `~` inverts an axis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mapping {
+    guid: GamepadGuid,
+    name: String,
+    platform: Option<String>,
+    bindings: Vec<(Input, Target)>,
+}
+
+impl Mapping {
+    /// Parses a mapping string.
+    ///
+    /// Elements for buttons or axes that SDL added later and ones like `hint:` are ignored.
+    /// Fails with [`io::ErrorKind::InvalidData`] if the string is not valid.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        parse_mapping(text).map_err(|msg| io::Error::new(io::ErrorKind::InvalidData, msg))
+    }
+
+    /// Returns the GUID of the joystick the mapping is for.
+    pub fn guid(&self) -> GamepadGuid {
+        self.guid
+    }
+
+    /// Returns the name of the mapping.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the platform the mapping is for, such as `Linux`, or `None` if it is for all
+    /// of them.
+    pub fn platform(&self) -> Option<&str> {
+        self.platform.as_deref()
+    }
+}
+
+fn parse_mapping(text: &str) -> Result<Mapping, String> {
+    let mut fields = text.trim().split(',');
+    let guid = fields.next().unwrap_or_default();
+    let guid: GamepadGuid = guid.parse().map_err(|e: io::Error| e.to_string())?;
+    let name = fields.next().ok_or("the mapping has no name")?.to_owned();
+    let mut mapping = Mapping {
+        guid,
+        name,
+        platform: None,
+        bindings: Vec::new(),
+    };
+    for field in fields.filter(|field| !field.is_empty()) {
+        let (key, value) = field
+            .split_once(':')
+            .ok_or_else(|| format!("`{field}` is not a mapping element"))?;
+        if key == "platform" {
+            mapping.platform = Some(value.to_owned());
+            continue;
+        }
+        let target = match parse_target(key) {
+            Some(target) => target,
+            None => continue,
+        };
+        let input =
+            parse_input(value).ok_or_else(|| format!("`{value}` is not a button, axis or hat"))?;
+        mapping.bindings.push((input, target));
+    }
+    Ok(mapping)
+}
+
+fn parse_target(key: &str) -> Option<Target> {
+    let (half, name) = parse_half(key);
+    if let Some((_, button)) = BUTTONS.iter().find(|(n, _)| *n == name) {
+        return (half == Half::Full).then_some(Target::Button(*button));
+    }
+    let (_, axis) = AXES.iter().find(|(n, _)| *n == name)?;
+    Some(Target::Axis(*axis, half))
+}
+
+fn parse_input(value: &str) -> Option<Input> {
+    let (half, value) = parse_half(value);
+    let kind = value.chars().next()?;
+    let rest = &value[1..];
+    match kind {
+        'b' if half == Half::Full => Some(Input::Button(rest.parse().ok()?)),
+        'a' => {
+            let (rest, invert) = match rest.strip_suffix('~') {
+                Some(rest) => (rest, true),
+                None => (rest, false),
+            };
+            Some(Input::Axis {
+                index: rest.parse().ok()?,
+                half,
+                invert,
+            })
+        }
+        'h' if half == Half::Full => {
+            let (index, mask) = rest.split_once('.')?;
+            Some(Input::Hat {
+                index: index.parse().ok()?,
+                mask: mask.parse().ok()?,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn parse_half(s: &str) -> (Half, &str) {
+    if let Some(rest) = s.strip_prefix('+') {
+        (Half::Positive, rest)
+    } else if let Some(rest) = s.strip_prefix('-') {
+        (Half::Negative, rest)
+    } else {
+        (Half::Full, s)
+    }
+}
+
+/// A collection of SDL mapping strings, such as SDL's `gamecontrollerdb.txt`.
+#[derive(Debug, Clone, Default)]
+pub struct MappingDb {
+    mappings: Vec<Mapping>,
+    warnings: Vec<String>,
+}
+
+impl MappingDb {
+    /// Creates an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses one mapping string per line, skipping empty lines, comments starting with `#` and
+    /// the mappings for platforms other than Linux.
+    ///
+    /// Lines that aren't valid are skipped as well, like SDL does, and listed in
+    /// [`warnings`](Self::warnings).
+    pub fn parse(text: &str) -> Self {
+        let mut db = Self::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            // other platforms may use GUIDs or elements that aren't valid here
+            let platform = line
+                .split(',')
+                .find_map(|field| field.strip_prefix("platform:"));
+            if platform.map_or(false, |platform| platform != "Linux") {
+                continue;
+            }
+            match parse_mapping(line) {
+                Ok(mapping) => db.add(mapping),
+                Err(msg) => db.warnings.push(format!("line {}: {msg}", i + 1)),
+            }
+        }
+        db
+    }
+
+    /// Reads and parses the mapping strings in the file at `path`, see [`parse`](Self::parse).
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Returns why the lines that [`parse`](Self::parse) skipped aren't valid, starting with their
+    /// line number.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Adds a mapping, replacing the one with the same GUID, if any.
+    pub fn add(&mut self, mapping: Mapping) {
+        match self.mappings.iter_mut().find(|m| m.guid == mapping.guid) {
+            Some(existing) => *existing = mapping,
+            None => self.mappings.push(mapping),
+        }
+    }
+
+    /// Returns the mapping for the joystick with `guid`.
+    ///
+    /// If there is none with exactly that GUID, one that only differs in the version of the
+    /// joystick is returned, like SDL does.
+    pub fn find(&self, guid: GamepadGuid) -> Option<&Mapping> {
+        self.mappings
+            .iter()
+            .find(|m| m.guid == guid)
+            .or_else(|| self.mappings.iter().find(|m| m.guid.matches_loosely(&guid)))
+    }
+
+    /// Returns the number of mappings.
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+
+    /// Returns `true` if there are no mappings.
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    /// Returns an iterator over the mappings.
+    pub fn iter(&self) -> impl Iterator<Item = &Mapping> {
+        self.mappings.iter()
+    }
+}
+
+/// An absolute axis that a binding reads.
+#[derive(Debug, Clone)]
+struct Axis {
+    normalizer: AxisNormalizer,
+    value: i32,
+}
+
+/// Presents a joystick as a gamepad with standardized buttons and axes.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct Gamepad {
+    guid: GamepadGuid,
+    name: String,
+    bindings: Vec<(Source, Target)>,
+    keys: AttributeSet<KeyCode>,
+    axes: HashMap<AbsoluteAxisCode, Axis>,
+    deadzone: f32,
+    buttons: [bool; BUTTONS.len()],
+    values: [f32; AXES.len()],
+    events: Vec<GamepadEvent>,
+}
+
+impl Gamepad {
+    /// Creates a gamepad for `device`, with its mapping from `db`.
+    ///
+    /// If there is no mapping for the device, but it has the `BTN_SOUTH` button, its codes are
+    /// taken to follow the kernel's gamepad specification. Otherwise this fails with
+    /// `NotFound`.
+    pub fn new(device: &Device, db: &MappingDb) -> io::Result<Self> {
+        let guid = GamepadGuid::from_device(device);
+        match db.find(guid) {
+            Some(mapping) => Self::with_mapping(device, mapping),
+            None if device
+                .supported_keys()
+                .map_or(false, |keys| keys.contains(KeyCode::BTN_SOUTH)) =>
+            {
+                Self::from_device(device, None)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("there is no mapping for the joystick {guid}"),
+            )),
+        }
+    }
+
+    /// Creates a gamepad for `device` with the given mapping, regardless of its GUID.
+    pub fn with_mapping(device: &Device, mapping: &Mapping) -> io::Result<Self> {
+        Self::from_device(device, Some(mapping))
+    }
+
+    fn from_device(device: &Device, mapping: Option<&Mapping>) -> io::Result<Self> {
+        let no_keys = AttributeSet::new();
+        let supported_keys = device.supported_keys().unwrap_or(&no_keys);
+        let pressed = device.get_key_state()?;
+        let axes: Vec<_> = device.get_absinfo()?.collect();
+        let name = match mapping {
+            Some(mapping) => mapping.name(),
+            None => device.name().unwrap_or_default(),
+        };
+        let mut gamepad = Self::from_state(supported_keys, &axes, mapping);
+        gamepad.guid = GamepadGuid::from_device(device);
+        gamepad.name = name.to_owned();
+        for key in pressed.iter() {
+            gamepad.keys.insert(key);
+        }
+        gamepad.update();
+        gamepad.events.clear();
+        Ok(gamepad)
+    }
+
+    /// Creates a gamepad for a device with the given keys and absolute axes, with the keys up and
+    /// the axes at the values of their `AbsInfo`.
+    pub(crate) fn from_state(
+        supported_keys: &AttributeSetRef<KeyCode>,
+        axes: &[(AbsoluteAxisCode, AbsInfo)],
+        mapping: Option<&Mapping>,
+    ) -> Self {
+        let bindings = match mapping {
+            Some(mapping) => resolve(mapping, supported_keys, axes),
+            None => kernel_bindings(supported_keys, axes),
+        };
+        let axes = axes
+            .iter()
+            .map(|&(axis, info)| {
+                // the flat is left to the deadzone of the gamepad, since it would otherwise
+                // apply to the middle of triggers
+                let range = AbsInfo::new(0, info.minimum(), info.maximum(), 0, 0, 0);
+                let axis_state = Axis {
+                    normalizer: AxisNormalizer::new(range),
+                    value: info.value(),
+                };
+                (axis, axis_state)
+            })
+            .collect();
+        let mut gamepad = Gamepad {
+            guid: GamepadGuid([0; 16]),
+            name: mapping.map(|m| m.name.clone()).unwrap_or_default(),
+            bindings,
+            keys: AttributeSet::new(),
+            axes,
+            deadzone: 0.0,
+            buttons: [false; BUTTONS.len()],
+            values: [0.0; AXES.len()],
+            events: Vec::new(),
+        };
+        gamepad.update();
+        gamepad.events.clear();
+        gamepad
+    }
+
+    /// Sets the radial deadzone of the sticks, as a fraction of their range, clamped to [0, 1].
+    /// Defaults to 0.
+    pub fn deadzone(mut self, deadzone: f32) -> Self {
+        self.deadzone = if deadzone.is_nan() {
+            0.0
+        } else {
+            deadzone.clamp(0.0, 1.0)
+        };
+        self.update();
+        self.events.clear();
+        self
+    }
+
+    /// Returns the GUID of the device.
+    pub fn guid(&self) -> GamepadGuid {
+        self.guid
+    }
+
+    /// Returns the name of the mapping, or that of the device if it has none.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns `true` if the button is mapped to a code of the device.
+    pub fn has_button(&self, button: GamepadButton) -> bool {
+        self.bindings
+            .iter()
+            .any(|(_, target)| *target == Target::Button(button))
+    }
+
+    /// Returns `true` if the axis is mapped to a code of the device.
+    pub fn has_axis(&self, axis: GamepadAxis) -> bool {
+        self.bindings
+            .iter()
+            .any(|(_, target)| matches!(target, Target::Axis(a, _) if *a == axis))
+    }
+
+    /// Returns `true` if the button is pressed.
+    pub fn button(&self, button: GamepadButton) -> bool {
+        self.buttons[button as usize]
+    }
+
+    /// Returns the value of the axis.
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.values[axis as usize]
+    }
+
+    /// Updates the state of the gamepad with a frame of the device, and returns the buttons and
+    /// axes that changed.
+    pub fn process_frame(&mut self, frame: Frame<'_>) -> impl Iterator<Item = GamepadEvent> + '_ {
+        for ev in frame {
+            self.apply(ev);
+        }
+        self.update();
+        self.events.drain(..)
+    }
+
+    fn apply(&mut self, ev: InputEvent) {
+        match ev.event_type() {
+            EventType::KEY => {
+                let key = KeyCode(ev.code());
+                if ev.value() == 0 {
+                    self.keys.remove(key);
+                } else {
+                    self.keys.insert(key);
+                }
+            }
+            EventType::ABSOLUTE => {
+                if let Some(axis) = self.axes.get_mut(&AbsoluteAxisCode(ev.code())) {
+                    axis.value = ev.value();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Computes the buttons and axes from the state of the device.
+    fn update(&mut self) {
+        let mut buttons = [false; BUTTONS.len()];
+        let mut values = [0.0; AXES.len()];
+        for &(source, target) in &self.bindings {
+            // the value of the source, and whether it goes from -1 rather than 0 to 1
+            let (value, bipolar) = self.read(source);
+            let unipolar = if bipolar { (value + 1.0) / 2.0 } else { value };
+            match target {
+                Target::Button(button) => buttons[button as usize] |= unipolar > 0.5,
+                Target::Axis(axis, half) => {
+                    values[axis as usize] += match half {
+                        Half::Full if axis.is_trigger() => unipolar,
+                        Half::Full if bipolar => value,
+                        Half::Full => unipolar * 2.0 - 1.0,
+                        Half::Positive => unipolar,
+                        Half::Negative => -unipolar,
+                    }
+                }
+            }
+        }
+        for (i, value) in values.iter_mut().enumerate() {
+            let min = if AXES[i].1.is_trigger() { 0.0 } else { -1.0 };
+            *value = value.clamp(min, 1.0);
+        }
+        if self.deadzone > 0.0 {
+            for (x, y) in [
+                (GamepadAxis::LeftX, GamepadAxis::LeftY),
+                (GamepadAxis::RightX, GamepadAxis::RightY),
+            ] {
+                let (x, y) = (x as usize, y as usize);
+                (values[x], values[y]) = axis::radial_deadzone(
+                    values[x],
+                    values[y],
+                    self.deadzone,
+                    &ResponseCurve::Linear,
+                );
+            }
+        }
+
+        for (i, &(_, button)) in BUTTONS.iter().enumerate() {
+            if buttons[i] != self.buttons[i] {
+                self.events.push(GamepadEvent::Button {
+                    button,
+                    pressed: buttons[i],
+                });
+            }
+        }
+        for (i, &(_, axis)) in AXES.iter().enumerate() {
+            if values[i] != self.values[i] {
+                self.events.push(GamepadEvent::Axis {
+                    axis,
+                    value: values[i],
+                });
+            }
+        }
+        self.buttons = buttons;
+        self.values = values;
+    }
+
+    /// Returns the value of `source` in [0, 1], or in [-1, 1] along with `true` for full axes.
+    fn read(&self, source: Source) -> (f32, bool) {
+        let value = |axis: AbsoluteAxisCode| self.axes.get(&axis).map_or(0, |axis| axis.value);
+        match source {
+            Source::Key(key) => (flag(self.keys.contains(key)), false),
+            Source::Axis { axis, half, invert } => {
+                let mut v = match self.axes.get(&axis) {
+                    Some(axis) => axis.normalizer.normalize(axis.value),
+                    None => 0.0,
+                };
+                if invert {
+                    v = -v;
+                }
+                match half {
+                    Half::Full => (v, true),
+                    Half::Positive => (v.max(0.0), false),
+                    Half::Negative => ((-v).max(0.0), false),
+                }
+            }
+            Source::Hat { x, y, mask } => {
+                let (x, y) = (value(x), value(y));
+                let pressed = HAT_DIRECTIONS.iter().any(|&(bit, direction)| {
+                    mask & bit != 0
+                        && match direction {
+                            GamepadButton::DPadUp => y < 0,
+                            GamepadButton::DPadRight => x > 0,
+                            GamepadButton::DPadDown => y > 0,
+                            _ => x < 0,
+                        }
+                });
+                (flag(pressed), false)
+            }
+        }
+    }
+}
+
+fn flag(on: bool) -> f32 {
+    if on {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Turns the elements of `mapping` into bindings of the codes of a device, numbering its buttons,
+/// axes and hats like SDL does. Elements for buttons, axes or hats the device doesn't have are
+/// dropped.
+fn resolve(
+    mapping: &Mapping,
+    keys: &AttributeSetRef<KeyCode>,
+    axes: &[(AbsoluteAxisCode, AbsInfo)],
+) -> Vec<(Source, Target)> {
+    let is_hat =
+        |code: u16| (AbsoluteAxisCode::ABS_HAT0X.0..=AbsoluteAxisCode::ABS_HAT3Y.0).contains(&code);
+    // joystick buttons come first, then all other keys
+    let buttons: Vec<_> = keys
+        .iter()
+        .filter(|key| key.0 >= KeyCode::BTN_TRIGGER.0 && (key.0 as usize) < KeyCode::COUNT - 1)
+        .chain(keys.iter().filter(|key| key.0 < KeyCode::BTN_TRIGGER.0))
+        .collect();
+    let sticks: Vec<_> = axes
+        .iter()
+        .map(|&(axis, _)| axis)
+        .filter(|axis| !is_hat(axis.0) && (axis.0 as usize) < AbsoluteAxisCode::COUNT - 1)
+        .collect();
+    let hats: Vec<_> = (0..4)
+        .map(|i| {
+            let x = AbsoluteAxisCode(AbsoluteAxisCode::ABS_HAT0X.0 + 2 * i);
+            (x, AbsoluteAxisCode(x.0 + 1))
+        })
+        .filter(|(x, y)| axes.iter().any(|(axis, _)| axis == x || axis == y))
+        .collect();
+
+    mapping
+        .bindings
+        .iter()
+        .filter_map(|&(input, target)| {
+            let source = match input {
+                Input::Button(i) => Source::Key(*buttons.get(i)?),
+                Input::Axis {
+                    index,
+                    half,
+                    invert,
+                } => Source::Axis {
+                    axis: *sticks.get(index)?,
+                    half,
+                    invert,
+                },
+                Input::Hat { index, mask } => {
+                    let (x, y) = *hats.get(index)?;
+                    Source::Hat { x, y, mask }
+                }
+            };
+            Some((source, target))
+        })
+        .collect()
+}
+
+/// Returns the bindings of the kernel's gamepad specification for the codes a device has.
+fn kernel_bindings(
+    keys: &AttributeSetRef<KeyCode>,
+    axes: &[(AbsoluteAxisCode, AbsInfo)],
+) -> Vec<(Source, Target)> {
+    let has_axis = |code: AbsoluteAxisCode| axes.iter().any(|(axis, _)| *axis == code);
+    let mut bindings: Vec<_> = KERNEL_BUTTONS
+        .iter()
+        .filter(|(key, _)| keys.contains(*key))
+        .map(|&(key, button)| (Source::Key(key), Target::Button(button)))
+        .collect();
+    bindings.extend(
+        KERNEL_TRIGGERS
+            .iter()
+            .filter(|(key, _)| keys.contains(*key))
+            .map(|&(key, axis)| (Source::Key(key), Target::Axis(axis, Half::Full))),
+    );
+    bindings.extend(KERNEL_AXES.iter().filter(|(code, _)| has_axis(*code)).map(
+        |&(axis, target)| {
+            let source = Source::Axis {
+                axis,
+                half: Half::Full,
+                invert: false,
+            };
+            (source, Target::Axis(target, Half::Full))
+        },
+    ));
+    let (x, y) = (AbsoluteAxisCode::ABS_HAT0X, AbsoluteAxisCode::ABS_HAT0Y);
+    if has_axis(x) || has_axis(y) {
+        bindings.extend(
+            HAT_DIRECTIONS
+                .iter()
+                .map(|&(mask, button)| (Source::Hat { x, y, mask }, Target::Button(button))),
+        );
+    }
+    bindings
+}
@@ -0,0 +1,552 @@
+//! Recognizing touchpad gestures from multi-touch frames.
+//!
+//! A [`GestureRecognizer`] follows the contacts of a Type B multi-touch device, i.e. one that
+//! reports them in `ABS_MT_SLOT`s, and turns the [`Frame`]s of that device into
+//! [`GestureEvent`]s: swipes, pinches, holds and taps. Distances are measured in millimetres,
+//! using the resolution of the device's `ABS_MT_POSITION_X`/`Y` axes, so the thresholds in
+//! [`GestureConfig`] mean the same on every touchpad.
+//!
+//! Touchpads often track fewer contacts than they can detect, and report the number of fingers
+//! with the `BTN_TOOL_FINGER` to `BTN_TOOL_QUINTTAP` keys instead. Those are taken into account
+//! for the finger count of a gesture, while the movement is measured from the tracked contacts.
+//!
+//! A finger that rests on the touchpad doesn't produce any events, so a hold can't be recognized
+//! from frames alone. Whenever there's a [`deadline`](GestureRecognizer::deadline), call
+//! [`process_timeout`](GestureRecognizer::process_timeout) once it's reached if no frame arrived
+//! in the meantime. Times are given in the device's [`Clock`](crate::Clock), as returned by
+//! [`Frame::clock_time`] and [`Clock::now`](crate::Clock::now).
+//!
+//! ```no_run
+//! use evdev::gestures::GestureRecognizer;
+//! use evdev::Device;
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let mut device = Device::open("/dev/input/event0")?;
+//! let mut recognizer = GestureRecognizer::new(&device)?;
+//! loop {
+//!     let mut frames = device.fetch_frames()?;
+//!     while let Some(frame) = frames.next_frame() {
+//!         for gesture in recognizer.process_frame(frame) {
+//!             println!("{gesture:?}");
+//!         }
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::io;
+use std::time::Duration;
+
+use crate::multitouch::MtTracker;
+use crate::{Device, Frame};
+
+/// A high-level event recognized by a [`GestureRecognizer`].
+///
+/// Distances are in millimetres. Every gesture that begins also ends, and `cancelled` is set if it
+/// ended because the number of fingers changed rather than because they were lifted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GestureEvent {
+    /// Two or more fingers started moving in the same direction.
+    SwipeBegin { fingers: u8 },
+    /// The fingers of a swipe moved by `dx` and `dy` since the last update.
+    SwipeUpdate { fingers: u8, dx: f64, dy: f64 },
+    /// The fingers of a swipe were lifted or their number changed.
+    SwipeEnd { fingers: u8, cancelled: bool },
+    /// Two or more fingers started moving towards or away from each other.
+    PinchBegin { fingers: u8 },
+    /// The fingers of a pinch changed their distance from each other to `scale` times what it was
+    /// at the beginning, turned by `rotation` degrees clockwise and moved by `dx` and `dy` since
+    /// the last update.
+    PinchUpdate {
+        fingers: u8,
+        scale: f64,
+        rotation: f64,
+        dx: f64,
+        dy: f64,
+    },
+    /// The fingers of a pinch were lifted or their number changed.
+    PinchEnd { fingers: u8, cancelled: bool },
+    /// The fingers rested on the touchpad without moving.
+    HoldBegin { fingers: u8 },
+    /// The fingers of a hold were lifted, moved or their number changed.
+    HoldEnd { fingers: u8, cancelled: bool },
+    /// The fingers were put down and lifted again quickly, without moving.
+    Tap { fingers: u8 },
+    /// A second tap with the same number of fingers followed the previous one quickly. This comes
+    /// right after the [`Tap`](Self::Tap) event of that second tap.
+    DoubleTap { fingers: u8 },
+}
+
+/// The thresholds a [`GestureRecognizer`] works with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GestureConfig {
+    tap_time: Duration,
+    tap_distance: f64,
+    double_tap_time: Duration,
+    hold_time: Duration,
+    swipe_distance: f64,
+    pinch_distance: f64,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        GestureConfig {
+            tap_time: Duration::from_millis(180),
+            tap_distance: 1.5,
+            double_tap_time: Duration::from_millis(300),
+            hold_time: Duration::from_millis(180),
+            swipe_distance: 3.0,
+            pinch_distance: 3.0,
+        }
+    }
+}
+
+impl GestureConfig {
+    /// Creates the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The longest time fingers may touch the touchpad to count as a tap. Defaults to 180 ms.
+    pub fn tap_time(mut self, time: Duration) -> Self {
+        self.tap_time = time;
+        self
+    }
+
+    /// How far, in millimetres, fingers may move during a tap or a hold. Defaults to 1.5 mm.
+    pub fn tap_distance(mut self, mm: f64) -> Self {
+        self.tap_distance = mm;
+        self
+    }
+
+    /// The longest time between the end of a tap and the beginning of the next one for the two to
+    /// make a double tap. Defaults to 300 ms.
+    pub fn double_tap_time(mut self, time: Duration) -> Self {
+        self.double_tap_time = time;
+        self
+    }
+
+    /// How long fingers have to rest on the touchpad to begin a hold. Defaults to 180 ms.
+    pub fn hold_time(mut self, time: Duration) -> Self {
+        self.hold_time = time;
+        self
+    }
+
+    /// How far, in millimetres, fingers have to move together to begin a swipe. Defaults to
+    /// 3 mm.
+    pub fn swipe_distance(mut self, mm: f64) -> Self {
+        self.swipe_distance = mm;
+        self
+    }
+
+    /// How much, in millimetres, the distance of the fingers from their center has to change to
+    /// begin a pinch. Defaults to 3 mm.
+    pub fn pinch_distance(mut self, mm: f64) -> Self {
+        self.pinch_distance = mm;
+        self
+    }
+}
+
+/// Where the tracked contacts are, in millimetres.
+#[derive(Debug, Clone, Copy)]
+struct Shape {
+    contacts: usize,
+    center: (f64, f64),
+    /// The average distance of the contacts from the center.
+    spread: f64,
+    /// The direction from the first contact to the second, in degrees.
+    angle: Option<f64>,
+}
+
+impl Shape {
+    fn distance_to(&self, other: &Shape) -> f64 {
+        let (dx, dy) = self.offset_to(other);
+        dx.hypot(dy)
+    }
+
+    fn offset_to(&self, other: &Shape) -> (f64, f64) {
+        (
+            other.center.0 - self.center.0,
+            other.center.1 - self.center.1,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Idle,
+    /// Fingers are down, but it isn't clear yet what they are doing.
+    Pending {
+        since: Duration,
+        /// The most fingers that were down at once.
+        max_fingers: u8,
+        fingers: u8,
+        start: Shape,
+        /// Whether they moved too far for a tap or a hold.
+        moved: bool,
+    },
+    /// A single finger moving, e.g. the pointer, which isn't a gesture.
+    Motion,
+    Hold {
+        fingers: u8,
+        start: Shape,
+    },
+    Swipe {
+        fingers: u8,
+        last: Shape,
+    },
+    Pinch {
+        fingers: u8,
+        start: Shape,
+        last: Shape,
+    },
+}
+
+/// Turns the frames of a touchpad into [`GestureEvent`]s.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    touch: MtTracker,
+    state: State,
+    /// The finger count and end of the last tap that wasn't part of a double tap yet.
+    last_tap: Option<(u8, Duration)>,
+    events: Vec<GestureEvent>,
+}
+
+impl GestureRecognizer {
+    /// Creates a recognizer for `device` with the default configuration.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] if the device doesn't have the
+    /// `ABS_MT_POSITION_X` and `ABS_MT_POSITION_Y` axes.
+    pub fn new(device: &Device) -> io::Result<Self> {
+        Self::with_config(device, GestureConfig::default())
+    }
+
+    /// Creates a recognizer for `device` with the given configuration.
+    ///
+    /// Contacts that are on the touchpad already are picked up from the device's
+    /// [cached state](Device::cached_state).
+    pub fn with_config(device: &Device, config: GestureConfig) -> io::Result<Self> {
+        Ok(Self::from_tracker(MtTracker::new(device)?, config))
+    }
+
+    /// Creates a recognizer for a touchpad with the given position axes and number of slots.
+    #[cfg(test)]
+    pub(crate) fn from_axes(
+        x: crate::AbsInfo,
+        y: crate::AbsInfo,
+        num_slots: usize,
+        config: GestureConfig,
+    ) -> Self {
+        Self::from_tracker(MtTracker::from_axes(x, y, num_slots), config)
+    }
+
+    fn from_tracker(touch: MtTracker, config: GestureConfig) -> Self {
+        GestureRecognizer {
+            config,
+            touch,
+            state: State::Idle,
+            last_tap: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns the number of fingers on the touchpad.
+    pub fn fingers(&self) -> u8 {
+        let tracked = self.touch.slots().active_slots().count();
+        tracked.max(self.touch.tool_fingers()) as u8
+    }
+
+    /// Processes the next frame of the device and returns the gestures it completes.
+    pub fn process_frame(&mut self, frame: Frame<'_>) -> impl Iterator<Item = GestureEvent> + '_ {
+        for ev in frame {
+            self.touch.apply(ev);
+        }
+        let now = frame.clock_time();
+        self.check_hold(now);
+        self.update(now);
+        self.events.drain(..)
+    }
+
+    /// Returns the time, in the device's clock, at which
+    /// [`process_timeout`](Self::process_timeout) should be called if no frame arrives before.
+    pub fn deadline(&self) -> Option<Duration> {
+        match self.state {
+            State::Pending {
+                since,
+                moved: false,
+                ..
+            } => Some(since + self.config.hold_time),
+            _ => None,
+        }
+    }
+
+    /// Lets the recognizer know that it's `now` in the device's clock, and returns the gestures
+    /// that began in the meantime.
+    pub fn process_timeout(&mut self, now: Duration) -> impl Iterator<Item = GestureEvent> + '_ {
+        self.check_hold(now);
+        self.events.drain(..)
+    }
+
+    fn shape(&self) -> Shape {
+        let contacts: Vec<(f64, f64)> = self
+            .touch
+            .slots()
+            .active_slots()
+            .map(|slot| self.touch.position(slot))
+            .collect();
+        let n = contacts.len().max(1) as f64;
+        let center = (
+            contacts.iter().map(|c| c.0).sum::<f64>() / n,
+            contacts.iter().map(|c| c.1).sum::<f64>() / n,
+        );
+        let spread = contacts
+            .iter()
+            .map(|c| (c.0 - center.0).hypot(c.1 - center.1))
+            .sum::<f64>()
+            / n;
+        let angle = match contacts[..] {
+            [a, b, ..] => Some((b.1 - a.1).atan2(b.0 - a.0).to_degrees()),
+            _ => None,
+        };
+        Shape {
+            contacts: contacts.len(),
+            center,
+            spread,
+            angle,
+        }
+    }
+
+    fn check_hold(&mut self, now: Duration) {
+        if let State::Pending {
+            since,
+            fingers,
+            start,
+            moved: false,
+            ..
+        } = self.state
+        {
+            if now >= since + self.config.hold_time {
+                self.events.push(GestureEvent::HoldBegin { fingers });
+                self.state = State::Hold { fingers, start };
+            }
+        }
+    }
+
+    fn update(&mut self, now: Duration) {
+        let fingers = self.fingers();
+        let shape = self.shape();
+        let config = &self.config;
+        let events = &mut self.events;
+        self.state = match self.state {
+            State::Idle if fingers == 0 => State::Idle,
+            State::Idle => State::Pending {
+                since: now,
+                max_fingers: fingers,
+                fingers,
+                start: shape,
+                moved: false,
+            },
+            State::Pending {
+                since,
+                max_fingers,
+                moved,
+                ..
+            } if fingers == 0 => {
+                if !moved && now <= since + config.tap_time {
+                    let tap = (max_fingers, now);
+                    events.push(GestureEvent::Tap {
+                        fingers: max_fingers,
+                    });
+                    self.last_tap = match self.last_tap {
+                        Some((fingers, end))
+                            if fingers == max_fingers
+                                && since >= end
+                                && since - end <= config.double_tap_time =>
+                        {
+                            events.push(GestureEvent::DoubleTap { fingers });
+                            None
+                        }
+                        _ => Some(tap),
+                    };
+                }
+                State::Idle
+            }
+            State::Pending {
+                since,
+                max_fingers,
+                fingers: last_fingers,
+                start,
+                moved,
+            } => {
+                if fingers != last_fingers || shape.contacts != start.contacts {
+                    // measure from here on, as the center jumps when a finger is added or lifted
+                    State::Pending {
+                        since,
+                        max_fingers: max_fingers.max(fingers),
+                        fingers,
+                        start: shape,
+                        moved,
+                    }
+                } else if fingers >= 2
+                    && shape.contacts >= 2
+                    && (shape.spread - start.spread).abs() > config.pinch_distance
+                {
+                    events.push(GestureEvent::PinchBegin { fingers });
+                    pinch_update(events, fingers, &start, &start, &shape);
+                    State::Pinch {
+                        fingers,
+                        start,
+                        last: shape,
+                    }
+                } else if fingers >= 2 && start.distance_to(&shape) > config.swipe_distance {
+                    events.push(GestureEvent::SwipeBegin { fingers });
+                    swipe_update(events, fingers, &start, &shape);
+                    State::Swipe {
+                        fingers,
+                        last: shape,
+                    }
+                } else if start.distance_to(&shape) > config.tap_distance {
+                    if fingers == 1 {
+                        State::Motion
+                    } else {
+                        State::Pending {
+                            since,
+                            max_fingers,
+                            fingers,
+                            start,
+                            moved: true,
+                        }
+                    }
+                } else {
+                    self.state
+                }
+            }
+            State::Motion if fingers == 0 => State::Idle,
+            State::Motion if fingers == 1 => State::Motion,
+            State::Motion => State::Pending {
+                since: now,
+                max_fingers: fingers,
+                fingers,
+                start: shape,
+                moved: true,
+            },
+            State::Hold {
+                fingers: hold_fingers,
+                start,
+            } => {
+                let lifted = fingers == 0;
+                let changed = fingers != hold_fingers || shape.contacts != start.contacts;
+                let moved = !changed && start.distance_to(&shape) > config.tap_distance;
+                if lifted || changed || moved {
+                    events.push(GestureEvent::HoldEnd {
+                        fingers: hold_fingers,
+                        cancelled: !lifted,
+                    });
+                }
+                if lifted {
+                    State::Idle
+                } else if changed || moved {
+                    State::Pending {
+                        since: now,
+                        max_fingers: fingers,
+                        fingers,
+                        start: shape,
+                        moved,
+                    }
+                } else {
+                    self.state
+                }
+            }
+            State::Swipe {
+                fingers: swipe_fingers,
+                last,
+            } => {
+                if fingers != swipe_fingers || shape.contacts != last.contacts {
+                    events.push(GestureEvent::SwipeEnd {
+                        fingers: swipe_fingers,
+                        cancelled: fingers != 0,
+                    });
+                    restart(now, fingers, shape)
+                } else {
+                    swipe_update(events, fingers, &last, &shape);
+                    State::Swipe {
+                        fingers,
+                        last: shape,
+                    }
+                }
+            }
+            State::Pinch {
+                fingers: pinch_fingers,
+                start,
+                last,
+            } => {
+                if fingers != pinch_fingers || shape.contacts != last.contacts {
+                    events.push(GestureEvent::PinchEnd {
+                        fingers: pinch_fingers,
+                        cancelled: fingers != 0,
+                    });
+                    restart(now, fingers, shape)
+                } else {
+                    pinch_update(events, fingers, &start, &last, &shape);
+                    State::Pinch {
+                        fingers,
+                        start,
+                        last: shape,
+                    }
+                }
+            }
+        };
+    }
+}
+
+/// The state after a gesture ended because fingers were added or lifted.
+fn restart(now: Duration, fingers: u8, shape: Shape) -> State {
+    if fingers == 0 {
+        return State::Idle;
+    }
+    // neither a tap nor a hold, those fingers already did something else
+    State::Pending {
+        since: now,
+        max_fingers: fingers,
+        fingers,
+        start: shape,
+        moved: true,
+    }
+}
+
+fn swipe_update(events: &mut Vec<GestureEvent>, fingers: u8, last: &Shape, shape: &Shape) {
+    let (dx, dy) = last.offset_to(shape);
+    if dx != 0.0 || dy != 0.0 {
+        events.push(GestureEvent::SwipeUpdate { fingers, dx, dy });
+    }
+}
+
+fn pinch_update(
+    events: &mut Vec<GestureEvent>,
+    fingers: u8,
+    start: &Shape,
+    last: &Shape,
+    shape: &Shape,
+) {
+    let scale = if start.spread > f64::EPSILON {
+        shape.spread / start.spread
+    } else {
+        1.0
+    };
+    let rotation = match (last.angle, shape.angle) {
+        // the shortest way around
+        (Some(a), Some(b)) => (b - a + 540.0).rem_euclid(360.0) - 180.0,
+        _ => 0.0,
+    };
+    let (dx, dy) = last.offset_to(shape);
+    events.push(GestureEvent::PinchUpdate {
+        fingers,
+        scale,
+        rotation,
+        dx,
+        dy,
+    });
+}
@@ -0,0 +1,233 @@
+use std::fs;
+use std::io;
+use std::os::fd::{AsRawFd, BorrowedFd};
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::raw_stream::is_event_node;
+use crate::{BusType, InputId, SysfsInfo};
+
+const BY_ID_DIR: &str = "/dev/input/by-id";
+const BY_PATH_DIR: &str = "/dev/input/by-path";
+
+/// Identifies a device in a way that stays the same across reboots and replugging, unlike the
+/// path of its node in `/dev/input`.
+///
+/// An identity is made up of the device's [`InputId`], name, unique name and physical path, the
+/// sysfs path of the hardware device it belongs to, and the `/dev/input/by-id` and
+/// `/dev/input/by-path` symlinks that udev created for it. Get the identity of an open device
+/// with [`Device::identity`](crate::Device::identity), or of a device node or one of its symlinks
+/// with [`DeviceIdentity::from_path`], store it (e.g. with the `serde` feature), and later open
+/// the device again with [`Device::open_by_identity`](crate::Device::open_by_identity).
+///
+/// # Equality
+///
+/// Two identities are equal if they have the same bus type, vendor, product and name, and the
+/// same unique name if both of them have one. Many devices don't report a unique name, so the
+/// comparison is tolerant of it missing on either side. The version is ignored, as it may change
+/// with a firmware update. Because of that tolerance, equality isn't transitive and identities
+/// don't implement `Eq`.
+///
+/// Several devices can be equal to an identity, e.g. two keyboards of the same model without a
+/// serial number. [`find_nodes`](Self::find_nodes) ranks them by how much more of the identity
+/// they share, in this order of importance:
+///
+/// 1. the same unique name, with both identities having one,
+/// 2. the same `/dev/input/by-id` symlink,
+/// 3. the same `/dev/input/by-path` symlink,
+/// 4. the same physical path,
+/// 5. the same sysfs parent device.
+///
+/// Devices that rank the same are ordered by the path of their node.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeviceIdentity {
+    pub(crate) bus_type: BusType,
+    pub(crate) vendor: u16,
+    pub(crate) product: u16,
+    pub(crate) version: u16,
+    pub(crate) name: Option<String>,
+    pub(crate) uniq: Option<String>,
+    pub(crate) phys: Option<String>,
+    pub(crate) parent: Option<PathBuf>,
+    pub(crate) by_id: Option<PathBuf>,
+    pub(crate) by_path: Option<PathBuf>,
+}
+
+impl DeviceIdentity {
+    /// Determines the identity of the device node at `path`, without opening it.
+    ///
+    /// `path` can also be one of the symlinks in `/dev/input/by-id` or `/dev/input/by-path`.
+    pub fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        let stat = nix::sys::stat::stat(path.as_ref())?;
+        Self::from_devnum(stat.st_rdev, &Links::read())
+    }
+
+    /// Determines the identity of the device node open as `fd`.
+    pub(crate) fn from_fd(fd: BorrowedFd<'_>) -> io::Result<Self> {
+        let stat = nix::sys::stat::fstat(fd.as_raw_fd())?;
+        Self::from_devnum(stat.st_rdev, &Links::read())
+    }
+
+    fn from_devnum(rdev: libc::dev_t, links: &Links) -> io::Result<Self> {
+        let info = SysfsInfo::from_devnum_in(rdev, crate::SYSFS_ROOT)?;
+        let id = info.input_id()?;
+        Ok(DeviceIdentity {
+            bus_type: id.bus_type(),
+            vendor: id.vendor(),
+            product: id.product(),
+            version: id.version(),
+            name: info.name()?,
+            uniq: info.unique_name()?,
+            phys: info.physical_path()?,
+            // virtual devices don't have one
+            parent: info.input_syspath().join("device").canonicalize().ok(),
+            by_id: links.find(&links.by_id, rdev),
+            by_path: links.find(&links.by_path, rdev),
+        })
+    }
+
+    /// Returns the bus type, vendor, product and version of the device.
+    pub fn input_id(&self) -> InputId {
+        InputId::new(self.bus_type, self.vendor, self.product, self.version)
+    }
+
+    /// Returns the name of the device.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the unique identifier of the device, e.g. its serial number.
+    pub fn unique_name(&self) -> Option<&str> {
+        self.uniq.as_deref()
+    }
+
+    /// Returns the physical location of the device.
+    pub fn physical_path(&self) -> Option<&str> {
+        self.phys.as_deref()
+    }
+
+    /// Returns the sysfs directory of the hardware device that the input device belongs to, if
+    /// any, e.g. `/sys/devices/.../usb1/1-2/1-2:1.0/0003:046D:C52B.0001`.
+    pub fn parent(&self) -> Option<&Path> {
+        self.parent.as_deref()
+    }
+
+    /// Returns the symlink to the device node in `/dev/input/by-id`, if udev created one.
+    ///
+    /// These are based on the model and serial number of the device.
+    pub fn by_id(&self) -> Option<&Path> {
+        self.by_id.as_deref()
+    }
+
+    /// Returns the symlink to the device node in `/dev/input/by-path`, if udev created one.
+    ///
+    /// These are based on the port the device is connected to.
+    pub fn by_path(&self) -> Option<&Path> {
+        self.by_path.as_deref()
+    }
+
+    /// Finds the nodes in `/dev/input` of the devices that are equal to this identity, best match
+    /// first.
+    ///
+    /// See the [type-level documentation](Self) for how matches are ranked.
+    pub fn find_nodes(&self) -> io::Result<Vec<PathBuf>> {
+        let links = Links::read();
+        let mut nodes = Vec::new();
+        for entry in fs::read_dir("/dev/input")? {
+            let path = entry?.path();
+            if !is_event_node(&path) {
+                continue;
+            }
+            let metadata = match fs::metadata(&path) {
+                Ok(metadata) if metadata.file_type().is_char_device() => metadata,
+                _ => continue,
+            };
+            // the device might be gone already
+            if let Ok(identity) = Self::from_devnum(metadata.rdev() as libc::dev_t, &links) {
+                if identity == *self {
+                    nodes.push((self.rank(&identity), path));
+                }
+            }
+        }
+        nodes.sort_by(|(a_rank, a_path), (b_rank, b_path)| {
+            b_rank.cmp(a_rank).then_with(|| a_path.cmp(b_path))
+        });
+        Ok(nodes.into_iter().map(|(_, path)| path).collect())
+    }
+
+    /// How well `other`, which is equal to this identity, matches it. Higher is better.
+    pub(crate) fn rank(&self, other: &DeviceIdentity) -> [bool; 5] {
+        fn same<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+            a.is_some() && a == b
+        }
+        [
+            same(&self.uniq, &other.uniq),
+            same(&self.by_id, &other.by_id),
+            same(&self.by_path, &other.by_path),
+            same(&self.phys, &other.phys),
+            same(&self.parent, &other.parent),
+        ]
+    }
+}
+
+impl PartialEq for DeviceIdentity {
+    fn eq(&self, other: &Self) -> bool {
+        let same_uniq = match (&self.uniq, &other.uniq) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        };
+        self.bus_type == other.bus_type
+            && self.vendor == other.vendor
+            && self.product == other.product
+            && self.name == other.name
+            && same_uniq
+    }
+}
+
+/// The symlinks udev created in `/dev/input/by-id` and `/dev/input/by-path`, along with the
+/// device numbers of the nodes they point to.
+struct Links {
+    by_id: Vec<(PathBuf, libc::dev_t)>,
+    by_path: Vec<(PathBuf, libc::dev_t)>,
+}
+
+impl Links {
+    fn read() -> Self {
+        Links {
+            by_id: read_links(BY_ID_DIR),
+            by_path: read_links(BY_PATH_DIR),
+        }
+    }
+
+    fn find(&self, links: &[(PathBuf, libc::dev_t)], rdev: libc::dev_t) -> Option<PathBuf> {
+        links
+            .iter()
+            .find(|&&(_, link_rdev)| link_rdev == rdev)
+            .map(|(path, _)| path.clone())
+    }
+}
+
+fn read_links(dir: &str) -> Vec<(PathBuf, libc::dev_t)> {
+    // without udev, these directories don't exist
+    let readdir = match fs::read_dir(dir) {
+        Ok(readdir) => readdir,
+        Err(_) => return Vec::new(),
+    };
+    let mut links: Vec<_> = readdir
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let metadata = fs::metadata(&path).ok()?;
+            if !metadata.file_type().is_char_device() {
+                return None;
+            }
+            Some((path, metadata.rdev() as libc::dev_t))
+        })
+        .collect();
+    // so that the same link is picked every time if there are several for a node
+    links.sort();
+    links
+}
@@ -0,0 +1,262 @@
+//! Translating key codes to characters with keyboard layouts.
+//!
+//! A [`Keymap`] assigns up to four symbols to every key: the plain one, the one with Shift, and
+//! the ones with AltGr and AltGr plus Shift. A symbol is either a character or a dead key, which
+//! doesn't type anything itself but puts an accent on the next character, as on the `´` key of a
+//! German keyboard. Caps Lock shifts the keys whose shifted symbol is the uppercase version of the
+//! plain one, and Num Lock turns the keypad into digits.
+//!
+//! There are built-in layouts for US English (`us`), UK English (`gb`), German (`de`), French
+//! (`fr`) and US Dvorak (`dvorak`). More can be loaded from a simple text format:
+//!
+//! ```text
+//! # a comment
+//! include us
+//! KEY_Y z Z
+//! KEY_Z y Y
+//! KEY_Q q Q @
+//! KEY_EQUAL dead:´ dead:`
+//! KEY_KP1 none 1
+//! ```
+//!
+//! Every line but comments names a key code and lists its symbols in the order above. A symbol is
+//! a single character, `space`, `U+` and a hexadecimal code point, `dead:` followed by the accent
+//! of a dead key, or `none`. `include` copies all keys of a built-in layout, and later lines
+//! replace the symbols of keys that were given before.
+//!
+//! ```
+//! use evdev::keymap::Keymap;
+//! use evdev::{KeyCode, ModifierState};
+//!
+//! let mut keymap = Keymap::builtin("de").unwrap();
+//! let mut modifiers = ModifierState::new();
+//! assert_eq!(keymap.translate(&modifiers, KeyCode::KEY_Y), Some('z'));
+//! modifiers.set_held(KeyCode::KEY_LEFTSHIFT, true);
+//! assert_eq!(keymap.translate(&modifiers, KeyCode::KEY_EQUAL), None);
+//! modifiers.set_held(KeyCode::KEY_LEFTSHIFT, false);
+//! assert_eq!(keymap.translate(&modifiers, KeyCode::KEY_E), Some('è'));
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::{KeyCode, ModifierState};
+
+/// The built-in layouts, by name.
+const BUILTIN: [(&str, &str); 5] = [
+    ("us", include_str!("keymap/us.txt")),
+    ("gb", include_str!("keymap/gb.txt")),
+    ("de", include_str!("keymap/de.txt")),
+    ("fr", include_str!("keymap/fr.txt")),
+    ("dvorak", include_str!("keymap/dvorak.txt")),
+];
+
+/// The keys whose second level is selected by Num Lock rather than Shift.
+const KEYPAD: [KeyCode; 11] = [
+    KeyCode::KEY_KP0,
+    KeyCode::KEY_KP1,
+    KeyCode::KEY_KP2,
+    KeyCode::KEY_KP3,
+    KeyCode::KEY_KP4,
+    KeyCode::KEY_KP5,
+    KeyCode::KEY_KP6,
+    KeyCode::KEY_KP7,
+    KeyCode::KEY_KP8,
+    KeyCode::KEY_KP9,
+    KeyCode::KEY_KPDOT,
+];
+
+/// The characters with an accent, by the accent of the dead key and the character it goes on.
+const COMPOSE: [(char, &str, &str); 6] = [
+    ('`', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+    ('´', "aeiouyAEIOUY", "áéíóúýÁÉÍÓÚÝ"),
+    ('^', "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+    ('~', "anoANO", "ãñõÃÑÕ"),
+    ('¨', "aeiouyAEIOUY", "äëïöüÿÄËÏÖÜŸ"),
+    ('¸', "cC", "çÇ"),
+];
+
+/// What a key types at one of its levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Keysym {
+    /// A character.
+    Char(char),
+    /// A dead key, which puts this accent on the next character.
+    Dead(char),
+}
+
+/// A keyboard layout, along with the dead key that is waiting for the next character.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    keys: HashMap<KeyCode, [Option<Keysym>; 4]>,
+    pending: Option<char>,
+}
+
+impl Keymap {
+    /// Returns the built-in layout called `name`, e.g. `"us"`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        let (_, text) = BUILTIN.iter().find(|(n, _)| *n == name)?;
+        Some(Self::parse(text).expect("the built-in layouts are valid"))
+    }
+
+    /// Returns the names of the built-in layouts.
+    pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+        BUILTIN.iter().map(|(name, _)| *name)
+    }
+
+    /// Parses a layout in the text format described in the [module-level documentation](self).
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] naming the line that isn't valid.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut keys = HashMap::new();
+        for (i, line) in text.lines().enumerate() {
+            parse_line(&mut keys, line).map_err(|msg| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {msg}", i + 1))
+            })?;
+        }
+        Ok(Keymap {
+            keys,
+            pending: None,
+        })
+    }
+
+    /// Reads and parses the layout in the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Returns the symbol `key` has with the given modifiers, without taking dead keys into
+    /// account.
+    ///
+    /// Ctrl, Alt and Meta don't change the symbol, and AltGr only does on keys that have
+    /// symbols for it.
+    pub fn lookup(&self, modifiers: &ModifierState, key: KeyCode) -> Option<Keysym> {
+        let levels = self.keys.get(&key)?;
+        if KEYPAD.contains(&key) {
+            let level = (modifiers.num_lock() != modifiers.shift()) as usize;
+            return levels[level];
+        }
+        let altgr = modifiers.altgr() && (levels[2].is_some() || levels[3].is_some());
+        let base = if altgr { 2 } else { 0 };
+        let caps = modifiers.caps_lock() && is_alphabetic(levels[base], levels[base + 1]);
+        if modifiers.shift() != caps {
+            levels[base + 1].or(levels[base])
+        } else {
+            levels[base]
+        }
+    }
+
+    /// Returns the character typed by pressing `key` with the given modifiers.
+    ///
+    /// Call this for every key that goes down or repeats. A dead key returns `None` and puts its
+    /// accent on the character of the next key, if there is one with that accent. Otherwise the
+    /// accent is dropped, except for a space or the same dead key, which type the accent itself.
+    /// Keys without a symbol, like the modifiers, don't affect a pending dead key.
+    pub fn translate(&mut self, modifiers: &ModifierState, key: KeyCode) -> Option<char> {
+        match (self.lookup(modifiers, key)?, self.pending.take()) {
+            (Keysym::Dead(accent), Some(pending)) if accent == pending => Some(accent),
+            (Keysym::Dead(accent), _) => {
+                self.pending = Some(accent);
+                None
+            }
+            (Keysym::Char(' '), Some(accent)) => Some(accent),
+            (Keysym::Char(c), Some(accent)) => Some(compose(accent, c).unwrap_or(c)),
+            (Keysym::Char(c), None) => Some(c),
+        }
+    }
+
+    /// Returns the accent of the dead key that is waiting for the next character.
+    pub fn pending_dead_key(&self) -> Option<char> {
+        self.pending
+    }
+
+    /// Forgets the dead key that is waiting for the next character, if any.
+    pub fn reset(&mut self) {
+        self.pending = None;
+    }
+}
+
+fn parse_line(keys: &mut HashMap<KeyCode, [Option<Keysym>; 4]>, line: &str) -> Result<(), String> {
+    let mut words = line.split_whitespace();
+    let first = match words.next() {
+        Some(word) if !word.starts_with('#') => word,
+        _ => return Ok(()),
+    };
+    if first == "include" {
+        let name = words.next().ok_or("`include` needs the name of a layout")?;
+        let (_, text) = BUILTIN
+            .iter()
+            .find(|(n, _)| *n == name)
+            .ok_or_else(|| format!("there is no built-in layout called `{name}`"))?;
+        for line in text.lines() {
+            parse_line(keys, line)?;
+        }
+        return Ok(());
+    }
+
+    let key: KeyCode = first
+        .parse()
+        .map_err(|_| format!("`{first}` is not a key code"))?;
+    let mut levels = [None; 4];
+    let mut count = 0;
+    for word in words {
+        if count == levels.len() {
+            return Err("a key has at most four symbols".to_owned());
+        }
+        levels[count] = parse_keysym(word)?;
+        count += 1;
+    }
+    if count == 0 {
+        return Err(format!("`{first}` has no symbols"));
+    }
+    keys.insert(key, levels);
+    Ok(())
+}
+
+fn parse_keysym(word: &str) -> Result<Option<Keysym>, String> {
+    let single = |s: &str| {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    };
+    let sym = match word {
+        "none" => return Ok(None),
+        "space" => Keysym::Char(' '),
+        _ => {
+            if let Some(hex) = word.strip_prefix("U+") {
+                let c = u32::from_str_radix(hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("`{word}` is not a character"))?;
+                Keysym::Char(c)
+            } else if let Some(accent) = word.strip_prefix("dead:") {
+                Keysym::Dead(single(accent).ok_or_else(|| format!("`{word}` is not a dead key"))?)
+            } else {
+                Keysym::Char(single(word).ok_or_else(|| format!("`{word}` is not a symbol"))?)
+            }
+        }
+    };
+    Ok(Some(sym))
+}
+
+/// Returns `true` if the two symbols are a lowercase character and its uppercase version.
+fn is_alphabetic(lower: Option<Keysym>, upper: Option<Keysym>) -> bool {
+    match (lower, upper) {
+        (Some(Keysym::Char(lower)), Some(Keysym::Char(upper))) => {
+            lower != upper && lower.to_uppercase().eq(std::iter::once(upper))
+        }
+        _ => false,
+    }
+}
+
+fn compose(accent: char, c: char) -> Option<char> {
+    let (_, bases, composed) = COMPOSE.iter().find(|(a, _, _)| *a == accent)?;
+    let i = bases.chars().position(|base| base == c)?;
+    composed.chars().nth(i)
+}
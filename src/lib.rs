@@ -147,6 +147,10 @@
 //! async runtime with the fd returned by `<Device as AsRawFd>::as_raw_fd` to process events when
 //! they are ready.
 //!
+//! Besides tokio, the `async-io` feature provides streams for `smol` and `async-std` in the
+//! `async_io` module, the `mio` feature implements `mio::event::Source` for the device types, and
+//! the `calloop` feature provides event sources for calloop in the `calloop` module.
+//!
 //! For demonstrations of how to use this library in blocking, nonblocking, and async (tokio) modes,
 //! please reference the "examples" directory.
 
@@ -164,19 +168,49 @@
 // has to be first for its macro
 #[macro_use]
 mod attribute_set;
-
+#[cfg(feature = "async-io")]
+pub mod async_io;
+#[cfg(any(feature = "tokio", feature = "async-io"))]
+mod async_stream;
+pub mod axis;
+#[cfg(feature = "calloop")]
+pub mod calloop;
+mod classify;
+mod clock;
 mod compat;
 mod constants;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod device_set;
 mod device_state;
+mod event_mask;
 pub mod event_variants;
 mod ff;
+mod filter;
+mod frame;
+pub mod gamepad;
+pub mod gestures;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod identity;
 mod inputid;
+pub mod keymap;
+#[cfg(feature = "mio")]
+mod mio_source;
+mod modifiers;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod monitor;
+mod multitouch;
+pub mod pointer;
 pub mod raw_stream;
+pub mod repeat;
 mod scancodes;
 mod sync_stream;
 mod sys;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod sysfs;
+pub mod tablet;
 #[cfg(test)]
 mod tests;
+pub mod touchpad;
 pub mod uinput;
 
 use crate::compat::{input_absinfo, input_event, uinput_abs_setup};
@@ -187,13 +221,29 @@ use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
 
 pub use attribute_set::{AttributeSet, AttributeSetRef, EvdevEnum};
+pub use classify::DeviceClasses;
+pub use clock::Clock;
 pub use constants::*;
-pub use device_state::DeviceState;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use device_set::{
+    DeviceKey, DeviceSet, FetchSetEvents, FetchSetFrames, SetEvent, SetFrame, SetMember,
+};
+pub use device_state::{DeviceState, MtSlots};
+pub use event_mask::EventMask;
 pub use event_variants::*;
 pub use ff::*;
+pub use filter::{enumerate_matching, DeviceFilter, EnumerateMatching};
+pub use frame::Frame;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use identity::DeviceIdentity;
 pub use inputid::*;
+pub use modifiers::ModifierState;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use monitor::*;
 pub use scancodes::*;
 pub use sync_stream::*;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use sysfs::SysfsInfo;
 
 macro_rules! common_trait_impls {
     ($raw:ty, $wrapper:ty) => {
@@ -371,11 +421,23 @@ common_trait_impls!(input_event, InputEvent);
 
 impl InputEvent {
     /// Returns the timestamp associated with the event.
+    ///
+    /// This is only meaningful as a `SystemTime` if the device uses [`Clock::Realtime`], which is
+    /// the default; see [`clock_time`](Self::clock_time) otherwise.
     #[inline]
     pub fn timestamp(&self) -> SystemTime {
         timeval_to_systime(&self.0.time)
     }
 
+    /// Returns the timestamp associated with the event as the duration since the epoch of the
+    /// [`Clock`] the device was set to.
+    ///
+    /// Use [`Clock::to_instant`] to turn [`Clock::Monotonic`] timestamps into an `Instant`.
+    #[inline]
+    pub fn clock_time(&self) -> Duration {
+        clock::timeval_to_duration(&self.0.time)
+    }
+
     /// Returns the type of event this describes, e.g. Key, Switch, etc.
     #[inline]
     pub fn event_type(&self) -> EventType {
@@ -494,6 +556,9 @@ impl Iterator for EnumerateDevices {
     }
 }
 
+/// Where sysfs is normally mounted.
+const SYSFS_ROOT: &str = "/sys";
+
 /// A safe Rust version of clock_gettime against CLOCK_REALTIME
 fn systime_to_timeval(time: &SystemTime) -> libc::timeval {
     let (sign, dur) = match time.duration_since(SystemTime::UNIX_EPOCH) {
@@ -516,6 +581,12 @@ fn timeval_to_systime(tv: &libc::timeval) -> SystemTime {
     }
 }
 
+/// Whether `err` means that the device is gone, i.e. it was unplugged or access to it was revoked.
+#[cfg(any(feature = "tokio", feature = "async-io", feature = "calloop"))]
+fn is_device_gone(err: &io::Error) -> bool {
+    err.raw_os_error() == Some(libc::ENODEV)
+}
+
 /// SAFETY: T must not have any padding or otherwise uninitialized bytes inside of it
 pub(crate) unsafe fn cast_to_bytes<T: ?Sized>(mem: &T) -> &[u8] {
     std::slice::from_raw_parts(mem as *const T as *const u8, std::mem::size_of_val(mem))
@@ -0,0 +1,54 @@
+//! `mio::event::Source` implementations for the device types, see the `evtest_mio` example.
+
+use std::io;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd};
+
+use ::mio::event::Source;
+use ::mio::unix::SourceFd;
+use ::mio::{Interest, Registry, Token};
+use nix::fcntl;
+
+use crate::raw_stream::RawDevice;
+use crate::uinput::VirtualDevice;
+use crate::Device;
+
+fn set_nonblocking(fd: BorrowedFd<'_>) -> io::Result<()> {
+    let mut flags = fcntl::OFlag::from_bits_retain(fcntl::fcntl(fd.as_raw_fd(), fcntl::F_GETFL)?);
+    flags.insert(fcntl::OFlag::O_NONBLOCK);
+    fcntl::fcntl(fd.as_raw_fd(), fcntl::F_SETFL(flags))?;
+    Ok(())
+}
+
+macro_rules! impl_source {
+    ($($ty:ty),*) => {$(
+        /// Registering puts the device into nonblocking mode. As mio's readiness events are
+        /// edge-triggered, keep fetching events after each one until that fails with
+        /// [`WouldBlock`](io::ErrorKind::WouldBlock).
+        impl Source for $ty {
+            fn register(
+                &mut self,
+                registry: &Registry,
+                token: Token,
+                interests: Interest,
+            ) -> io::Result<()> {
+                set_nonblocking(self.as_fd())?;
+                SourceFd(&self.as_raw_fd()).register(registry, token, interests)
+            }
+
+            fn reregister(
+                &mut self,
+                registry: &Registry,
+                token: Token,
+                interests: Interest,
+            ) -> io::Result<()> {
+                SourceFd(&self.as_raw_fd()).reregister(registry, token, interests)
+            }
+
+            fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+                SourceFd(&self.as_raw_fd()).deregister(registry)
+            }
+        }
+    )*};
+}
+
+impl_source!(Device, RawDevice, VirtualDevice);
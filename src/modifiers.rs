@@ -0,0 +1,206 @@
+use std::io;
+
+use crate::{AttributeSet, AttributeSetRef, Device, KeyCode, KeyEvent, LedCode};
+
+/// The modifier keys, in the order of their bits in `ModifierState::keys`.
+const MODIFIER_KEYS: [KeyCode; 8] = [
+    KeyCode::KEY_LEFTSHIFT,
+    KeyCode::KEY_RIGHTSHIFT,
+    KeyCode::KEY_LEFTCTRL,
+    KeyCode::KEY_RIGHTCTRL,
+    KeyCode::KEY_LEFTALT,
+    KeyCode::KEY_RIGHTALT,
+    KeyCode::KEY_LEFTMETA,
+    KeyCode::KEY_RIGHTMETA,
+];
+
+const CAPS_LOCK: u8 = 1 << 0;
+const NUM_LOCK: u8 = 1 << 1;
+const SCROLL_LOCK: u8 = 1 << 2;
+
+/// The lock keys, with their bit in `ModifierState::locks` and the LED that shows them.
+const LOCKS: [(KeyCode, u8, LedCode); 3] = [
+    (KeyCode::KEY_CAPSLOCK, CAPS_LOCK, LedCode::LED_CAPSL),
+    (KeyCode::KEY_NUMLOCK, NUM_LOCK, LedCode::LED_NUML),
+    (KeyCode::KEY_SCROLLLOCK, SCROLL_LOCK, LedCode::LED_SCROLLL),
+];
+
+/// Which modifier keys of a keyboard are held, and which of its locks are active.
+///
+/// The right Alt key is AltGr. Layouts without an AltGr level, like the US one, treat it as Alt.
+///
+/// The state follows a keyboard through [`process_event`](Self::process_event), after being
+/// seeded with [`from_device`](Self::from_device). Pressing Caps Lock, Num Lock or Scroll Lock
+/// toggles the lock, which the kernel doesn't show on the keyboard's LEDs by itself once the
+/// keyboard is grabbed; [`led_states`](Self::led_states) tells which LEDs to set.
+///
+/// ```no_run
+/// use evdev::{Device, EventSummary, EventType, InputEvent, ModifierState};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut device = Device::open("/dev/input/event0")?;
+/// let mut modifiers = ModifierState::from_device(&device)?;
+/// device.grab()?;
+/// loop {
+///     let events: Vec<_> = device.fetch_events()?.collect();
+///     for event in events {
+///         if let EventSummary::Key(event, ..) = event.destructure() {
+///             if modifiers.process_event(event) {
+///                 let leds = modifiers
+///                     .led_states()
+///                     .map(|(led, on)| InputEvent::new(EventType::LED.0, led.0, on as i32));
+///                 device.send_events(&leds)?;
+///             }
+///         }
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ModifierState {
+    keys: u8,
+    locks: u8,
+}
+
+impl ModifierState {
+    /// Creates a state without any modifiers held or locks active.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the state of a keyboard with the given keys held and LEDs lit.
+    pub fn from_state(keys: &AttributeSetRef<KeyCode>, leds: &AttributeSetRef<LedCode>) -> Self {
+        let mut state = Self::new();
+        for key in MODIFIER_KEYS {
+            state.set_held(key, keys.contains(key));
+        }
+        for (_, bit, led) in LOCKS {
+            set_bit(&mut state.locks, bit, leds.contains(led));
+        }
+        state
+    }
+
+    /// Reads the held keys and lit LEDs of `device` from the kernel, see
+    /// [`from_state`](Self::from_state).
+    pub fn from_device(device: &Device) -> io::Result<Self> {
+        let keys = device.get_key_state()?;
+        let leds = device.get_led_state()?;
+        Ok(Self::from_state(&keys, &leds))
+    }
+
+    /// Updates the state with a key of the keyboard going down or up.
+    ///
+    /// Pressing a lock key toggles the lock, in which case this returns `true` so that the LEDs
+    /// can be updated. Repeats and keys that are neither modifiers nor locks are ignored.
+    pub fn process_event(&mut self, event: KeyEvent) -> bool {
+        let (key, value) = event.destructure();
+        match value {
+            0 | 1 => self.set_held(key, value == 1),
+            _ => return false,
+        }
+        match LOCKS.iter().find(|(lock, _, _)| *lock == key) {
+            Some((_, bit, _)) if value == 1 => {
+                self.locks ^= bit;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the LEDs of Caps Lock, Num Lock and Scroll Lock, and whether they should be lit.
+    pub fn led_states(&self) -> [(LedCode, bool); 3] {
+        LOCKS.map(|(_, bit, led)| (led, self.locks & bit != 0))
+    }
+
+    /// Returns the LEDs that should be lit for the active locks.
+    pub fn leds(&self) -> AttributeSet<LedCode> {
+        self.led_states()
+            .into_iter()
+            .filter(|&(_, on)| on)
+            .map(|(led, _)| led)
+            .collect()
+    }
+
+    /// Returns `true` if `key` is one of the Shift, Ctrl, Alt or Meta keys.
+    pub fn is_modifier(key: KeyCode) -> bool {
+        MODIFIER_KEYS.contains(&key)
+    }
+
+    /// Returns `true` if the modifier `key` is held.
+    pub fn is_held(&self, key: KeyCode) -> bool {
+        match MODIFIER_KEYS.iter().position(|&k| k == key) {
+            Some(i) => self.keys & (1 << i) != 0,
+            None => false,
+        }
+    }
+
+    /// Sets whether the modifier `key` is held. Other keys are ignored.
+    pub fn set_held(&mut self, key: KeyCode, held: bool) {
+        if let Some(i) = MODIFIER_KEYS.iter().position(|&k| k == key) {
+            set_bit(&mut self.keys, 1 << i, held);
+        }
+    }
+
+    /// Returns `true` if either Shift key is held.
+    pub fn shift(&self) -> bool {
+        self.is_held(KeyCode::KEY_LEFTSHIFT) || self.is_held(KeyCode::KEY_RIGHTSHIFT)
+    }
+
+    /// Returns `true` if either Ctrl key is held.
+    pub fn ctrl(&self) -> bool {
+        self.is_held(KeyCode::KEY_LEFTCTRL) || self.is_held(KeyCode::KEY_RIGHTCTRL)
+    }
+
+    /// Returns `true` if either Alt key is held, including AltGr.
+    pub fn alt(&self) -> bool {
+        self.is_held(KeyCode::KEY_LEFTALT) || self.is_held(KeyCode::KEY_RIGHTALT)
+    }
+
+    /// Returns `true` if either Meta key, also known as Super or the Windows key, is held.
+    pub fn meta(&self) -> bool {
+        self.is_held(KeyCode::KEY_LEFTMETA) || self.is_held(KeyCode::KEY_RIGHTMETA)
+    }
+
+    /// Returns `true` if AltGr, the right Alt key, is held.
+    pub fn altgr(&self) -> bool {
+        self.is_held(KeyCode::KEY_RIGHTALT)
+    }
+
+    /// Returns `true` if Caps Lock is active.
+    pub fn caps_lock(&self) -> bool {
+        self.locks & CAPS_LOCK != 0
+    }
+
+    /// Returns `true` if Num Lock is active.
+    pub fn num_lock(&self) -> bool {
+        self.locks & NUM_LOCK != 0
+    }
+
+    /// Returns `true` if Scroll Lock is active.
+    pub fn scroll_lock(&self) -> bool {
+        self.locks & SCROLL_LOCK != 0
+    }
+
+    /// Sets whether Caps Lock is active.
+    pub fn set_caps_lock(&mut self, active: bool) {
+        set_bit(&mut self.locks, CAPS_LOCK, active);
+    }
+
+    /// Sets whether Num Lock is active.
+    pub fn set_num_lock(&mut self, active: bool) {
+        set_bit(&mut self.locks, NUM_LOCK, active);
+    }
+
+    /// Sets whether Scroll Lock is active.
+    pub fn set_scroll_lock(&mut self, active: bool) {
+        set_bit(&mut self.locks, SCROLL_LOCK, active);
+    }
+}
+
+fn set_bit(bits: &mut u8, bit: u8, on: bool) {
+    if on {
+        *bits |= bit;
+    } else {
+        *bits &= !bit;
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::{HashSet, VecDeque};
+use std::io;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd};
+use std::path::PathBuf;
+
+use nix::fcntl;
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify, InotifyEvent};
+
+use crate::raw_stream::is_event_node;
+use crate::Device;
+
+/// A change to the set of evdev devices, reported by a [`DeviceMonitor`].
+#[derive(Debug)]
+// boxing the device would only save a move when a device is removed
+#[allow(clippy::large_enum_variant)]
+pub enum MonitorEvent {
+    /// A device node appeared and was opened.
+    Added(PathBuf, Device),
+    /// A device node was removed.
+    Removed(PathBuf),
+    /// The kernel's queue of changes overflowed, so some additions and removals were lost.
+    /// Enumerate the devices again to catch up.
+    Overflowed,
+}
+
+/// Watches `/dev/input` for evdev devices being added or removed.
+///
+/// Only changes are reported, so create the monitor before calling [`enumerate`](crate::enumerate)
+/// to not miss a device that is plugged in in between.
+///
+/// New device nodes are sometimes created before udev has applied their permissions. If a node
+/// can't be opened for that reason, the monitor retries once its permissions change and only
+/// reports it as added once it could be opened.
+///
+/// If changes come in faster than they are read, the kernel drops them. The monitor doesn't rescan
+/// `/dev/input` by itself then, but reports [`MonitorEvent::Overflowed`] so that the devices can be
+/// enumerated again.
+///
+/// The monitor can be used as a blocking [`Iterator`], or polled for readability through its file
+/// descriptor and drained with [`fetch_events`](Self::fetch_events) after calling
+/// [`set_nonblocking`](Self::set_nonblocking). With the `tokio` feature, it can be turned into a
+/// [`DeviceMonitorStream`] as well.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use evdev::{DeviceMonitor, MonitorEvent};
+///
+/// for event in DeviceMonitor::new()? {
+///     match event? {
+///         MonitorEvent::Added(path, device) => println!("{}: {:?}", path.display(), device.name()),
+///         MonitorEvent::Removed(path) => println!("{} removed", path.display()),
+///         MonitorEvent::Overflowed => println!("some changes were lost"),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct DeviceMonitor {
+    inotify: Inotify,
+    dir: PathBuf,
+    /// Nodes that appeared but couldn't be opened yet because of their permissions.
+    pending: HashSet<PathBuf>,
+    queue: VecDeque<MonitorEvent>,
+}
+
+impl DeviceMonitor {
+    /// Starts watching `/dev/input`.
+    pub fn new() -> io::Result<Self> {
+        Self::watch("/dev/input")
+    }
+
+    fn watch(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        let inotify = Inotify::init(InitFlags::IN_CLOEXEC)?;
+        inotify.add_watch(
+            &dir,
+            AddWatchFlags::IN_CREATE
+                | AddWatchFlags::IN_MOVED_TO
+                | AddWatchFlags::IN_ATTRIB
+                | AddWatchFlags::IN_DELETE
+                | AddWatchFlags::IN_MOVED_FROM,
+        )?;
+        Ok(DeviceMonitor {
+            inotify,
+            dir,
+            pending: HashSet::new(),
+            queue: VecDeque::new(),
+        })
+    }
+
+    /// Reads the pending changes and returns the resulting events.
+    ///
+    /// By default this will block until there are changes. Note that a change doesn't necessarily
+    /// produce an event, e.g. when a node is created that isn't an evdev device.
+    pub fn fetch_events(&mut self) -> io::Result<impl Iterator<Item = MonitorEvent> + '_> {
+        self.fill_events()?;
+        Ok(self.queue.drain(..))
+    }
+
+    /// Set `O_NONBLOCK` on the monitor's file descriptor.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        let mut flags =
+            fcntl::OFlag::from_bits_retain(fcntl::fcntl(self.as_raw_fd(), fcntl::F_GETFL)?);
+        flags.set(fcntl::OFlag::O_NONBLOCK, nonblocking);
+        fcntl::fcntl(self.as_raw_fd(), fcntl::F_SETFL(flags))?;
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    pub fn into_event_stream(self) -> io::Result<DeviceMonitorStream> {
+        DeviceMonitorStream::new(self)
+    }
+
+    fn fill_events(&mut self) -> io::Result<()> {
+        for event in self.inotify.read_events()? {
+            self.process_event(event);
+        }
+        Ok(())
+    }
+
+    fn process_event(&mut self, event: InotifyEvent) {
+        if event.mask.contains(AddWatchFlags::IN_Q_OVERFLOW) {
+            self.queue.push_back(MonitorEvent::Overflowed);
+            return;
+        }
+        let path = match event.name {
+            Some(name) => self.dir.join(name),
+            None => return,
+        };
+        if !is_event_node(&path) {
+            return;
+        }
+
+        if event
+            .mask
+            .intersects(AddWatchFlags::IN_DELETE | AddWatchFlags::IN_MOVED_FROM)
+        {
+            // devices that were never opened were never reported as added either
+            if !self.pending.remove(&path) {
+                self.queue.push_back(MonitorEvent::Removed(path));
+            }
+            return;
+        }
+
+        let appeared = event
+            .mask
+            .intersects(AddWatchFlags::IN_CREATE | AddWatchFlags::IN_MOVED_TO);
+        if appeared || self.pending.contains(&path) {
+            self.open(path);
+        }
+    }
+
+    fn open(&mut self, path: PathBuf) {
+        match Device::open(&path) {
+            Ok(device) => {
+                self.pending.remove(&path);
+                self.queue.push_back(MonitorEvent::Added(path, device));
+            }
+            // udev hasn't gotten to it yet, try again once the permissions change
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                self.pending.insert(path);
+            }
+            // most likely, the device is already gone again
+            Err(_) => {
+                self.pending.remove(&path);
+            }
+        }
+    }
+}
+
+impl Iterator for DeviceMonitor {
+    type Item = io::Result<MonitorEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.queue.pop_front() {
+                return Some(Ok(event));
+            }
+            if let Err(e) = self.fill_events() {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+impl AsFd for DeviceMonitor {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inotify.as_fd()
+    }
+}
+
+impl AsRawFd for DeviceMonitor {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inotify.as_fd().as_raw_fd()
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod tokio_stream {
+    use super::*;
+
+    use std::future::poll_fn;
+    use std::task::{ready, Context, Poll};
+    use tokio::io::unix::AsyncFd;
+
+    /// An asynchronous stream of [`MonitorEvent`]s.
+    ///
+    /// This can be used by calling [`stream.next_event().await?`](Self::next_event), or if you
+    /// need to pass it as a stream somewhere, the `futures::Stream` implementation.
+    /// There's also a lower-level [`Self::poll_event`] function if you need to fetch an event from
+    /// inside a `Future::poll` impl.
+    pub struct DeviceMonitorStream {
+        monitor: AsyncFd<DeviceMonitor>,
+    }
+    impl Unpin for DeviceMonitorStream {}
+
+    impl DeviceMonitorStream {
+        pub(crate) fn new(monitor: DeviceMonitor) -> io::Result<Self> {
+            monitor.set_nonblocking(true)?;
+            let monitor = AsyncFd::new(monitor)?;
+            Ok(Self { monitor })
+        }
+
+        /// Returns a reference to the underlying monitor.
+        pub fn monitor(&self) -> &DeviceMonitor {
+            self.monitor.get_ref()
+        }
+
+        /// Try to wait for the next event in this stream. Any errors are likely to be fatal, i.e.
+        /// any calls afterwards will likely error as well.
+        pub async fn next_event(&mut self) -> io::Result<MonitorEvent> {
+            poll_fn(|cx| self.poll_event(cx)).await
+        }
+
+        /// A lower-level function for directly polling this stream.
+        pub fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<MonitorEvent>> {
+            loop {
+                if let Some(event) = self.monitor.get_mut().queue.pop_front() {
+                    return Poll::Ready(Ok(event));
+                }
+
+                let mut guard = ready!(self.monitor.poll_read_ready_mut(cx))?;
+
+                let res = guard.try_io(|monitor| monitor.get_mut().fill_events());
+                if let Ok(res) = res {
+                    res?;
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "stream-trait")]
+    impl futures_core::Stream for DeviceMonitorStream {
+        type Item = io::Result<MonitorEvent>;
+        fn poll_next(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Option<Self::Item>> {
+            self.get_mut().poll_event(cx).map(Some)
+        }
+    }
+}
+#[cfg(feature = "tokio")]
+pub use tokio_stream::DeviceMonitorStream;
@@ -0,0 +1,150 @@
+//! Following the contacts of a Type B multi-touch touchpad, as needed by the
+//! [`gestures`](crate::gestures) and [`touchpad`](crate::touchpad) modules.
+
+use std::io;
+
+use crate::device_state::MtSlots;
+use crate::{AbsInfo, AbsoluteAxisCode, Device, EventType, InputEvent, KeyCode};
+
+/// The size assumed for axes that don't report their resolution.
+const ASSUMED_SIZE_MM: f64 = 100.0;
+
+/// The keys that report the number of fingers on the touchpad, in order.
+const TOOL_KEYS: [KeyCode; 5] = [
+    KeyCode::BTN_TOOL_FINGER,
+    KeyCode::BTN_TOOL_DOUBLETAP,
+    KeyCode::BTN_TOOL_TRIPLETAP,
+    KeyCode::BTN_TOOL_QUADTAP,
+    KeyCode::BTN_TOOL_QUINTTAP,
+];
+
+/// Converts the values of an axis to millimetres.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Axis {
+    minimum: i32,
+    pub(crate) units_per_mm: f64,
+    /// The length of the axis in millimetres.
+    pub(crate) size: f64,
+}
+
+impl Axis {
+    fn new(info: AbsInfo) -> Self {
+        let range = (info.maximum() - info.minimum()).max(1) as f64;
+        let units_per_mm = if info.resolution() > 0 {
+            info.resolution() as f64
+        } else {
+            range / ASSUMED_SIZE_MM
+        };
+        Axis {
+            minimum: info.minimum(),
+            units_per_mm,
+            size: range / units_per_mm,
+        }
+    }
+
+    pub(crate) fn to_mm(self, value: i32) -> f64 {
+        (value - self.minimum) as f64 / self.units_per_mm
+    }
+}
+
+/// The slots of a touchpad and its `BTN_TOOL_*` keys, updated event by event.
+#[derive(Debug, Clone)]
+pub(crate) struct MtTracker {
+    pub(crate) x: Axis,
+    pub(crate) y: Axis,
+    slots: MtSlots,
+    /// Which of the `BTN_TOOL_*` keys in `TOOL_KEYS` are down.
+    tools: [bool; 5],
+}
+
+impl MtTracker {
+    /// Creates a tracker for `device`, with the contacts that are on the touchpad already picked
+    /// up from its cached state.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] if the device doesn't have the
+    /// `ABS_MT_POSITION_X` and `ABS_MT_POSITION_Y` axes.
+    pub(crate) fn new(device: &Device) -> io::Result<Self> {
+        let (mut x, mut y, mut num_slots) = (None, None, 1);
+        for (axis, info) in device.get_absinfo()? {
+            match axis {
+                AbsoluteAxisCode::ABS_MT_POSITION_X => x = Some(info),
+                AbsoluteAxisCode::ABS_MT_POSITION_Y => y = Some(info),
+                AbsoluteAxisCode::ABS_MT_SLOT => num_slots = info.maximum().max(0) as usize + 1,
+                _ => {}
+            }
+        }
+        let (x, y) = match (x, y) {
+            (Some(x), Some(y)) => (x, y),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the device doesn't report multi-touch positions",
+                ))
+            }
+        };
+
+        let mut tracker = Self::from_axes(x, y, num_slots);
+        let state = device.cached_state();
+        if let Some(mt) = state.mt_vals() {
+            if mt.num_slots() == num_slots {
+                tracker.slots = mt.clone();
+            }
+        }
+        if let Some(keys) = state.key_vals() {
+            for (down, &key) in tracker.tools.iter_mut().zip(&TOOL_KEYS) {
+                *down = keys.contains(key);
+            }
+        }
+        Ok(tracker)
+    }
+
+    /// Creates a tracker for a touchpad with the given position axes and number of slots.
+    pub(crate) fn from_axes(x: AbsInfo, y: AbsInfo, num_slots: usize) -> Self {
+        MtTracker {
+            x: Axis::new(x),
+            y: Axis::new(y),
+            slots: MtSlots::new(num_slots),
+            tools: [false; 5],
+        }
+    }
+
+    /// Applies an `ABS_MT_*` or `BTN_TOOL_*` event. Other events are ignored.
+    pub(crate) fn apply(&mut self, ev: InputEvent) {
+        match ev.event_type() {
+            EventType::ABSOLUTE => self.slots.apply(AbsoluteAxisCode(ev.code()), ev.value()),
+            EventType::KEY => {
+                if let Some(i) = TOOL_KEYS.iter().position(|key| key.code() == ev.code()) {
+                    self.tools[i] = ev.value() != 0;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn slots(&self) -> &MtSlots {
+        &self.slots
+    }
+
+    /// Returns the value of `axis` in `slot`, or 0 if the device didn't report it.
+    pub(crate) fn get(&self, slot: usize, axis: AbsoluteAxisCode) -> i32 {
+        self.slots.get(slot, axis).unwrap_or(0)
+    }
+
+    /// Returns the position of the contact in `slot`, in millimetres.
+    pub(crate) fn position(&self, slot: usize) -> (f64, f64) {
+        (
+            self.x
+                .to_mm(self.get(slot, AbsoluteAxisCode::ABS_MT_POSITION_X)),
+            self.y
+                .to_mm(self.get(slot, AbsoluteAxisCode::ABS_MT_POSITION_Y)),
+        )
+    }
+
+    /// Returns the number of fingers the `BTN_TOOL_*` keys report.
+    pub(crate) fn tool_fingers(&self) -> usize {
+        self.tools
+            .iter()
+            .rposition(|&down| down)
+            .map_or(0, |i| i + 1)
+    }
+}
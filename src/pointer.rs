@@ -0,0 +1,535 @@
+//! Pointer acceleration and scrolling for relative devices.
+//!
+//! An [`Accelerator`] scales the `REL_X`/`REL_Y` motion of a mouse or trackball by a factor that
+//! depends on how fast the pointer moves, with the same profiles as libinput: a
+//! [flat](AccelProfile::Flat) one that always applies the same factor, the
+//! [adaptive](AccelProfile::Adaptive) default and a [custom curve](AccelCurve).
+//!
+//! The velocity is measured from the timestamps of the events, so the accelerator has to see all
+//! `REL_X`/`REL_Y` events of one frame in a single call to [`Accelerator::accelerate`]. Fractions
+//! of a unit that can't be emitted are carried over to the next frame, so slow movements are not
+//! lost. Events take and keep the device's units: the [resolution](Accelerator::dpi) only serves
+//! to measure velocities the same way on every device.
+//!
+//! A [`ScrollAccumulator`] reconciles the high-resolution and the legacy wheel events of a mouse,
+//! which report the same scrolling twice, into one [`ScrollDelta`] per frame. That has the
+//! fractional distance scrolled, the whole detents derived from it, and can be turned back into
+//! both kinds of events, so that a re-emitting device always has both, even if the mouse only
+//! reports the legacy ones.
+//!
+//! ```no_run
+//! use evdev::pointer::{AccelProfile, Accelerator};
+//! use evdev::uinput::VirtualDevice;
+//! use evdev::{Device, EventSummary, InputEvent};
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let mut device = Device::open("/dev/input/event0")?;
+//! let mut virt = VirtualDevice::builder()?
+//!     .name("Accelerated mouse")
+//!     .with_relative_axes(device.supported_relative_axes().unwrap())?
+//!     .with_keys(device.supported_keys().unwrap())?
+//!     .build()?;
+//! let mut accel = Accelerator::new(AccelProfile::Adaptive).speed(0.3);
+//! device.grab()?;
+//! loop {
+//!     let mut frames = device.fetch_frames()?;
+//!     while let Some(frame) = frames.next_frame() {
+//!         let mut rel = Vec::new();
+//!         let mut out = Vec::new();
+//!         for event in frame {
+//!             match event.destructure() {
+//!                 EventSummary::RelativeAxis(ev, ..) => rel.push(ev),
+//!                 _ => out.push(event),
+//!             }
+//!         }
+//!         out.extend(accel.accelerate(rel).map(InputEvent::from));
+//!         virt.emit(&out)?;
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::io;
+use std::time::Duration;
+
+use crate::compat::input_event;
+use crate::{Device, EventType, InputEvent, RelativeAxisCode, RelativeAxisEvent};
+
+/// The resolution velocities are normalized to, in dots per inch.
+const DEFAULT_DPI: f64 = 1000.0;
+
+/// Frames further apart than this don't belong to the same movement.
+const MOTION_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// The time assumed to have passed before the first frame of a movement, that of a 125 Hz mouse.
+const FIRST_FRAME_INTERVAL_MS: f64 = 8.0;
+
+/// The `REL_*_HI_RES` units that make one detent of a wheel.
+const HI_RES_PER_DETENT: i32 = 120;
+
+/// How an [`Accelerator`] turns the velocity of the pointer into an acceleration factor.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccelProfile {
+    /// The motion is scaled by the same factor regardless of the velocity, `1 + speed`.
+    Flat,
+    /// libinput's default: slow movements are decelerated for precision, and movements faster
+    /// than a threshold are accelerated linearly up to a maximum factor. The
+    /// [speed](Accelerator::speed) lowers the threshold and raises the incline and the maximum.
+    Adaptive,
+    /// The output velocity is looked up on a curve; the speed setting is ignored.
+    Custom(AccelCurve),
+}
+
+/// The curve of a [custom](AccelProfile::Custom) acceleration profile.
+///
+/// Like libinput's custom profile, the curve is given as output velocities for input velocities
+/// at a fixed `step`: `points[i]` is the output for an input of `i * step`. Velocities are in
+/// units per millisecond at 1000 dpi. Between points the curve is interpolated linearly, and past
+/// the last point it is extended with the slope of the last segment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccelCurve {
+    step: f64,
+    points: Vec<f64>,
+}
+
+impl AccelCurve {
+    /// Creates a curve from output velocities at multiples of `step`.
+    ///
+    /// Fails with `InvalidInput` unless `step` is positive, there are between 2 and 64 points,
+    /// and all of them are finite and not negative.
+    pub fn new(step: f64, points: Vec<f64>) -> io::Result<Self> {
+        if !(step.is_finite() && step > 0.0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the step of an acceleration curve must be positive",
+            ));
+        }
+        if !(2..=64).contains(&points.len()) || !points.iter().all(|p| p.is_finite() && *p >= 0.0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "an acceleration curve needs 2 to 64 points that are not negative",
+            ));
+        }
+        Ok(AccelCurve { step, points })
+    }
+
+    /// Returns the distance between the input velocities of two points.
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Returns the output velocities of the curve.
+    pub fn points(&self) -> &[f64] {
+        &self.points
+    }
+
+    /// Returns the output velocity for the input velocity `v`.
+    pub fn output(&self, v: f64) -> f64 {
+        let last = self.points.len() - 1;
+        let pos = v.max(0.0) / self.step;
+        let i = (pos.floor() as usize).min(last - 1);
+        let (a, b) = (self.points[i], self.points[i + 1]);
+        a + (b - a) * (pos - i as f64)
+    }
+}
+
+/// Scales relative pointer motion depending on its velocity.
+///
+/// See the [module-level documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct Accelerator {
+    profile: AccelProfile,
+    speed: f64,
+    dpi: f64,
+    remainder: (f64, f64),
+    /// The time and velocity of the last frame with motion.
+    last: Option<(Duration, f64)>,
+}
+
+impl Accelerator {
+    /// Creates an accelerator with the given profile, a speed of 0 and a resolution of 1000 dpi.
+    pub fn new(profile: AccelProfile) -> Self {
+        Accelerator {
+            profile,
+            speed: 0.0,
+            dpi: DEFAULT_DPI,
+            remainder: (0.0, 0.0),
+            last: None,
+        }
+    }
+
+    /// Sets the pointer speed, from -1 (slowest) to 1 (fastest), like libinput's speed setting.
+    ///
+    /// Values outside of that range are clamped.
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = if speed.is_nan() {
+            0.0
+        } else {
+            speed.clamp(-1.0, 1.0)
+        };
+        self
+    }
+
+    /// Sets the resolution of the device in dots per inch, so that its velocities are measured
+    /// like those of a 1000 dpi mouse.
+    ///
+    /// A resolution of 0 is ignored.
+    pub fn dpi(mut self, dpi: u32) -> Self {
+        if dpi > 0 {
+            self.dpi = dpi as f64;
+        }
+        self
+    }
+
+    /// Returns the profile of this accelerator.
+    pub fn profile(&self) -> &AccelProfile {
+        &self.profile
+    }
+
+    /// Returns the pointer speed, from -1 to 1.
+    pub fn get_speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Forgets the velocity of the last frame and the fractions carried over from it.
+    ///
+    /// Call this when the motion was interrupted without the accelerator seeing it, e.g. when
+    /// motion was discarded for a while.
+    pub fn reset(&mut self) {
+        self.remainder = (0.0, 0.0);
+        self.last = None;
+    }
+
+    /// Accelerates the relative events of one frame.
+    ///
+    /// The `REL_X` and `REL_Y` events are combined into at most one accelerated event for each
+    /// axis, which are left out if the motion amounts to less than a unit. Other relative axes
+    /// such as the wheels are passed through unchanged. The events keep the timestamp of the
+    /// input.
+    pub fn accelerate(
+        &mut self,
+        events: impl IntoIterator<Item = RelativeAxisEvent>,
+    ) -> impl Iterator<Item = RelativeAxisEvent> {
+        let mut motion = None;
+        let (mut dx, mut dy) = (0i64, 0i64);
+        let mut others = Vec::new();
+        for event in events {
+            match event.code() {
+                RelativeAxisCode::REL_X => dx += event.value() as i64,
+                RelativeAxisCode::REL_Y => dy += event.value() as i64,
+                _ => {
+                    others.push(event);
+                    continue;
+                }
+            }
+            motion.get_or_insert(event);
+        }
+
+        let mut out = Vec::with_capacity(2 + others.len());
+        if let Some(template) = motion {
+            if dx != 0 || dy != 0 {
+                let factor = self.factor(dx as f64, dy as f64, template.clock_time());
+                let x = dx as f64 * factor + self.remainder.0;
+                let y = dy as f64 * factor + self.remainder.1;
+                let (ox, oy) = (x.trunc(), y.trunc());
+                self.remainder = (x - ox, y - oy);
+                for (code, value) in [(RelativeAxisCode::REL_X, ox), (RelativeAxisCode::REL_Y, oy)]
+                {
+                    if value != 0.0 {
+                        out.push(with_value(template, code, value as i32));
+                    }
+                }
+            }
+        }
+        out.extend(others);
+        out.into_iter()
+    }
+
+    /// Returns the acceleration factor for a motion of `dx`/`dy` at `time`, and remembers its
+    /// velocity.
+    fn factor(&mut self, dx: f64, dy: f64, time: Duration) -> f64 {
+        let interval = match self.last {
+            Some((last, _)) if time >= last && time - last <= MOTION_TIMEOUT => {
+                Some((time - last).as_secs_f64() * 1000.0)
+            }
+            _ => {
+                self.reset();
+                None
+            }
+        };
+        let last_velocity = self.last.map(|(_, v)| v);
+        let distance = dx.hypot(dy) * DEFAULT_DPI / self.dpi;
+        let velocity = match (interval, last_velocity) {
+            // events that arrive with the same timestamp continue the last velocity
+            (Some(ms), Some(v)) if ms <= 0.0 => v,
+            (Some(ms), _) => distance / ms,
+            (None, _) => distance / FIRST_FRAME_INTERVAL_MS,
+        };
+        self.last = Some((time, velocity));
+
+        match last_velocity {
+            // Simpson's rule over the velocities of the last and this frame, which smooths jumps
+            Some(last) => {
+                (self.profile_factor(last)
+                    + 4.0 * self.profile_factor((last + velocity) / 2.0)
+                    + self.profile_factor(velocity))
+                    / 6.0
+            }
+            None => self.profile_factor(velocity),
+        }
+    }
+
+    /// Returns the acceleration factor of the profile at velocity `v` in units/ms at 1000 dpi.
+    fn profile_factor(&self, v: f64) -> f64 {
+        match &self.profile {
+            AccelProfile::Flat => 1.0 + self.speed,
+            AccelProfile::Adaptive => {
+                let threshold = (0.4 - 0.25 * self.speed).max(0.2);
+                let max_accel = 2.0 + 1.5 * self.speed;
+                let incline = 1.1 + 0.75 * self.speed;
+                let factor = if v < 0.07 {
+                    10.0 * v + 0.3
+                } else if v < threshold {
+                    1.0
+                } else {
+                    1.0 + incline * (v - threshold)
+                };
+                factor.min(max_accel)
+            }
+            AccelProfile::Custom(curve) => {
+                if v > 0.0 {
+                    curve.output(v) / v
+                } else {
+                    // the slope at the origin
+                    (curve.points[1] - curve.points[0]) / curve.step
+                }
+            }
+        }
+    }
+}
+
+/// Which wheel events a [`ScrollAccumulator`] goes by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ScrollMode {
+    /// Use the legacy events of a wheel until it reports a high-resolution event, and the
+    /// high-resolution ones from then on.
+    #[default]
+    Auto,
+    /// Use the `REL_WHEEL_HI_RES` and `REL_HWHEEL_HI_RES` events, and ignore the legacy ones.
+    HiRes,
+    /// Use the legacy `REL_WHEEL` and `REL_HWHEEL` events, and synthesize the high-resolution
+    /// ones from them.
+    Legacy,
+}
+
+/// The scrolling of one frame, as returned by [`ScrollAccumulator::process_frame`].
+///
+/// Positive values scroll up and to the right, like the wheel events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollDelta {
+    vertical_hi_res: i32,
+    horizontal_hi_res: i32,
+    vertical_detents: i32,
+    horizontal_detents: i32,
+    time: libc::timeval,
+}
+
+impl ScrollDelta {
+    /// Returns the vertical distance scrolled, in detents of the wheel.
+    pub fn vertical(&self) -> f64 {
+        self.vertical_hi_res as f64 / HI_RES_PER_DETENT as f64
+    }
+
+    /// Returns the horizontal distance scrolled, in detents of the wheel.
+    pub fn horizontal(&self) -> f64 {
+        self.horizontal_hi_res as f64 / HI_RES_PER_DETENT as f64
+    }
+
+    /// Returns the vertical distance scrolled, in the 1/120 detents of `REL_WHEEL_HI_RES`.
+    pub fn vertical_hi_res(&self) -> i32 {
+        self.vertical_hi_res
+    }
+
+    /// Returns the horizontal distance scrolled, in the 1/120 detents of `REL_HWHEEL_HI_RES`.
+    pub fn horizontal_hi_res(&self) -> i32 {
+        self.horizontal_hi_res
+    }
+
+    /// Returns the whole detents for `REL_WHEEL` that the vertical scrolling added up to.
+    pub fn vertical_detents(&self) -> i32 {
+        self.vertical_detents
+    }
+
+    /// Returns the whole detents for `REL_HWHEEL` that the horizontal scrolling added up to.
+    pub fn horizontal_detents(&self) -> i32 {
+        self.horizontal_detents
+    }
+
+    /// Returns `true` if nothing was scrolled.
+    pub fn is_empty(&self) -> bool {
+        self.vertical_hi_res == 0 && self.horizontal_hi_res == 0
+    }
+
+    /// Returns the legacy and high-resolution wheel events for this frame, leaving out the ones
+    /// that are 0. They have the timestamp of the first wheel event of the frame.
+    pub fn events(&self) -> impl Iterator<Item = RelativeAxisEvent> {
+        let time = self.time;
+        [
+            (RelativeAxisCode::REL_WHEEL, self.vertical_detents),
+            (RelativeAxisCode::REL_WHEEL_HI_RES, self.vertical_hi_res),
+            (RelativeAxisCode::REL_HWHEEL, self.horizontal_detents),
+            (RelativeAxisCode::REL_HWHEEL_HI_RES, self.horizontal_hi_res),
+        ]
+        .into_iter()
+        .filter(|&(_, value)| value != 0)
+        .map(move |(code, value)| {
+            RelativeAxisEvent::from_event(InputEvent(input_event {
+                time,
+                type_: EventType::RELATIVE.0,
+                code: code.0,
+                value,
+            }))
+        })
+    }
+}
+
+/// One wheel of a [`ScrollAccumulator`].
+#[derive(Debug, Clone, Copy, Default)]
+struct Wheel {
+    /// Whether the wheel reported high-resolution events.
+    hi_res: bool,
+    /// The high-resolution units since the last detent.
+    accumulated: i32,
+    /// The sign of the last movement.
+    direction: i32,
+}
+
+impl Wheel {
+    /// Returns the high-resolution units and the detents for the events of one frame.
+    fn process(
+        &mut self,
+        mode: ScrollMode,
+        legacy: Option<i32>,
+        hi_res: Option<i32>,
+    ) -> (i32, i32) {
+        if mode == ScrollMode::Auto && hi_res.is_some() {
+            self.hi_res = true;
+        }
+        let use_hi_res = match mode {
+            ScrollMode::Auto => self.hi_res,
+            ScrollMode::HiRes => true,
+            ScrollMode::Legacy => false,
+        };
+        let value = if use_hi_res {
+            hi_res.unwrap_or(0)
+        } else {
+            legacy.unwrap_or(0).saturating_mul(HI_RES_PER_DETENT)
+        };
+        if value == 0 {
+            return (0, 0);
+        }
+
+        // like the kernel, count a detent halfway to the next one, since some wheels rest a bit
+        // short of their detents, and start over when the direction changes
+        if value.signum() != self.direction {
+            self.direction = value.signum();
+            self.accumulated = 0;
+        }
+        self.accumulated = self.accumulated.saturating_add(value);
+        if self.accumulated.abs() < HI_RES_PER_DETENT / 2 {
+            return (value, 0);
+        }
+        let mut detents = self.accumulated / HI_RES_PER_DETENT;
+        if detents == 0 {
+            detents = self.accumulated.signum();
+        }
+        self.accumulated -= detents * HI_RES_PER_DETENT;
+        (value, detents)
+    }
+}
+
+/// Combines the legacy and high-resolution wheel events of a mouse.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Clone, Default)]
+pub struct ScrollAccumulator {
+    mode: ScrollMode,
+    vertical: Wheel,
+    horizontal: Wheel,
+}
+
+impl ScrollAccumulator {
+    /// Creates an accumulator that goes by the wheel events `mode` says.
+    pub fn new(mode: ScrollMode) -> Self {
+        ScrollAccumulator {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    /// Creates an accumulator for `device`, which goes by its high-resolution events if it
+    /// supports `REL_WHEEL_HI_RES` and by its legacy events otherwise.
+    pub fn for_device(device: &Device) -> Self {
+        let hi_res = device.supported_relative_axes().map_or(false, |axes| {
+            axes.contains(RelativeAxisCode::REL_WHEEL_HI_RES)
+                || axes.contains(RelativeAxisCode::REL_HWHEEL_HI_RES)
+        });
+        Self::new(if hi_res {
+            ScrollMode::HiRes
+        } else {
+            ScrollMode::Legacy
+        })
+    }
+
+    /// Returns which wheel events the accumulator goes by.
+    pub fn mode(&self) -> ScrollMode {
+        self.mode
+    }
+
+    /// Combines the wheel events of one frame. Other events are ignored.
+    pub fn process_frame(
+        &mut self,
+        events: impl IntoIterator<Item = RelativeAxisEvent>,
+    ) -> ScrollDelta {
+        let mut time = None;
+        let (mut wheel, mut wheel_hi_res) = (None, None);
+        let (mut hwheel, mut hwheel_hi_res) = (None, None);
+        for event in events {
+            let sum = match event.code() {
+                RelativeAxisCode::REL_WHEEL => &mut wheel,
+                RelativeAxisCode::REL_WHEEL_HI_RES => &mut wheel_hi_res,
+                RelativeAxisCode::REL_HWHEEL => &mut hwheel,
+                RelativeAxisCode::REL_HWHEEL_HI_RES => &mut hwheel_hi_res,
+                _ => continue,
+            };
+            *sum = Some(sum.unwrap_or(0i32).saturating_add(event.value()));
+            time.get_or_insert(InputEvent::from(event).0.time);
+        }
+        let (vertical_hi_res, vertical_detents) =
+            self.vertical.process(self.mode, wheel, wheel_hi_res);
+        let (horizontal_hi_res, horizontal_detents) =
+            self.horizontal.process(self.mode, hwheel, hwheel_hi_res);
+        ScrollDelta {
+            vertical_hi_res,
+            horizontal_hi_res,
+            vertical_detents,
+            horizontal_detents,
+            time: time.unwrap_or(libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            }),
+        }
+    }
+}
+
+/// Returns an event with the time of `template`, but with `code` and `value`.
+fn with_value(
+    template: RelativeAxisEvent,
+    code: RelativeAxisCode,
+    value: i32,
+) -> RelativeAxisEvent {
+    let mut raw = InputEvent::from(template).0;
+    raw.code = code.0;
+    raw.value = value;
+    RelativeAxisEvent::from_event(InputEvent(raw))
+}
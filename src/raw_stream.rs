@@ -9,9 +9,10 @@ use std::{io, mem};
 use crate::compat::{input_absinfo, input_event, input_id, input_keymap_entry};
 use crate::constants::*;
 use crate::ff::*;
+use crate::frame::{is_syn_report, Frame};
 use crate::{
-    sys, AbsInfo, AttributeSet, AttributeSetRef, AutoRepeat, FFEffect, FFEffectCode, FFEvent,
-    InputEvent, InputId, KeyCode,
+    sys, AbsInfo, AttributeSet, AttributeSetRef, AutoRepeat, Clock, DeviceClasses, EventMask,
+    FFEffect, FFEffectCode, FFEvent, InputEvent, InputId, KeyCode,
 };
 
 fn ioctl_get_cstring(
@@ -38,6 +39,13 @@ fn bytes_into_string_lossy(v: Vec<u8>) -> String {
     String::from_utf8(v).unwrap_or_else(|v| String::from_utf8_lossy(v.as_bytes()).into_owned())
 }
 
+fn unsupported(code: impl std::fmt::Debug) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("the device doesn't support {code:?}"),
+    )
+}
+
 const ABSINFO_ZERO: input_absinfo = input_absinfo {
     value: 0,
     minimum: 0,
@@ -85,6 +93,9 @@ pub struct RawDevice {
     supported_snd: Option<AttributeSet<SoundCode>>,
     pub(crate) event_buf: Vec<input_event>,
     grabbed: bool,
+    clock: Clock,
+    /// The mask set with `set_event_mask`, if any.
+    pub(crate) event_mask: Option<EventMask>,
 }
 
 impl RawDevice {
@@ -96,6 +107,21 @@ impl RawDevice {
         Self::_open(path.as_ref())
     }
 
+    /// Opens the device that best matches `identity`.
+    ///
+    /// See [`DeviceIdentity::find_nodes`](crate::DeviceIdentity::find_nodes) for how the best
+    /// match is chosen. Fails with [`io::ErrorKind::NotFound`] if no device matches.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn open_by_identity(identity: &crate::DeviceIdentity) -> io::Result<RawDevice> {
+        match identity.find_nodes()?.first() {
+            Some(path) => Self::open(path),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no device matches the identity",
+            )),
+        }
+    }
+
     fn _open(path: &Path) -> io::Result<RawDevice> {
         let mut options = OpenOptions::new();
 
@@ -254,6 +280,8 @@ impl RawDevice {
             max_ff_effects,
             event_buf: Vec::new(),
             grabbed: false,
+            clock: Clock::Realtime,
+            event_mask: None,
         })
     }
 
@@ -422,6 +450,22 @@ impl RawDevice {
         self.supported_snd.as_deref()
     }
 
+    /// Guesses what kind of device this is from its capabilities, e.g. a keyboard, a touchpad or
+    /// a joystick.
+    ///
+    /// This follows the same rules as the `input_id` builtin of systemd's udev, which sets the
+    /// `ID_INPUT_*` properties.
+    pub fn classify(&self) -> DeviceClasses {
+        crate::classify::classify(&crate::classify::Capabilities {
+            events: self.supported_events(),
+            keys: self.supported_keys(),
+            relative_axes: self.supported_relative_axes(),
+            absolute_axes: self.supported_absolute_axes(),
+            properties: self.properties(),
+            bus_type: self.input_id().bus_type(),
+        })
+    }
+
     /// Read a maximum of `num` events into the internal buffer. If the underlying fd is not
     /// O_NONBLOCK, this will block.
     ///
@@ -454,6 +498,21 @@ impl RawDevice {
         Ok(self.event_buf.drain(..).map(InputEvent::from))
     }
 
+    /// Fetches events from the kernel ring buffer and groups them into [`Frame`]s, without doing
+    /// synchronization on SYN_DROPPED.
+    ///
+    /// Events of a frame that hasn't been completed by a `SYN_REPORT` yet are kept back until a
+    /// later call. A `SYN_DROPPED` is passed through as part of the frame it appeared in.
+    ///
+    /// By default this will block until events are available.
+    pub fn fetch_frames(&mut self) -> io::Result<FetchFrames<'_>> {
+        self.fill_events()?;
+        Ok(FetchFrames {
+            dev: self,
+            consumed_to: 0,
+        })
+    }
+
     /// Retrieve the current keypress state directly via kernel syscall.
     #[inline]
     pub fn get_key_state(&self) -> io::Result<AttributeSet<KeyCode>> {
@@ -498,6 +557,46 @@ impl RawDevice {
         Ok(led_vals)
     }
 
+    /// Retrieve the current sound state directly via kernel syscall.
+    #[inline]
+    pub fn get_sound_state(&self) -> io::Result<AttributeSet<SoundCode>> {
+        let mut snd_vals = AttributeSet::new();
+        self.update_sound_state(&mut snd_vals)?;
+        Ok(snd_vals)
+    }
+
+    /// Retrieve the current value of a multi-touch axis for every slot directly via kernel
+    /// syscall.
+    ///
+    /// The returned `Vec` has one entry per slot, see [`mt_slot_count`](Self::mt_slot_count).
+    #[inline]
+    pub fn get_mt_slot_state(&self, axis: AbsoluteAxisCode) -> io::Result<Vec<i32>> {
+        let mut slot_vals = vec![0; self.mt_slot_count()?];
+        self.update_mt_slot_state(axis, &mut slot_vals)?;
+        Ok(slot_vals)
+    }
+
+    /// Returns the number of multi-touch slots, i.e. the maximum of `ABS_MT_SLOT` plus one.
+    ///
+    /// Returns 0 if the device does not support `ABS_MT_SLOT`.
+    pub fn mt_slot_count(&self) -> io::Result<usize> {
+        if !self
+            .supported_absolute_axes()
+            .map_or(false, |axes| axes.contains(AbsoluteAxisCode::ABS_MT_SLOT))
+        {
+            return Ok(0);
+        }
+        let mut absinfo = ABSINFO_ZERO;
+        unsafe {
+            sys::eviocgabs(
+                self.as_raw_fd(),
+                AbsoluteAxisCode::ABS_MT_SLOT.0 as u32,
+                &mut absinfo,
+            )?
+        };
+        Ok(usize::try_from(absinfo.maximum + 1).unwrap_or(0))
+    }
+
     /// Fetch the current kernel key state directly into the provided buffer.
     /// If you don't already have a buffer, you probably want
     /// [`get_key_state`](Self::get_key_state) instead.
@@ -550,6 +649,40 @@ impl RawDevice {
         Ok(())
     }
 
+    /// Fetch the current kernel sound state directly into the provided buffer.
+    /// If you don't already have a buffer, you probably want
+    /// [`get_sound_state`](Self::get_sound_state) instead.
+    #[inline]
+    pub fn update_sound_state(&self, snd_vals: &mut AttributeSet<SoundCode>) -> io::Result<()> {
+        unsafe { sys::eviocgsnd(self.as_raw_fd(), snd_vals.as_mut_raw_slice())? };
+        Ok(())
+    }
+
+    /// Fetch the current value of a multi-touch axis for every slot directly into the provided
+    /// buffer, one value per slot. If `slot_vals` is shorter than the number of slots, the
+    /// remaining slots are left out.
+    /// If you don't already have a buffer, you probably want
+    /// [`get_mt_slot_state`](Self::get_mt_slot_state) instead.
+    pub fn update_mt_slot_state(
+        &self,
+        axis: AbsoluteAxisCode,
+        slot_vals: &mut [i32],
+    ) -> io::Result<()> {
+        // EVIOCGMTSLOTS takes a `struct input_mt_request_layout`: the axis code followed by one
+        // value per slot.
+        let mut request = vec![0i32; slot_vals.len() + 1];
+        request[0] = axis.0 as i32;
+        let buf = unsafe {
+            std::slice::from_raw_parts_mut(
+                request.as_mut_ptr() as *mut u8,
+                mem::size_of_val(request.as_slice()),
+            )
+        };
+        unsafe { sys::eviocgmtslots(self.as_raw_fd(), buf)? };
+        slot_vals.copy_from_slice(&request[1..]);
+        Ok(())
+    }
+
     /// Update the auto repeat delays
     #[inline]
     pub fn update_auto_repeat(&mut self, repeat: &AutoRepeat) -> io::Result<()> {
@@ -667,6 +800,86 @@ impl RawDevice {
         self.grabbed
     }
 
+    /// Revokes access to the device through this file descriptor.
+    ///
+    /// Revoking applies to the open file description, so it also cuts off anyone else holding a
+    /// duplicate of the fd, e.g. a client it was handed to. Afterwards, reads, writes and ioctls
+    /// fail with `ENODEV`, just as if the device had been unplugged, and a grab is released. This
+    /// can't be undone; the device has to be opened again.
+    pub fn revoke(&mut self) -> io::Result<()> {
+        unsafe {
+            sys::eviocrevoke(self.as_raw_fd(), 0)?;
+        }
+        self.grabbed = false;
+        Ok(())
+    }
+
+    /// Resolves the sysfs directories of this device, to read metadata the ioctls don't provide.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn sysfs_info(&self) -> io::Result<crate::SysfsInfo> {
+        crate::SysfsInfo::from_fd(self.as_fd())
+    }
+
+    /// Determines the identity of this device, which can be used to find it again after a reboot
+    /// or after it was replugged.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn identity(&self) -> io::Result<crate::DeviceIdentity> {
+        crate::DeviceIdentity::from_fd(self.as_fd())
+    }
+
+    /// Restricts the events the kernel delivers to this handle to the ones `mask` allows, using
+    /// `EVIOCSMASK`.
+    ///
+    /// This only affects this handle; other handles to the same device keep receiving all events.
+    pub fn set_event_mask(&mut self, mask: &EventMask) -> io::Result<()> {
+        let fd = self.as_raw_fd();
+        mask.for_each_raw(|type_, codes| {
+            let raw = sys::input_mask {
+                type_: type_.into(),
+                codes_size: codes.len() as u32,
+                codes_ptr: codes.as_ptr() as u64,
+            };
+            unsafe { sys::eviocsmask(fd, &raw)? };
+            Ok(())
+        })?;
+        self.event_mask = Some(mask.clone());
+        Ok(())
+    }
+
+    /// Reads back the event mask of this handle directly via kernel syscall, using
+    /// `EVIOCGMASK`.
+    pub fn get_event_mask(&self) -> io::Result<EventMask> {
+        let fd = self.as_raw_fd();
+        EventMask::from_raw(|type_, codes| {
+            let mut raw = sys::input_mask {
+                type_: type_.into(),
+                codes_size: codes.len() as u32,
+                codes_ptr: codes.as_mut_ptr() as u64,
+            };
+            unsafe { sys::eviocgmask(fd, &mut raw)? };
+            Ok(())
+        })
+    }
+
+    /// Sets the clock the kernel uses to timestamp events from this device.
+    ///
+    /// If there are events that haven't been read yet when the clock changes, the kernel discards
+    /// them and queues a SYN_DROPPED.
+    pub fn set_clock(&mut self, clock: Clock) -> io::Result<()> {
+        let clockid: libc::c_int = clock.clockid();
+        unsafe {
+            sys::eviocsclockid(self.as_raw_fd(), &clockid)?;
+        }
+        self.clock = clock;
+        Ok(())
+    }
+
+    /// Returns the clock used to timestamp events from this device, see
+    /// [`set_clock`](Self::set_clock).
+    pub fn clock(&self) -> Clock {
+        self.clock
+    }
+
     /// Send an event to the device.
     ///
     /// Events that are typically sent to devices are
@@ -678,6 +891,63 @@ impl RawDevice {
         Ok(())
     }
 
+    /// Turns the LEDs in `leds` on and all other LEDs of the device off.
+    ///
+    /// Fails with `InvalidInput` if `leds` has an LED that the device doesn't support. The kernel
+    /// echoes the change back as `EV_LED` events, which update the cached
+    /// [`led_vals`](crate::DeviceState::led_vals) of a [`Device`](crate::Device) once they are
+    /// read.
+    pub fn set_leds(&mut self, leds: &AttributeSetRef<LedCode>) -> io::Result<()> {
+        let supported = self.supported_leds();
+        if let Some(led) = leds
+            .iter()
+            .find(|&led| !supported.map_or(false, |s| s.contains(led)))
+        {
+            return Err(unsupported(led));
+        }
+        let events: Vec<_> = supported
+            .into_iter()
+            .flat_map(|s| s.iter())
+            .map(|led| InputEvent::new(EventType::LED.0, led.0, leds.contains(led) as i32))
+            .collect();
+        self.send_report(&events)
+    }
+
+    /// Turns an LED of the device on or off, see [`set_leds`](Self::set_leds).
+    pub fn set_led(&mut self, led: LedCode, on: bool) -> io::Result<()> {
+        if !self.supported_leds().map_or(false, |s| s.contains(led)) {
+            return Err(unsupported(led));
+        }
+        self.send_report(&[InputEvent::new(EventType::LED.0, led.0, on as i32)])
+    }
+
+    /// Plays a sound on the device.
+    ///
+    /// For `SND_TONE`, `hz` is the frequency of the tone, and 0 stops it. `SND_BELL` and
+    /// `SND_CLICK` are turned on by any other value than 0. Fails with `InvalidInput` if the
+    /// device doesn't support the sound.
+    pub fn beep(&mut self, sound: SoundCode, hz: u32) -> io::Result<()> {
+        if !self.supported_sounds().map_or(false, |s| s.contains(sound)) {
+            return Err(unsupported(sound));
+        }
+        let value = i32::try_from(hz).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "the frequency is too high")
+        })?;
+        self.send_report(&[InputEvent::new(EventType::SOUND.0, sound.0, value)])
+    }
+
+    /// Sends `events` followed by a `SYN_REPORT`, in one write.
+    fn send_report(&mut self, events: &[InputEvent]) -> io::Result<()> {
+        let syn = InputEvent::new(
+            EventType::SYNCHRONIZATION.0,
+            SynchronizationCode::SYN_REPORT.0,
+            0,
+        );
+        let mut events = events.to_vec();
+        events.push(syn);
+        self.send_events(&events)
+    }
+
     /// Uploads a force feedback effect to the device.
     pub fn upload_ff_effect(&mut self, data: FFEffectData) -> io::Result<FFEffect> {
         let mut effect: sys::ff_effect = data.into();
@@ -729,6 +999,32 @@ impl TryFrom<File> for RawDevice {
     }
 }
 
+/// The frames of a [`RawDevice`], produced by [`RawDevice::fetch_frames`].
+///
+/// Frames borrow from the device's buffer, so they are handed out one at a time by
+/// [`next_frame`](Self::next_frame) rather than through [`Iterator`].
+pub struct FetchFrames<'a> {
+    dev: &'a mut RawDevice,
+    /// The index into dev.event_buf up to which we'll delete events when dropped.
+    consumed_to: usize,
+}
+
+impl FetchFrames<'_> {
+    /// Returns the next complete frame, or `None` if there is none left.
+    pub fn next_frame(&mut self) -> Option<Frame<'_>> {
+        let buf = &self.dev.event_buf[self.consumed_to..];
+        let end = buf.iter().position(is_syn_report)? + 1;
+        self.consumed_to += end;
+        Some(Frame::from_block(&buf[..end], false))
+    }
+}
+
+impl Drop for FetchFrames<'_> {
+    fn drop(&mut self) {
+        self.dev.event_buf.drain(..self.consumed_to);
+    }
+}
+
 /// Crawls `/dev/input` for evdev devices.
 ///
 /// Will not bubble up any errors in opening devices or traversing the directory. Instead returns
@@ -746,13 +1042,11 @@ pub struct EnumerateDevices {
 impl Iterator for EnumerateDevices {
     type Item = (PathBuf, RawDevice);
     fn next(&mut self) -> Option<(PathBuf, RawDevice)> {
-        use std::os::unix::ffi::OsStrExt;
         let readdir = self.readdir.as_mut()?;
         loop {
             if let Ok(entry) = readdir.next()? {
                 let path = entry.path();
-                let fname = path.file_name().unwrap();
-                if fname.as_bytes().starts_with(b"event") {
+                if is_event_node(&path) {
                     if let Ok(dev) = RawDevice::open(&path) {
                         return Some((path, dev));
                     }
@@ -762,8 +1056,113 @@ impl Iterator for EnumerateDevices {
     }
 }
 
+/// Whether `path` looks like an evdev device node, i.e. `/dev/input/event*`.
+pub(crate) fn is_event_node(path: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+    path.file_name()
+        .map_or(false, |fname| fname.as_bytes().starts_with(b"event"))
+}
+
+#[cfg(any(feature = "tokio", feature = "async-io"))]
+mod stream_core {
+    use super::*;
+
+    use crate::async_stream::AsyncSource;
+    use std::task::{ready, Context, Poll};
+
+    /// The runtime-independent part of the raw event streams.
+    pub(super) struct RawEventStreamCore<S> {
+        device: S,
+        index: usize,
+        /// Set once reading failed because the device was unplugged or revoked.
+        gone: bool,
+    }
+
+    impl<S: AsyncSource<Device = RawDevice>> RawEventStreamCore<S> {
+        pub(super) fn new(device: RawDevice) -> io::Result<Self> {
+            Ok(Self {
+                device: S::new(device)?,
+                index: 0,
+                gone: false,
+            })
+        }
+
+        pub(super) fn device(&self) -> &RawDevice {
+            self.device.get_ref()
+        }
+
+        pub(super) fn device_mut(&mut self) -> &mut RawDevice {
+            self.device.get_mut()
+        }
+
+        #[cfg(any(feature = "stream-trait", feature = "async-io"))]
+        pub(super) fn is_gone(&self) -> bool {
+            self.gone
+        }
+
+        pub(super) fn block_frame(&self, block: std::ops::Range<usize>) -> Frame<'_> {
+            Frame::from_block(&self.device().event_buf[block], false)
+        }
+
+        /// Waits for the next block of events that ends with a `SYN_REPORT` and marks it as
+        /// consumed.
+        pub(super) fn poll_next_block(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<io::Result<std::ops::Range<usize>>> {
+            loop {
+                let buf = &self.device.get_ref().event_buf[self.index..];
+                if let Some(end) = buf.iter().position(is_syn_report) {
+                    let block = self.index..self.index + end + 1;
+                    self.index = block.end;
+                    return Poll::Ready(Ok(block));
+                }
+
+                // keep the incomplete frame around until the rest of it arrives
+                let index = std::mem::take(&mut self.index);
+                self.device.get_mut().event_buf.drain(..index);
+
+                ready!(self.poll_fill(cx))?;
+            }
+        }
+
+        pub(super) fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<InputEvent>> {
+            loop {
+                if let Some(&ev) = self.device.get_ref().event_buf.get(self.index) {
+                    self.index += 1;
+                    return Poll::Ready(Ok(InputEvent::from(ev)));
+                }
+
+                self.device.get_mut().event_buf.clear();
+                self.index = 0;
+
+                ready!(self.poll_fill(cx))?;
+            }
+        }
+
+        /// Waits for new events and reads them into the buffer. Once the device is gone, this
+        /// keeps failing with `ENODEV` without touching the fd again.
+        fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            if self.gone {
+                return Poll::Ready(Err(io::Error::from_raw_os_error(libc::ENODEV)));
+            }
+            match ready!(self
+                .device
+                .poll_read_with(cx, |device| device.fill_events()))
+            {
+                Ok(_) => Poll::Ready(Ok(())),
+                Err(e) => {
+                    self.gone = crate::is_device_gone(&e);
+                    Poll::Ready(Err(e))
+                }
+            }
+        }
+    }
+}
+
 #[cfg(feature = "tokio")]
 mod tokio_stream {
+    use super::stream_core::RawEventStreamCore;
     use super::*;
 
     use std::future::poll_fn;
@@ -777,27 +1176,25 @@ mod tokio_stream {
     /// There's also a lower-level [`Self::poll_event`] function if you need to fetch an event from
     /// inside a `Future::poll` impl.
     pub struct EventStream {
-        device: AsyncFd<RawDevice>,
-        index: usize,
+        inner: RawEventStreamCore<AsyncFd<RawDevice>>,
     }
     impl Unpin for EventStream {}
 
     impl EventStream {
         pub(crate) fn new(device: RawDevice) -> io::Result<Self> {
-            use nix::fcntl;
-            fcntl::fcntl(device.as_raw_fd(), fcntl::F_SETFL(fcntl::OFlag::O_NONBLOCK))?;
-            let device = AsyncFd::new(device)?;
-            Ok(Self { device, index: 0 })
+            Ok(Self {
+                inner: RawEventStreamCore::new(device)?,
+            })
         }
 
         /// Returns a reference to the underlying device
         pub fn device(&self) -> &RawDevice {
-            self.device.get_ref()
+            self.inner.device()
         }
 
         /// Returns a mutable reference to the underlying device.
         pub fn device_mut(&mut self) -> &mut RawDevice {
-            self.device.get_mut()
+            self.inner.device_mut()
         }
 
         /// Try to wait for the next event in this stream. Any errors are likely to be fatal, i.e.
@@ -806,43 +1203,119 @@ mod tokio_stream {
             poll_fn(|cx| self.poll_event(cx)).await
         }
 
-        /// A lower-level function for directly polling this stream.
-        pub fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<InputEvent>> {
-            'outer: loop {
-                if let Some(&ev) = self.device.get_ref().event_buf.get(self.index) {
-                    self.index += 1;
-                    return Poll::Ready(Ok(InputEvent::from(ev)));
-                }
-
-                self.device.get_mut().event_buf.clear();
-                self.index = 0;
+        /// Try to wait for the next complete [`Frame`] in this stream.
+        pub async fn next_frame(&mut self) -> io::Result<Frame<'_>> {
+            let block = poll_fn(|cx| self.inner.poll_next_block(cx)).await?;
+            Ok(self.inner.block_frame(block))
+        }
 
-                loop {
-                    let mut guard = ready!(self.device.poll_read_ready_mut(cx))?;
+        /// A lower-level function for directly polling this stream for frames.
+        pub fn poll_frame(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Frame<'_>>> {
+            let block = ready!(self.inner.poll_next_block(cx))?;
+            Poll::Ready(Ok(self.inner.block_frame(block)))
+        }
 
-                    let res = guard.try_io(|device| device.get_mut().fill_events());
-                    match res {
-                        Ok(res) => {
-                            let _ = res?;
-                            continue 'outer;
-                        }
-                        Err(_would_block) => continue,
-                    }
-                }
-            }
+        /// A lower-level function for directly polling this stream.
+        pub fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<InputEvent>> {
+            self.inner.poll_event(cx)
         }
     }
 
     #[cfg(feature = "stream-trait")]
     impl futures_core::Stream for EventStream {
         type Item = io::Result<InputEvent>;
+
+        /// Ends the stream after reporting that the device was unplugged or revoked.
         fn poll_next(
             self: std::pin::Pin<&mut Self>,
             cx: &mut Context<'_>,
         ) -> Poll<Option<Self::Item>> {
-            self.get_mut().poll_event(cx).map(Some)
+            let this = self.get_mut();
+            if this.inner.is_gone() {
+                return Poll::Ready(None);
+            }
+            this.poll_event(cx).map(Some)
         }
     }
 }
 #[cfg(feature = "tokio")]
 pub use tokio_stream::EventStream;
+
+#[cfg(feature = "async-io")]
+pub(crate) mod async_io_stream {
+    use super::stream_core::RawEventStreamCore;
+    use super::*;
+
+    use crate::async_stream::AsyncIo;
+    use std::future::poll_fn;
+    use std::task::{ready, Context, Poll};
+
+    /// An asynchronous stream of raw input events, driven by the `async-io` reactor.
+    ///
+    /// This can be used by calling [`stream.next_event().await?`](Self::next_event), or if you
+    /// need to pass it as a stream somewhere, the [`futures::Stream`](futures_core::Stream)
+    /// implementation. There's also a lower-level [`Self::poll_event`] function if you need to
+    /// fetch an event from inside a `Future::poll` impl.
+    pub struct EventStream {
+        inner: RawEventStreamCore<AsyncIo<RawDevice>>,
+    }
+    impl Unpin for EventStream {}
+
+    impl EventStream {
+        /// Puts `device` into nonblocking mode and registers it with the reactor.
+        pub fn new(device: RawDevice) -> io::Result<Self> {
+            Ok(Self {
+                inner: RawEventStreamCore::new(device)?,
+            })
+        }
+
+        /// Returns a reference to the underlying device
+        pub fn device(&self) -> &RawDevice {
+            self.inner.device()
+        }
+
+        /// Returns a mutable reference to the underlying device.
+        pub fn device_mut(&mut self) -> &mut RawDevice {
+            self.inner.device_mut()
+        }
+
+        /// Try to wait for the next event in this stream. Any errors are likely to be fatal, i.e.
+        /// any calls afterwards will likely error as well.
+        pub async fn next_event(&mut self) -> io::Result<InputEvent> {
+            poll_fn(|cx| self.poll_event(cx)).await
+        }
+
+        /// Try to wait for the next complete [`Frame`] in this stream.
+        pub async fn next_frame(&mut self) -> io::Result<Frame<'_>> {
+            let block = poll_fn(|cx| self.inner.poll_next_block(cx)).await?;
+            Ok(self.inner.block_frame(block))
+        }
+
+        /// A lower-level function for directly polling this stream for frames.
+        pub fn poll_frame(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Frame<'_>>> {
+            let block = ready!(self.inner.poll_next_block(cx))?;
+            Poll::Ready(Ok(self.inner.block_frame(block)))
+        }
+
+        /// A lower-level function for directly polling this stream.
+        pub fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<InputEvent>> {
+            self.inner.poll_event(cx)
+        }
+    }
+
+    impl futures_core::Stream for EventStream {
+        type Item = io::Result<InputEvent>;
+
+        /// Ends the stream after reporting that the device was unplugged or revoked.
+        fn poll_next(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+            if this.inner.is_gone() {
+                return Poll::Ready(None);
+            }
+            this.poll_event(cx).map(Some)
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Generating key repeats in userspace.
+//!
+//! Only devices with `EV_REP` repeat their keys, and a grabbed device's repeats go wherever its
+//! events go. A [`KeyRepeater`] produces the repeats itself, e.g. to re-emit them through a
+//! [`VirtualDevice`](crate::uinput::VirtualDevice) or for keys a remapper synthesizes. It works
+//! like the kernel: the key that went down last repeats with value 2, first after the
+//! [`delay`](AutoRepeat::delay) and then every [`period`](AutoRepeat::period), until it is
+//! released or another key goes down. Buttons (`BTN_*`) don't repeat.
+//!
+//! The repeater doesn't run a timer on its own. Pass it the key events with
+//! [`process_event`](KeyRepeater::process_event), and then either wait for the next repeat
+//! at most as long as [`timeout`](KeyRepeater::timeout) says before calling
+//! [`process_timeout`](KeyRepeater::process_timeout), or block in [`wait`](KeyRepeater::wait).
+//! With the `tokio` feature, [`next_repeat`](KeyRepeater::next_repeat) waits on a tokio timer.
+//!
+//! Times are given in the [`Clock`] of the repeater, which has to be the one of the device whose
+//! events it gets, see [`KeyRepeater::clock`].
+//!
+//! ```no_run
+//! use evdev::repeat::KeyRepeater;
+//! use evdev::uinput::VirtualDevice;
+//! use evdev::{AutoRepeat, Device, DeviceSet, EventSummary, SetEvent};
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let mut device = Device::open("/dev/input/event0")?;
+//! let mut virt = VirtualDevice::builder()?
+//!     .name("Repeating keyboard")
+//!     .with_keys(device.supported_keys().unwrap())?
+//!     .build()?;
+//! let mut repeater = KeyRepeater::new(AutoRepeat {
+//!     delay: 250,
+//!     period: 33,
+//! })
+//! .clock(device.clock());
+//! device.grab()?;
+//! let mut set = DeviceSet::new()?;
+//! set.insert(device)?;
+//! loop {
+//!     let mut events = Vec::new();
+//!     for event in set.fetch_events(repeater.timeout())? {
+//!         if let SetEvent::Event(_, event) = event {
+//!             repeater.process_event(event);
+//!             // drop the repeats of the device itself
+//!             if !matches!(event.destructure(), EventSummary::Key(_, _, 2)) {
+//!                 events.push(event);
+//!             }
+//!         }
+//!     }
+//!     if let Some(repeat) = repeater.process_timeout(repeater.get_clock().now()) {
+//!         events.push(repeat.into());
+//!     }
+//!     if !events.is_empty() {
+//!         virt.emit(&events)?;
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use crate::compat::input_event;
+use crate::{AutoRepeat, Clock, EventType, InputEvent, KeyCode, KeyEvent};
+
+/// Produces the repeats of held keys, see the [module-level documentation](self).
+#[derive(Debug, Clone)]
+pub struct KeyRepeater {
+    repeat: AutoRepeat,
+    clock: Clock,
+    /// The key that repeats, and when it repeats next.
+    repeating: Option<(KeyCode, Duration)>,
+}
+
+impl KeyRepeater {
+    /// Creates a repeater with the given delay and period, using [`Clock::Realtime`].
+    ///
+    /// Keys don't repeat if the period is 0, just as with the kernel.
+    pub fn new(repeat: AutoRepeat) -> Self {
+        KeyRepeater {
+            repeat,
+            clock: Clock::Realtime,
+            repeating: None,
+        }
+    }
+
+    /// Sets the clock that the timestamps of the events and the times passed to the repeater are
+    /// in, usually [`Device::clock`](crate::Device::clock).
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the clock of the repeater.
+    pub fn get_clock(&self) -> Clock {
+        self.clock
+    }
+
+    /// Returns the delay and period of the repeats.
+    pub fn auto_repeat(&self) -> &AutoRepeat {
+        &self.repeat
+    }
+
+    /// Changes the delay and period of the repeats, which applies from the next key that goes
+    /// down.
+    pub fn set_auto_repeat(&mut self, repeat: AutoRepeat) {
+        self.repeat = repeat;
+    }
+
+    /// Returns the key that is repeating.
+    pub fn repeating(&self) -> Option<KeyCode> {
+        self.repeating.map(|(key, _)| key)
+    }
+
+    /// Lets the repeater know about an event of the device.
+    ///
+    /// A key going down starts repeating and cancels the repeats of the previous key, and
+    /// releasing the repeating key stops them. Repeats of the device and events other than keys
+    /// are ignored. An event without a timestamp, such as one created with [`KeyEvent::new`],
+    /// is taken to happen now.
+    pub fn process_event(&mut self, event: InputEvent) {
+        if event.event_type() != EventType::KEY {
+            return;
+        }
+        let key = KeyCode(event.code());
+        match event.value() {
+            0 if self.repeating() == Some(key) => self.repeating = None,
+            1 => {
+                self.repeating = if self.repeat.period > 0 && crate::classify::is_key(key) {
+                    let time = match event.clock_time() {
+                        Duration::ZERO => self.clock.now(),
+                        time => time,
+                    };
+                    Some((key, time + millis(self.repeat.delay)))
+                } else {
+                    None
+                };
+            }
+            _ => {}
+        }
+    }
+
+    /// Stops repeating the key that is repeating, if any.
+    pub fn cancel(&mut self) {
+        self.repeating = None;
+    }
+
+    /// Returns the time, in the repeater's clock, at which the next repeat is due.
+    pub fn deadline(&self) -> Option<Duration> {
+        self.repeating.map(|(_, next)| next)
+    }
+
+    /// Returns how long it is until the next repeat is due, or `None` if no key is repeating.
+    ///
+    /// This can be passed as the timeout to e.g. [`DeviceSet::fetch_events`].
+    ///
+    /// [`DeviceSet::fetch_events`]: crate::DeviceSet::fetch_events
+    pub fn timeout(&self) -> Option<Duration> {
+        self.deadline()
+            .map(|deadline| deadline.saturating_sub(self.clock.now()))
+    }
+
+    /// Lets the repeater know that it's `now` in its clock, and returns the repeat that is due, if
+    /// any.
+    ///
+    /// The repeat has the timestamp it was due at. If more than one repeat is overdue, only one is
+    /// returned and the next one is due a period from `now`, so that a late call doesn't cause a
+    /// burst of repeats.
+    pub fn process_timeout(&mut self, now: Duration) -> Option<KeyEvent> {
+        let (key, due) = self.repeating?;
+        if self.repeat.period == 0 {
+            // the repeats were turned off in the meantime
+            self.repeating = None;
+            return None;
+        }
+        if now < due {
+            return None;
+        }
+        let period = millis(self.repeat.period);
+        let mut next = due + period;
+        if next <= now {
+            next = now + period;
+        }
+        self.repeating = Some((key, next));
+        Some(repeat_event(key, due))
+    }
+
+    /// Blocks until the next repeat is due and returns it, or returns `None` once `timeout`
+    /// passed first or right away if no key is repeating.
+    pub fn wait(&mut self, timeout: Option<Duration>) -> Option<KeyEvent> {
+        let start = self.clock.now();
+        let until = match (self.deadline()?, timeout) {
+            (deadline, Some(timeout)) => deadline.min(start + timeout),
+            (deadline, None) => deadline,
+        };
+        loop {
+            let now = self.clock.now();
+            if now >= until {
+                return self.process_timeout(now);
+            }
+            std::thread::sleep(until - now);
+        }
+    }
+
+    /// Waits on a tokio timer until the next repeat is due and returns it.
+    ///
+    /// This never returns if no key is repeating, so that it can be used in `tokio::select!`
+    /// along with reading the events that start the repeats. It is cancel safe.
+    #[cfg(feature = "tokio")]
+    pub async fn next_repeat(&mut self) -> KeyEvent {
+        loop {
+            let deadline = match self.deadline() {
+                Some(deadline) => deadline,
+                None => std::future::pending().await,
+            };
+            let now = self.clock.now();
+            if deadline > now {
+                tokio::time::sleep(deadline - now).await;
+            }
+            if let Some(event) = self.process_timeout(self.clock.now()) {
+                return event;
+            }
+        }
+    }
+}
+
+fn millis(ms: u32) -> Duration {
+    Duration::from_millis(ms as u64)
+}
+
+fn repeat_event(key: KeyCode, time: Duration) -> KeyEvent {
+    KeyEvent::from_event(InputEvent(input_event {
+        time: crate::clock::duration_to_timeval(time),
+        type_: EventType::KEY.0,
+        code: key.0,
+        value: 2,
+    }))
+}
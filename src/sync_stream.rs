@@ -1,18 +1,19 @@
+use crate::clock::duration_to_timeval;
 use crate::compat::{input_absinfo, input_event};
 use crate::constants::*;
-use crate::device_state::DeviceState;
+use crate::device_state::{DeviceState, MtSlots};
 use crate::ff::*;
+use crate::frame::{is_syn_report, Frame};
 use crate::raw_stream::RawDevice;
 use crate::{
-    AbsInfo, AttributeSet, AttributeSetRef, AutoRepeat, EventSummary, FFEffect, InputEvent,
-    InputId, KeyCode,
+    AbsInfo, AttributeSet, AttributeSetRef, AutoRepeat, Clock, DeviceClasses, EventMask,
+    EventSummary, FFEffect, InputEvent, InputId, KeyCode,
 };
 
 use nix::fcntl;
 use std::fs::File;
 use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
 use std::path::Path;
-use std::time::SystemTime;
 use std::{fmt, io};
 
 /// A physical or virtual device supported by evdev.
@@ -44,6 +45,26 @@ impl Device {
         Self::_open(path.as_ref())
     }
 
+    /// Opens the device that best matches `identity`.
+    ///
+    /// See [`DeviceIdentity::find_nodes`](crate::DeviceIdentity::find_nodes) for how the best
+    /// match is chosen. Fails with [`io::ErrorKind::NotFound`] if no device matches.
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use evdev::{Device, DeviceIdentity};
+    ///
+    /// let identity = DeviceIdentity::from_path("/dev/input/by-id/usb-Logitech_USB_Receiver-event-kbd")?;
+    /// // ...after a reboot, when the node might be called differently
+    /// let device = Device::open_by_identity(&identity)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn open_by_identity(identity: &crate::DeviceIdentity) -> io::Result<Device> {
+        RawDevice::open_by_identity(identity).map(Self::from_raw_device)
+    }
+
     /// Opens a device, given an already opened file descriptor.
     #[inline(always)]
     pub fn from_fd(fd: OwnedFd) -> io::Result<Device> {
@@ -55,6 +76,15 @@ impl Device {
         RawDevice::open(path).map(Self::from_raw_device)
     }
 
+    /// Whether `ev` passes the event mask of this handle.
+    #[inline]
+    fn is_allowed(&self, ev: &InputEvent) -> bool {
+        self.raw
+            .event_mask
+            .as_ref()
+            .map_or(true, |mask| mask.allows(ev.event_type(), ev.code()))
+    }
+
     // TODO: should this be public?
     pub(crate) fn from_raw_device(raw: RawDevice) -> Device {
         let state = DeviceState::new(&raw);
@@ -278,6 +308,25 @@ impl Device {
         self.raw.supported_sounds()
     }
 
+    /// Guesses what kind of device this is from its capabilities, e.g. a keyboard, a touchpad or
+    /// a joystick.
+    ///
+    /// This follows the same rules as the `input_id` builtin of systemd's udev, which sets the
+    /// `ID_INPUT_*` properties.
+    ///
+    /// ```no_run
+    /// use evdev::DeviceClasses;
+    ///
+    /// let keyboards = evdev::enumerate()
+    ///     .filter(|(_, device)| device.classify().contains(DeviceClasses::KEYBOARD));
+    /// for (path, device) in keyboards {
+    ///     println!("{}: {}", path.display(), device.name().unwrap_or("unnamed keyboard"));
+    /// }
+    /// ```
+    pub fn classify(&self) -> DeviceClasses {
+        self.raw.classify()
+    }
+
     /// Retrieve the current keypress state directly via kernel syscall.
     pub fn get_key_state(&self) -> io::Result<AttributeSet<KeyCode>> {
         self.raw.get_key_state()
@@ -295,6 +344,17 @@ impl Device {
         self.raw.get_absinfo()
     }
 
+    /// Retrieve the current sound state directly via kernel syscall.
+    pub fn get_sound_state(&self) -> io::Result<AttributeSet<SoundCode>> {
+        self.raw.get_sound_state()
+    }
+
+    /// Retrieve the current value of a multi-touch axis for every slot directly via kernel
+    /// syscall.
+    pub fn get_mt_slot_state(&self, axis: AbsoluteAxisCode) -> io::Result<Vec<i32>> {
+        self.raw.get_mt_slot_state(axis)
+    }
+
     /// Retrieve the current switch state directly via kernel syscall.
     pub fn get_switch_state(&self) -> io::Result<AttributeSet<SwitchCode>> {
         self.raw.get_switch_state()
@@ -305,19 +365,36 @@ impl Device {
         self.raw.get_led_state()
     }
 
-    fn sync_state(&mut self, now: SystemTime) -> io::Result<()> {
+    fn sync_state(&mut self, now: libc::timeval) -> io::Result<()> {
         if let Some(ref mut key_vals) = self.state.key_vals {
             self.raw.update_key_state(key_vals)?;
         }
         if let Some(ref mut abs_vals) = self.state.abs_vals {
             self.raw.update_abs_state(abs_vals)?;
         }
+        if let (Some(ref mut mt_vals), Some(supported_abs)) =
+            (&mut self.state.mt_vals, self.raw.supported_absolute_axes())
+        {
+            for axis in supported_abs.iter().filter(|axis| axis.is_mt_slot_axis()) {
+                self.raw
+                    .update_mt_slot_state(axis, mt_vals.axis_vals_mut(axis))?;
+            }
+            if let Some(ref abs_vals) = self.state.abs_vals {
+                let slot = abs_vals[AbsoluteAxisCode::ABS_MT_SLOT.0 as usize].value;
+                if let Ok(slot) = usize::try_from(slot) {
+                    mt_vals.set_current_slot(slot);
+                }
+            }
+        }
         if let Some(ref mut switch_vals) = self.state.switch_vals {
             self.raw.update_switch_state(switch_vals)?;
         }
         if let Some(ref mut led_vals) = self.state.led_vals {
             self.raw.update_led_state(led_vals)?;
         }
+        if let Some(ref mut snd_vals) = self.state.snd_vals {
+            self.raw.update_sound_state(snd_vals)?;
+        }
         self.state.timestamp = now;
         Ok(())
     }
@@ -326,11 +403,12 @@ impl Device {
         let block_dropped = std::mem::take(&mut self.block_dropped);
         let sync = if block_dropped {
             self.prev_state.clone_from(&self.state);
-            let now = SystemTime::now();
+            let now = duration_to_timeval(self.raw.clock().now());
             self.sync_state(now)?;
-            Some(SyncState::KeyTypes {
-                time: crate::systime_to_timeval(&now),
-                start: KeyCode::new(0),
+            Some(SyncState::MtEnds {
+                time: now,
+                slot: 0,
+                ended: false,
             })
         } else {
             None
@@ -357,6 +435,20 @@ impl Device {
         })
     }
 
+    /// Fetches events from the kernel ring buffer and groups them into [`Frame`]s, doing
+    /// synchronization on SYN_DROPPED.
+    ///
+    /// The compensatory events inserted after a SYN_DROPPED are delivered as frames of their own,
+    /// marked with [`Frame::is_synthetic`].
+    ///
+    /// By default this will block until events are available.
+    pub fn fetch_frames(&mut self) -> io::Result<FetchFramesSynced<'_>> {
+        Ok(FetchFramesSynced {
+            events: self.fetch_events()?,
+            frame_buf: Vec::new(),
+        })
+    }
+
     #[cfg(feature = "tokio")]
     pub fn into_event_stream(self) -> io::Result<EventStream> {
         EventStream::new(self)
@@ -389,6 +481,56 @@ impl Device {
         self.raw.is_grabbed()
     }
 
+    /// Revokes access to the device through this file descriptor and any duplicate of it.
+    ///
+    /// See [`RawDevice::revoke`]. Fetching events afterwards fails with `ENODEV`.
+    pub fn revoke(&mut self) -> io::Result<()> {
+        self.raw.revoke()
+    }
+
+    /// Resolves the sysfs directories of this device, to read metadata the ioctls don't provide.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn sysfs_info(&self) -> io::Result<crate::SysfsInfo> {
+        self.raw.sysfs_info()
+    }
+
+    /// Determines the identity of this device, which can be used to find it again after a reboot
+    /// or after it was replugged.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn identity(&self) -> io::Result<crate::DeviceIdentity> {
+        self.raw.identity()
+    }
+
+    /// Restricts the events the kernel delivers to this handle to the ones `mask` allows.
+    ///
+    /// The compensation after a SYN_DROPPED respects the mask as well. Masked events don't reach
+    /// the [`cached_state`](Self::cached_state) either, so the cached values for masked codes
+    /// are only updated when the state is resynchronized.
+    pub fn set_event_mask(&mut self, mask: &EventMask) -> io::Result<()> {
+        self.raw.set_event_mask(mask)
+    }
+
+    /// Reads back the event mask of this handle directly via kernel syscall.
+    pub fn get_event_mask(&self) -> io::Result<EventMask> {
+        self.raw.get_event_mask()
+    }
+
+    /// Sets the clock the kernel uses to timestamp events from this device, which is
+    /// [`Clock::Realtime`] by default.
+    ///
+    /// If there are events that haven't been read yet when the clock changes, the kernel discards
+    /// them and queues a `SYN_DROPPED`, so the next fetch resynchronizes the device state.
+    /// [`DeviceState::timestamp`] keeps the time of the last event in the old clock until the
+    /// next event arrives.
+    pub fn set_clock(&mut self, clock: Clock) -> io::Result<()> {
+        self.raw.set_clock(clock)
+    }
+
+    /// Returns the clock used to timestamp events from this device.
+    pub fn clock(&self) -> Clock {
+        self.raw.clock()
+    }
+
     /// Send an event to the device.
     ///
     /// Events that are typically sent to devices are
@@ -399,6 +541,30 @@ impl Device {
         self.raw.send_events(events)
     }
 
+    /// Turns the LEDs in `leds` on and all other LEDs of the device off.
+    ///
+    /// Fails with `InvalidInput` if `leds` has an LED that the device doesn't support. The kernel
+    /// echoes the change back as `EV_LED` events, which update the
+    /// [`led_vals`](DeviceState::led_vals) of the [cached state](Self::cached_state) once they
+    /// are fetched.
+    pub fn set_leds(&mut self, leds: &AttributeSetRef<LedCode>) -> io::Result<()> {
+        self.raw.set_leds(leds)
+    }
+
+    /// Turns an LED of the device on or off, see [`set_leds`](Self::set_leds).
+    pub fn set_led(&mut self, led: LedCode, on: bool) -> io::Result<()> {
+        self.raw.set_led(led, on)
+    }
+
+    /// Plays a sound on the device.
+    ///
+    /// For `SND_TONE`, `hz` is the frequency of the tone, and 0 stops it. `SND_BELL` and
+    /// `SND_CLICK` are turned on by any other value than 0. Fails with `InvalidInput` if the
+    /// device doesn't support the sound.
+    pub fn beep(&mut self, sound: SoundCode, hz: u32) -> io::Result<()> {
+        self.raw.beep(sound, hz)
+    }
+
     /// Uploads a force feedback effect to the device.
     pub fn upload_ff_effect(&mut self, data: FFEffectData) -> io::Result<FFEffect> {
         self.raw.upload_ff_effect(data)
@@ -453,11 +619,19 @@ pub struct FetchEventsSynced<'a> {
     /// The index into dev.raw.event_buf up to which we'll delete events when dropped.
     consumed_to: usize,
     /// Our current synchronization state, i.e. whether we're currently diffing key_vals,
-    /// abs_vals, switch_vals, led_vals, or none of them.
+    /// abs_vals, switch_vals, led_vals, snd_vals, mt_vals, or none of them.
     sync: Option<SyncState>,
 }
 
 enum SyncState {
+    /// Contacts that were replaced by a new contact in the same slot while events were dropped
+    /// are ended first, in a frame of their own, so the consumer doesn't mistake the new contact
+    /// for a continuation of the old one.
+    MtEnds {
+        time: libc::timeval,
+        slot: usize,
+        ended: bool,
+    },
     KeyTypes {
         time: libc::timeval,
         start: KeyCode,
@@ -474,23 +648,91 @@ enum SyncState {
         time: libc::timeval,
         start: LedCode,
     },
+    Sounds {
+        time: libc::timeval,
+        start: SoundCode,
+    },
+    MtSlots {
+        time: libc::timeval,
+        slot: usize,
+    },
 }
 
+/// Returns the next compensatory event and applies it to `dev.prev_state`, so that `prev_state`
+/// always reflects what the consumer has been told so far. Events that the event mask blocks are
+/// applied as well, but not returned.
 #[inline]
 fn compensate_events(state: &mut Option<SyncState>, dev: &mut Device) -> Option<InputEvent> {
+    loop {
+        let ev = next_compensation_event(state, dev)?;
+        dev.prev_state.process_event(ev);
+        if dev.is_allowed(&ev) {
+            return Some(ev);
+        }
+    }
+}
+
+#[inline]
+fn next_compensation_event(state: &mut Option<SyncState>, dev: &Device) -> Option<InputEvent> {
     let sync = state.as_mut()?;
+    let new_event = |time: &libc::timeval, type_: EventType, code: u16, value: i32| {
+        InputEvent::from(input_event {
+            time: *time,
+            type_: type_.0,
+            code,
+            value,
+        })
+    };
+    let syn_report = |time: &libc::timeval| {
+        new_event(
+            time,
+            EventType::SYNCHRONIZATION,
+            SynchronizationCode::SYN_REPORT.0,
+            0,
+        )
+    };
+    // the consumer has to be told to switch slots before it is told about any per-slot value
+    let select_slot_or = |time: &libc::timeval, old_mt: &MtSlots, slot: usize, ev: InputEvent| {
+        if old_mt.current_slot() != slot {
+            new_event(
+                time,
+                EventType::ABSOLUTE,
+                AbsoluteAxisCode::ABS_MT_SLOT.0,
+                slot as i32,
+            )
+        } else {
+            ev
+        }
+    };
     // this macro checks if there are any differences between the old state and the new for the
     // specific substate(?) that we're checking and if so returns an input_event with the value set
     // to the value from the up-to-date state
     macro_rules! try_compensate {
         ($time:expr, $start:ident : $typ:ident, $evtype:ident, $sync:ident, $supporteds:ident, $state:ty, $get_state:expr, $get_value:expr) => {
+            try_compensate!(
+                $time,
+                $start: $typ,
+                $evtype,
+                $sync,
+                $supporteds,
+                $state,
+                $get_state,
+                $get_value,
+                |_| false
+            )
+        };
+        ($time:expr, $start:ident : $typ:ident, $evtype:ident, $sync:ident, $supporteds:ident, $state:ty, $get_state:expr, $get_value:expr, $skip:expr) => {
             if let Some(supported_types) = dev.$supporteds() {
                 let types_to_check = supported_types.slice_iter(*$start);
                 let get_state: fn(&DeviceState) -> $state = $get_state;
                 let vals = get_state(&dev.state);
                 let old_vals = get_state(&dev.prev_state);
                 let get_value: fn($state, $typ) -> _ = $get_value;
+                let skip = $skip;
                 for typ in types_to_check {
+                    if skip(typ) {
+                        continue;
+                    }
                     let prev = get_value(old_vals, typ);
                     let value = get_value(vals, typ);
                     if prev != value {
@@ -508,8 +750,38 @@ fn compensate_events(state: &mut Option<SyncState>, dev: &mut Device) -> Option<
         };
     }
     loop {
-        // check keys, then abs axes, then switches, then leds
+        // end replaced contacts, then check keys, abs axes, switches, leds, sounds and finally
+        // the multi-touch slots
         match sync {
+            SyncState::MtEnds { time, slot, ended } => {
+                if let (Some(mt), Some(old_mt)) = (dev.state.mt_vals(), dev.prev_state.mt_vals()) {
+                    while *slot < mt.num_slots() {
+                        match (old_mt.tracking_id(*slot), mt.tracking_id(*slot)) {
+                            (Some(old_id), Some(id)) if old_id != id => {
+                                let end = new_event(
+                                    time,
+                                    EventType::ABSOLUTE,
+                                    AbsoluteAxisCode::ABS_MT_TRACKING_ID.0,
+                                    -1,
+                                );
+                                // no need for a frame of its own if the end is masked anyway
+                                *ended |= dev.is_allowed(&end);
+                                return Some(select_slot_or(time, old_mt, *slot, end));
+                            }
+                            _ => *slot += 1,
+                        }
+                    }
+                }
+                let (time, ended) = (*time, *ended);
+                *sync = SyncState::KeyTypes {
+                    time,
+                    start: KeyCode::new(0),
+                };
+                if ended {
+                    return Some(syn_report(&time));
+                }
+                continue;
+            }
             SyncState::KeyTypes { time, start } => {
                 try_compensate!(
                     time,
@@ -528,6 +800,8 @@ fn compensate_events(state: &mut Option<SyncState>, dev: &mut Device) -> Option<
                 continue;
             }
             SyncState::Absolutes { time, start } => {
+                // the multi-touch axes are compensated per slot below
+                let has_mt = dev.state.mt_vals.is_some();
                 try_compensate!(
                     time,
                     start: AbsoluteAxisCode,
@@ -536,7 +810,11 @@ fn compensate_events(state: &mut Option<SyncState>, dev: &mut Device) -> Option<
                     supported_absolute_axes,
                     &[input_absinfo],
                     |st| st.abs_vals().unwrap(),
-                    |vals, abs| vals[abs.0 as usize].value
+                    |vals, abs| vals[abs.0 as usize].value,
+                    |abs: AbsoluteAxisCode| {
+                        has_mt
+                            && (abs == AbsoluteAxisCode::ABS_MT_SLOT || abs.is_mt_slot_axis())
+                    }
                 );
                 *sync = SyncState::Switches {
                     time: *time,
@@ -572,12 +850,60 @@ fn compensate_events(state: &mut Option<SyncState>, dev: &mut Device) -> Option<
                     |st| st.led_vals().unwrap(),
                     |vals, led| vals.contains(led)
                 );
-                let ev = InputEvent::from(input_event {
+                *sync = SyncState::Sounds {
                     time: *time,
-                    type_: EventType::SYNCHRONIZATION.0,
-                    code: SynchronizationCode::SYN_REPORT.0,
-                    value: 0,
-                });
+                    start: SoundCode(0),
+                };
+                continue;
+            }
+            SyncState::Sounds { time, start } => {
+                try_compensate!(
+                    time,
+                    start: SoundCode,
+                    SOUND,
+                    Sounds,
+                    supported_sounds,
+                    &AttributeSetRef<SoundCode>,
+                    |st| st.snd_vals().unwrap(),
+                    |vals, snd| vals.contains(snd)
+                );
+                *sync = SyncState::MtSlots {
+                    time: *time,
+                    slot: 0,
+                };
+                continue;
+            }
+            SyncState::MtSlots { time, slot } => {
+                if let (Some(mt), Some(old_mt), Some(supported_abs)) = (
+                    dev.state.mt_vals(),
+                    dev.prev_state.mt_vals(),
+                    dev.supported_absolute_axes(),
+                ) {
+                    while *slot < mt.num_slots() {
+                        for axis in supported_abs.iter().filter(|axis| axis.is_mt_slot_axis()) {
+                            let value = mt.get(*slot, axis);
+                            if old_mt.get(*slot, axis) != value {
+                                let ev = new_event(
+                                    time,
+                                    EventType::ABSOLUTE,
+                                    axis.0,
+                                    value.unwrap_or_default(),
+                                );
+                                return Some(select_slot_or(time, old_mt, *slot, ev));
+                            }
+                        }
+                        *slot += 1;
+                    }
+                    if old_mt.current_slot() != mt.current_slot() {
+                        return Some(new_event(
+                            time,
+                            EventType::ABSOLUTE,
+                            AbsoluteAxisCode::ABS_MT_SLOT.0,
+                            mt.current_slot() as i32,
+                        ));
+                    }
+                }
+                let ev = syn_report(time);
                 *state = None;
                 return Some(ev);
             }
@@ -591,7 +917,6 @@ impl Iterator for FetchEventsSynced<'_> {
         // first: check if we need to emit compensatory events due to a SYN_DROPPED we found in the
         // last batch of blocks
         if let Some(ev) = compensate_events(&mut self.sync, self.dev) {
-            self.dev.prev_state.process_event(ev);
             return Some(ev);
         }
         let state = &mut self.dev.state;
@@ -619,6 +944,83 @@ impl Drop for FetchEventsSynced<'_> {
     }
 }
 
+/// The frames of a [`Device`], produced by [`Device::fetch_frames`].
+///
+/// Frames borrow from the device's buffer, so they are handed out one at a time by
+/// [`next_frame`](Self::next_frame) rather than through [`Iterator`].
+pub struct FetchFramesSynced<'a> {
+    events: FetchEventsSynced<'a>,
+    /// Holds the events of the synthetic frame we're currently returning to the consumer.
+    frame_buf: Vec<InputEvent>,
+}
+
+impl FetchFramesSynced<'_> {
+    /// Returns the next complete frame, or `None` if there is none left.
+    pub fn next_frame(&mut self) -> Option<Frame<'_>> {
+        let events = &mut self.events;
+        let next = next_frame(
+            &mut events.sync,
+            events.dev,
+            &mut events.range,
+            &mut events.consumed_to,
+            &mut self.frame_buf,
+        )?;
+        Some(next.to_frame(&events.dev.raw.event_buf, &self.frame_buf))
+    }
+}
+
+/// Where the next frame of a [`Device`] can be found.
+enum NextFrame {
+    /// The frame is made up of compensatory events, collected into a separate buffer.
+    Synthetic(libc::timeval),
+    /// The frame is this block of `dev.raw.event_buf`, including its `SYN_REPORT`.
+    Block(std::ops::Range<usize>),
+}
+
+impl NextFrame {
+    fn to_frame<'a>(&self, event_buf: &'a [input_event], frame_buf: &'a [InputEvent]) -> Frame<'a> {
+        match self {
+            NextFrame::Synthetic(time) => Frame::new(frame_buf, *time, true),
+            NextFrame::Block(block) => Frame::from_block(&event_buf[block.clone()], false),
+        }
+    }
+}
+
+/// Finds the next frame, either by collecting pending compensatory events into `frame_buf` or by
+/// taking the rest of the current block (or the next one) of the event buffer.
+fn next_frame(
+    sync: &mut Option<SyncState>,
+    dev: &mut Device,
+    range: &mut std::ops::Range<usize>,
+    consumed_to: &mut usize,
+    frame_buf: &mut Vec<InputEvent>,
+) -> Option<NextFrame> {
+    frame_buf.clear();
+    while let Some(ev) = compensate_events(sync, dev) {
+        if is_syn_report(ev.as_ref()) {
+            return Some(NextFrame::Synthetic(ev.as_ref().time));
+        }
+        frame_buf.push(ev);
+    }
+    if range.start == range.end {
+        let state = &mut dev.state;
+        let (res, end) = next_block(range, &dev.raw.event_buf, |ev| state.process_event(ev));
+        if let Some(end) = end {
+            *consumed_to = end;
+        }
+        if let Err(requires_sync) = res {
+            if requires_sync {
+                dev.block_dropped = true;
+            }
+            return None;
+        }
+    }
+    // the consumer may have already taken some of this block's events one at a time
+    let block = range.clone();
+    range.start = range.end;
+    Some(NextFrame::Block(block))
+}
+
 /// Err(true) means the device should sync the state with ioctl
 #[inline]
 fn sync_events(
@@ -627,38 +1029,53 @@ fn sync_events(
     mut handle_event: impl FnMut(InputEvent),
 ) -> (Result<input_event, bool>, Option<usize>) {
     let mut consumed_to = None;
-    let res = 'outer: loop {
+    let res = loop {
         if let Some(idx) = range.next() {
             // we're going through and emitting the events of a block that we checked
             break Ok(event_buf[idx]);
         }
-        // find the range of this new block: look for a SYN_REPORT
-        let block_start = range.end;
-        let mut block_dropped = false;
-        for (i, ev) in event_buf.iter().enumerate().skip(block_start) {
-            let ev = InputEvent::from(*ev);
-            match ev.destructure() {
-                EventSummary::Synchronization(_, SynchronizationCode::SYN_DROPPED, _) => {
-                    block_dropped = true;
-                }
-                EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) => {
-                    consumed_to = Some(i + 1);
-                    if block_dropped {
-                        *range = event_buf.len()..event_buf.len();
-                        break 'outer Err(true);
-                    } else {
-                        *range = block_start..i + 1;
-                        continue 'outer;
-                    }
-                }
-                _ => handle_event(ev),
-            }
+        let (res, end) = next_block(range, event_buf, &mut handle_event);
+        if end.is_some() {
+            consumed_to = end;
+        }
+        if let Err(requires_sync) = res {
+            break Err(requires_sync);
         }
-        break Err(false);
     };
     (res, consumed_to)
 }
 
+/// Finds the block following `range` by looking for a SYN_REPORT, and sets `range` to it,
+/// SYN_REPORT included. Err(true) means the device should sync the state with ioctl.
+#[inline]
+fn next_block(
+    range: &mut std::ops::Range<usize>,
+    event_buf: &[input_event],
+    mut handle_event: impl FnMut(InputEvent),
+) -> (Result<(), bool>, Option<usize>) {
+    let block_start = range.end;
+    let mut block_dropped = false;
+    for (i, ev) in event_buf.iter().enumerate().skip(block_start) {
+        let ev = InputEvent::from(*ev);
+        match ev.destructure() {
+            EventSummary::Synchronization(_, SynchronizationCode::SYN_DROPPED, _) => {
+                block_dropped = true;
+            }
+            EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) => {
+                if block_dropped {
+                    *range = event_buf.len()..event_buf.len();
+                    return (Err(true), Some(i + 1));
+                } else {
+                    *range = block_start..i + 1;
+                    return (Ok(()), Some(i + 1));
+                }
+            }
+            _ => handle_event(ev),
+        }
+    }
+    (Err(false), None)
+}
+
 impl fmt::Display for Device {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "{}:", self.name().unwrap_or("Unnamed device"))?;
@@ -750,10 +1167,18 @@ impl fmt::Display for Device {
             }
         }
 
-        if let Some(supported_snd) = self.supported_sounds() {
-            write!(f, "  Sounds:")?;
+        if let (Some(supported_snd), Some(snd_vals)) =
+            (self.supported_sounds(), self.state.snd_vals())
+        {
+            writeln!(f, "  Sounds:")?;
             for snd in supported_snd.iter() {
-                writeln!(f, "    {:?} (index {})", snd, snd.0)?;
+                writeln!(
+                    f,
+                    "    {:?} ({:?}, index {})",
+                    snd,
+                    snd_vals.contains(snd),
+                    snd.0
+                )?;
             }
         }
 
@@ -779,59 +1204,51 @@ impl fmt::Display for Device {
     }
 }
 
-#[cfg(feature = "tokio")]
-mod tokio_stream {
+#[cfg(any(feature = "tokio", feature = "async-io"))]
+mod stream_core {
     use super::*;
 
-    use std::future::poll_fn;
+    use crate::async_stream::AsyncSource;
     use std::task::{ready, Context, Poll};
-    use tokio::io::unix::AsyncFd;
 
-    /// An asynchronous stream of input events.
-    ///
-    /// This can be used by calling [`stream.next_event().await?`](Self::next_event), or if you
-    /// need to pass it as a stream somewhere, the [`futures::Stream`](Stream) implementation.
-    /// There's also a lower-level [`Self::poll_event`] function if you need to fetch an event from
-    /// inside a `Future::poll` impl.
-    pub struct EventStream {
-        device: AsyncFd<Device>,
+    /// The runtime-independent part of the synchronized event streams.
+    pub(super) struct EventStreamCore<S> {
+        device: S,
         event_range: std::ops::Range<usize>,
         consumed_to: usize,
         sync: Option<SyncState>,
+        frame_buf: Vec<InputEvent>,
+        /// Set once reading failed because the device was unplugged or revoked.
+        gone: bool,
     }
-    impl Unpin for EventStream {}
 
-    impl EventStream {
-        pub(crate) fn new(device: Device) -> io::Result<Self> {
-            device.set_nonblocking(true)?;
-            let device = AsyncFd::new(device)?;
+    impl<S: AsyncSource<Device = Device>> EventStreamCore<S> {
+        pub(super) fn new(device: Device) -> io::Result<Self> {
             Ok(Self {
-                device,
+                device: S::new(device)?,
                 event_range: 0..0,
                 consumed_to: 0,
                 sync: None,
+                frame_buf: Vec::new(),
+                gone: false,
             })
         }
 
-        /// Returns a reference to the underlying device
-        pub fn device(&self) -> &Device {
+        pub(super) fn device(&self) -> &Device {
             self.device.get_ref()
         }
 
-        /// Returns a mutable reference to the underlying device
-        pub fn device_mut(&mut self) -> &mut Device {
+        pub(super) fn device_mut(&mut self) -> &mut Device {
             self.device.get_mut()
         }
 
-        /// Try to wait for the next event in this stream. Any errors are likely to be fatal, i.e.
-        /// any calls afterwards will likely error as well.
-        pub async fn next_event(&mut self) -> io::Result<InputEvent> {
-            poll_fn(|cx| self.poll_event(cx)).await
+        #[cfg(any(feature = "stream-trait", feature = "async-io"))]
+        pub(super) fn is_gone(&self) -> bool {
+            self.gone
         }
 
-        /// A lower-level function for directly polling this stream.
-        pub fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<InputEvent>> {
-            'outer: loop {
+        pub(super) fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<InputEvent>> {
+            loop {
                 let dev = self.device.get_mut();
                 if let Some(ev) = compensate_events(&mut self.sync, dev) {
                     return Poll::Ready(Ok(ev));
@@ -852,40 +1269,222 @@ mod tokio_stream {
                         }
                     }
                 }
-                dev.raw.event_buf.drain(..self.consumed_to);
-                self.consumed_to = 0;
-
-                loop {
-                    let mut guard = ready!(self.device.poll_read_ready_mut(cx))?;
-
-                    let res = guard.try_io(|device| device.get_mut().fetch_events_inner());
-                    match res {
-                        Ok(res) => {
-                            self.sync = res?;
-                            self.event_range = 0..0;
-                            continue 'outer;
-                        }
-                        Err(_would_block) => continue,
-                    }
+                ready!(self.poll_refill(cx))?;
+            }
+        }
+
+        pub(super) fn to_frame(&self, next: NextFrame) -> Frame<'_> {
+            next.to_frame(&self.device().raw.event_buf, &self.frame_buf)
+        }
+
+        pub(super) fn poll_next_frame(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<io::Result<NextFrame>> {
+            loop {
+                let next = next_frame(
+                    &mut self.sync,
+                    self.device.get_mut(),
+                    &mut self.event_range,
+                    &mut self.consumed_to,
+                    &mut self.frame_buf,
+                );
+                if let Some(next) = next {
+                    return Poll::Ready(Ok(next));
+                }
+                ready!(self.poll_refill(cx))?;
+            }
+        }
+
+        /// Drops the events we're done with and waits for new ones to arrive. Once the device is
+        /// gone, this keeps failing with `ENODEV` without touching the fd again.
+        fn poll_refill(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            if self.gone {
+                return Poll::Ready(Err(io::Error::from_raw_os_error(libc::ENODEV)));
+            }
+            let consumed_to = std::mem::take(&mut self.consumed_to);
+            self.device.get_mut().raw.event_buf.drain(..consumed_to);
+
+            let res = ready!(self
+                .device
+                .poll_read_with(cx, |device| device.fetch_events_inner()));
+            match res {
+                Ok(sync) => {
+                    self.sync = sync;
+                    self.event_range = 0..0;
+                    Poll::Ready(Ok(()))
+                }
+                Err(e) => {
+                    self.gone = crate::is_device_gone(&e);
+                    Poll::Ready(Err(e))
                 }
             }
         }
     }
+}
+
+#[cfg(feature = "tokio")]
+mod tokio_stream {
+    use super::stream_core::EventStreamCore;
+    use super::*;
+
+    use std::future::poll_fn;
+    use std::task::{ready, Context, Poll};
+    use tokio::io::unix::AsyncFd;
+
+    /// An asynchronous stream of input events.
+    ///
+    /// This can be used by calling [`stream.next_event().await?`](Self::next_event), or if you
+    /// need to pass it as a stream somewhere, the [`futures::Stream`](Stream) implementation.
+    /// There's also a lower-level [`Self::poll_event`] function if you need to fetch an event from
+    /// inside a `Future::poll` impl.
+    pub struct EventStream {
+        inner: EventStreamCore<AsyncFd<Device>>,
+    }
+    impl Unpin for EventStream {}
+
+    impl EventStream {
+        pub(crate) fn new(device: Device) -> io::Result<Self> {
+            Ok(Self {
+                inner: EventStreamCore::new(device)?,
+            })
+        }
+
+        /// Returns a reference to the underlying device
+        pub fn device(&self) -> &Device {
+            self.inner.device()
+        }
+
+        /// Returns a mutable reference to the underlying device
+        pub fn device_mut(&mut self) -> &mut Device {
+            self.inner.device_mut()
+        }
+
+        /// Try to wait for the next event in this stream. Any errors are likely to be fatal, i.e.
+        /// any calls afterwards will likely error as well.
+        pub async fn next_event(&mut self) -> io::Result<InputEvent> {
+            poll_fn(|cx| self.poll_event(cx)).await
+        }
+
+        /// A lower-level function for directly polling this stream.
+        pub fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<InputEvent>> {
+            self.inner.poll_event(cx)
+        }
+
+        /// Try to wait for the next complete [`Frame`] in this stream. Synthetic frames are
+        /// inserted after a SYN_DROPPED, as with [`Device::fetch_frames`].
+        pub async fn next_frame(&mut self) -> io::Result<Frame<'_>> {
+            let next = poll_fn(|cx| self.inner.poll_next_frame(cx)).await?;
+            Ok(self.inner.to_frame(next))
+        }
+
+        /// A lower-level function for directly polling this stream for frames.
+        pub fn poll_frame(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Frame<'_>>> {
+            let next = ready!(self.inner.poll_next_frame(cx))?;
+            Poll::Ready(Ok(self.inner.to_frame(next)))
+        }
+    }
 
     #[cfg(feature = "stream-trait")]
     impl futures_core::Stream for EventStream {
         type Item = io::Result<InputEvent>;
+
+        /// Ends the stream after reporting that the device was unplugged or revoked.
         fn poll_next(
             self: std::pin::Pin<&mut Self>,
             cx: &mut Context<'_>,
         ) -> Poll<Option<Self::Item>> {
-            self.get_mut().poll_event(cx).map(Some)
+            let this = self.get_mut();
+            if this.inner.is_gone() {
+                return Poll::Ready(None);
+            }
+            this.poll_event(cx).map(Some)
         }
     }
 }
 #[cfg(feature = "tokio")]
 pub use tokio_stream::EventStream;
 
+#[cfg(feature = "async-io")]
+pub(crate) mod async_io_stream {
+    use super::stream_core::EventStreamCore;
+    use super::*;
+
+    use crate::async_stream::AsyncIo;
+    use std::future::poll_fn;
+    use std::task::{ready, Context, Poll};
+
+    /// An asynchronous stream of input events, driven by the `async-io` reactor.
+    ///
+    /// This can be used by calling [`stream.next_event().await?`](Self::next_event), or if you
+    /// need to pass it as a stream somewhere, the [`futures::Stream`](futures_core::Stream)
+    /// implementation. There's also a lower-level [`Self::poll_event`] function if you need to
+    /// fetch an event from inside a `Future::poll` impl.
+    pub struct EventStream {
+        inner: EventStreamCore<AsyncIo<Device>>,
+    }
+    impl Unpin for EventStream {}
+
+    impl EventStream {
+        /// Puts `device` into nonblocking mode and registers it with the reactor.
+        pub fn new(device: Device) -> io::Result<Self> {
+            Ok(Self {
+                inner: EventStreamCore::new(device)?,
+            })
+        }
+
+        /// Returns a reference to the underlying device
+        pub fn device(&self) -> &Device {
+            self.inner.device()
+        }
+
+        /// Returns a mutable reference to the underlying device
+        pub fn device_mut(&mut self) -> &mut Device {
+            self.inner.device_mut()
+        }
+
+        /// Try to wait for the next event in this stream. Any errors are likely to be fatal, i.e.
+        /// any calls afterwards will likely error as well.
+        pub async fn next_event(&mut self) -> io::Result<InputEvent> {
+            poll_fn(|cx| self.poll_event(cx)).await
+        }
+
+        /// A lower-level function for directly polling this stream.
+        pub fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<InputEvent>> {
+            self.inner.poll_event(cx)
+        }
+
+        /// Try to wait for the next complete [`Frame`] in this stream. Synthetic frames are
+        /// inserted after a SYN_DROPPED, as with [`Device::fetch_frames`].
+        pub async fn next_frame(&mut self) -> io::Result<Frame<'_>> {
+            let next = poll_fn(|cx| self.inner.poll_next_frame(cx)).await?;
+            Ok(self.inner.to_frame(next))
+        }
+
+        /// A lower-level function for directly polling this stream for frames.
+        pub fn poll_frame(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Frame<'_>>> {
+            let next = ready!(self.inner.poll_next_frame(cx))?;
+            Poll::Ready(Ok(self.inner.to_frame(next)))
+        }
+    }
+
+    impl futures_core::Stream for EventStream {
+        type Item = io::Result<InputEvent>;
+
+        /// Ends the stream after reporting that the device was unplugged or revoked.
+        fn poll_next(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+            if this.inner.is_gone() {
+                return Poll::Ready(None);
+            }
+            this.poll_event(cx).map(Some)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -956,4 +1555,18 @@ mod tests {
         assert_eq!(next(), (Err(false), None));
         assert_eq!(next(), (Err(false), None));
     }
+
+    #[test]
+    fn test_next_block() {
+        let evs = &[KEY4, KEY4, REPORT, REPORT, KEY4, DROPPED, REPORT, KEY4];
+        let mut range = 0..0;
+        let mut next = || {
+            let res = next_block(&mut range, evs, |_| {});
+            (res, range.clone())
+        };
+        assert_eq!(next(), ((Ok(()), Some(3)), 0..3));
+        assert_eq!(next(), ((Ok(()), Some(4)), 3..4));
+        assert_eq!(next(), ((Err(true), Some(7)), 8..8));
+        assert_eq!(next(), ((Err(false), None), 8..8));
+    }
 }
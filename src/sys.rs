@@ -48,6 +48,14 @@ pub struct uinput_ff_erase {
     pub effect_id: u32,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct input_mask {
+    pub type_: u32,
+    pub codes_size: u32,
+    pub codes_ptr: u64,
+}
+
 ioctl_read!(eviocgeffects, b'E', 0x84, ::libc::c_int);
 ioctl_read!(eviocgid, b'E', 0x02, /*struct*/ input_id);
 ioctl_read!(eviocgkeycode, b'E', 0x04, [::libc::c_uint; 2]);
@@ -73,7 +81,9 @@ ioctl_read_buf!(eviocgsw, b'E', 0x1b, u8);
 ioctl_write_ptr!(eviocsff, b'E', 0x80, ff_effect);
 ioctl_write_int!(eviocgrab, b'E', 0x90);
 ioctl_write_int!(eviocrevoke, b'E', 0x91);
-ioctl_write_int!(eviocsclockid, b'E', 0xa0);
+ioctl_read!(eviocgmask, b'E', 0x92, input_mask);
+ioctl_write_ptr!(eviocsmask, b'E', 0x93, input_mask);
+ioctl_write_ptr!(eviocsclockid, b'E', 0xa0, ::libc::c_int);
 
 const UINPUT_IOCTL_BASE: u8 = b'U';
 ioctl_write_ptr!(ui_dev_setup, UINPUT_IOCTL_BASE, 3, uinput_setup);
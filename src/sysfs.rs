@@ -0,0 +1,234 @@
+use std::fs;
+use std::io;
+use std::os::fd::{AsRawFd, BorrowedFd};
+use std::path::{Path, PathBuf};
+
+use crate::attribute_set::ArrayedEvdevEnum;
+use crate::constants::*;
+use crate::{AttributeSet, BusType, InputId, KeyCode, SYSFS_ROOT};
+
+/// Metadata about an evdev device from sysfs, without going through libudev.
+///
+/// This is resolved from the device number of the opened device node, so it works the same for
+/// devices opened by path and for file descriptors passed in with
+/// [`Device::from_fd`](crate::Device::from_fd). Get it with
+/// [`Device::sysfs_info`](crate::Device::sysfs_info).
+///
+/// The sysfs attributes are read when the accessors are called, not up front, so they fail with
+/// an error once the device is gone.
+#[derive(Debug, Clone)]
+pub struct SysfsInfo {
+    /// The sysfs directory of the event node, e.g. `/sys/devices/.../input/input3/event2`.
+    syspath: PathBuf,
+    /// The sysfs directory of the input node the event node belongs to, e.g.
+    /// `/sys/devices/.../input/input3`.
+    input_syspath: PathBuf,
+}
+
+macro_rules! capabilities {
+    ($($(#[$attr:meta])* $fn:ident: $typ:ty = $file:literal,)*) => {
+        $(
+            $(#[$attr])*
+            ///
+            #[doc = concat!("Read from `capabilities/", $file, "` of the input node.")]
+            pub fn $fn(&self) -> io::Result<AttributeSet<$typ>> {
+                self.read_bitmap(concat!("capabilities/", $file))
+            }
+        )*
+    };
+}
+
+impl SysfsInfo {
+    /// Resolves the sysfs directories of the device node open as `fd`.
+    pub fn from_fd(fd: BorrowedFd<'_>) -> io::Result<Self> {
+        Self::from_fd_in(fd, SYSFS_ROOT)
+    }
+
+    /// Resolves the sysfs directories of the device node open as `fd`, with sysfs mounted at
+    /// `sysfs_root` instead of `/sys`.
+    pub fn from_fd_in(fd: BorrowedFd<'_>, sysfs_root: impl AsRef<Path>) -> io::Result<Self> {
+        let stat = nix::sys::stat::fstat(fd.as_raw_fd())?;
+        Self::from_devnum_in(stat.st_rdev, sysfs_root)
+    }
+
+    /// Resolves the sysfs directories of the device node at `path`, without opening it.
+    pub(crate) fn from_path(path: &Path) -> io::Result<Self> {
+        let stat = nix::sys::stat::stat(path)?;
+        Self::from_devnum_in(stat.st_rdev, SYSFS_ROOT)
+    }
+
+    /// Resolves the sysfs directories of the character device with the device number `rdev`,
+    /// with sysfs mounted at `sysfs_root`.
+    pub fn from_devnum_in(rdev: libc::dev_t, sysfs_root: impl AsRef<Path>) -> io::Result<Self> {
+        let devnum = format!("{}:{}", libc::major(rdev), libc::minor(rdev));
+        let syspath = sysfs_root
+            .as_ref()
+            .join("dev/char")
+            .join(devnum)
+            .canonicalize()?;
+        let input_syspath = syspath.join("device").canonicalize()?;
+        Ok(SysfsInfo {
+            syspath,
+            input_syspath,
+        })
+    }
+
+    /// Returns the sysfs directory of the event node itself.
+    pub fn syspath(&self) -> &Path {
+        &self.syspath
+    }
+
+    /// Returns the sysfs directory of the input node that the event node belongs to, e.g.
+    /// `/sys/devices/virtual/input/input123` for a uinput device.
+    pub fn input_syspath(&self) -> &Path {
+        &self.input_syspath
+    }
+
+    /// Returns the name of the device, like [`Device::name`](crate::Device::name).
+    pub fn name(&self) -> io::Result<Option<String>> {
+        self.read_optional_attr("name")
+    }
+
+    /// Returns the physical location of the device, like
+    /// [`Device::physical_path`](crate::Device::physical_path).
+    pub fn physical_path(&self) -> io::Result<Option<String>> {
+        self.read_optional_attr("phys")
+    }
+
+    /// Returns the unique identifier of the device, like
+    /// [`Device::unique_name`](crate::Device::unique_name).
+    pub fn unique_name(&self) -> io::Result<Option<String>> {
+        self.read_optional_attr("uniq")
+    }
+
+    /// Returns the bus type, vendor, product and version of the device, like
+    /// [`Device::input_id`](crate::Device::input_id).
+    pub fn input_id(&self) -> io::Result<InputId> {
+        let read_id = |name: &str| -> io::Result<u16> {
+            let value = self.read_attr(&format!("id/{}", name))?;
+            u16::from_str_radix(&value, 16)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        };
+        Ok(InputId::new(
+            BusType(read_id("bustype")?),
+            read_id("vendor")?,
+            read_id("product")?,
+            read_id("version")?,
+        ))
+    }
+
+    /// Returns the modalias of the input node, which encodes its ids and capabilities, e.g.
+    /// `input:b0003v046DpC52Be0111-e0,1,2,4,...`.
+    pub fn modalias(&self) -> io::Result<String> {
+        self.read_attr("modalias")
+    }
+
+    /// Returns the name of the driver bound to the direct parent of the input device, e.g.
+    /// `hid-generic` or `logitech-dj` for HID devices and `atkbd` for PS/2 keyboards, or `None` if
+    /// there is no driver, like for uinput devices.
+    ///
+    /// Drivers further up, like `usbhid` or `i8042`, aren't taken into account.
+    pub fn driver(&self) -> io::Result<Option<String>> {
+        match fs::read_link(self.input_syspath.join("device/driver")) {
+            Ok(driver) => Ok(driver
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the sysfs directory of the USB device this input device is connected through,
+    /// if any.
+    ///
+    /// This is the device itself, e.g. `/sys/devices/.../usb1/1-2`, not one of its interfaces.
+    pub fn usb_device(&self) -> Option<PathBuf> {
+        self.find_ancestor(|dir| {
+            subsystem(dir).as_deref() == Some("usb") && dir.join("idVendor").exists()
+        })
+    }
+
+    /// Returns the sysfs directory of the Bluetooth connection this input device is connected
+    /// through, if any, e.g. `/sys/devices/.../hci0/hci0:256`.
+    pub fn bluetooth_device(&self) -> Option<PathBuf> {
+        self.find_ancestor(|dir| subsystem(dir).as_deref() == Some("bluetooth"))
+    }
+
+    /// Returns the input properties of the device.
+    ///
+    /// Read from `properties` of the input node.
+    pub fn properties(&self) -> io::Result<AttributeSet<PropType>> {
+        self.read_bitmap("properties")
+    }
+
+    capabilities! {
+        /// Returns the event types the device supports.
+        capabilities_ev: EventType = "ev",
+        /// Returns the keys and buttons the device supports.
+        capabilities_key: KeyCode = "key",
+        /// Returns the relative axes the device supports.
+        capabilities_rel: RelativeAxisCode = "rel",
+        /// Returns the absolute axes the device supports.
+        capabilities_abs: AbsoluteAxisCode = "abs",
+        /// Returns the misc events the device supports.
+        capabilities_msc: MiscCode = "msc",
+        /// Returns the switches the device supports.
+        capabilities_sw: SwitchCode = "sw",
+        /// Returns the LEDs the device supports.
+        capabilities_led: LedCode = "led",
+        /// Returns the sounds the device supports.
+        capabilities_snd: SoundCode = "snd",
+        /// Returns the force feedback effects the device supports.
+        capabilities_ff: FFEffectCode = "ff",
+    }
+
+    fn read_attr(&self, name: &str) -> io::Result<String> {
+        let value = fs::read_to_string(self.input_syspath.join(name))?;
+        Ok(value.trim_end().to_owned())
+    }
+
+    /// Reads an attribute that is empty if the device doesn't have it.
+    fn read_optional_attr(&self, name: &str) -> io::Result<Option<String>> {
+        let value = self.read_attr(name)?;
+        Ok(if value.is_empty() { None } else { Some(value) })
+    }
+
+    fn read_bitmap<T: ArrayedEvdevEnum>(&self, name: &str) -> io::Result<AttributeSet<T>> {
+        parse_bitmap(&self.read_attr(name)?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid sysfs bitmap"))
+    }
+
+    fn find_ancestor(&self, mut pred: impl FnMut(&Path) -> bool) -> Option<PathBuf> {
+        self.input_syspath
+            .ancestors()
+            .skip(1)
+            // skips over directories that aren't devices, like the `input` one above the node
+            .filter(|dir| dir.join("uevent").exists())
+            .find(|dir| pred(dir))
+            .map(Path::to_path_buf)
+    }
+}
+
+/// Returns the name of the subsystem a sysfs device belongs to.
+fn subsystem(dir: &Path) -> Option<String> {
+    let link = fs::read_link(dir.join("subsystem")).ok()?;
+    Some(link.file_name()?.to_string_lossy().into_owned())
+}
+
+/// Parses a sysfs bitmap, which is made up of space-separated hex words of the kernel's
+/// `unsigned long` size, most significant word first.
+fn parse_bitmap<T: ArrayedEvdevEnum>(s: &str) -> Option<AttributeSet<T>> {
+    const WORD_BYTES: usize = std::mem::size_of::<libc::c_ulong>();
+    let mut set = AttributeSet::new();
+    let bytes = set.as_mut_raw_slice();
+    for (i, word) in s.split_whitespace().rev().enumerate() {
+        let word = u64::from_str_radix(word, 16).ok()?;
+        for (j, byte) in word.to_le_bytes()[..WORD_BYTES].iter().enumerate() {
+            // bits beyond what we know about are ignored, just like the ioctls do
+            if let Some(b) = bytes.get_mut(i * WORD_BYTES + j) {
+                *b = *byte;
+            }
+        }
+    }
+    Some(set)
+}
@@ -0,0 +1,419 @@
+//! Tracking the pens and other tools of graphics tablets.
+//!
+//! Tablets report which tool is close to their surface with the `BTN_TOOL_PEN`,
+//! `BTN_TOOL_RUBBER`, ... keys, its position, pressure, tilt and distance with absolute axes,
+//! and whether its tip touches the surface with `BTN_TOUCH`. Wacom tablets also identify the
+//! tool by an id in `ABS_MISC` and a serial number in `MSC_SERIAL`, so that a drawing program can
+//! e.g. keep a color per pen. A [`Tablet`] turns the [`Frame`]s of such a device into
+//! [`ToolEvent`]s, in the order a drawing program needs them: a tool comes into proximity before
+//! it moves, and its tip goes up and its buttons are released before it leaves proximity.
+//!
+//! Pressure and distance are normalized to [0, 1], and tilt is converted to degrees using the
+//! [resolution](crate::AbsInfo::resolution) of the tilt axes, which is given in units per radian.
+//! Like libinput, tilt axes without a resolution are taken to span -64 to 64 degrees. Positions
+//! are left in the units of the device, which [`AxisNormalizer`]
+//! can convert to millimetres.
+//!
+//! ```no_run
+//! use evdev::tablet::{Tablet, ToolEvent};
+//! use evdev::Device;
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let mut device = Device::open("/dev/input/event0")?;
+//! let mut tablet = Tablet::new(&device)?;
+//! loop {
+//!     let mut frames = device.fetch_frames()?;
+//!     while let Some(frame) = frames.next_frame() {
+//!         for event in tablet.process_frame(frame) {
+//!             match event {
+//!                 ToolEvent::ProximityIn { tool, serial, .. } => {
+//!                     println!("{tool:?} {serial:?} is here")
+//!                 }
+//!                 ToolEvent::Axis { x, y, pressure, .. } => {
+//!                     println!("at {x}, {y} with {pressure:?}")
+//!                 }
+//!                 _ => {}
+//!             }
+//!         }
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::io;
+
+use crate::axis::{AxisNormalizer, AxisRange};
+use crate::{
+    AbsInfo, AbsoluteAxisCode, AttributeSet, AttributeSetRef, Device, EventType, Frame, InputEvent,
+    KeyCode, MiscCode,
+};
+
+/// The keys that report the tools, in the order they are checked.
+const TOOL_KEYS: [(KeyCode, ToolType); 7] = [
+    (KeyCode::BTN_TOOL_PEN, ToolType::Pen),
+    (KeyCode::BTN_TOOL_RUBBER, ToolType::Eraser),
+    (KeyCode::BTN_TOOL_BRUSH, ToolType::Brush),
+    (KeyCode::BTN_TOOL_PENCIL, ToolType::Pencil),
+    (KeyCode::BTN_TOOL_AIRBRUSH, ToolType::Airbrush),
+    (KeyCode::BTN_TOOL_MOUSE, ToolType::Mouse),
+    (KeyCode::BTN_TOOL_LENS, ToolType::Lens),
+];
+
+/// The tilt assumed for the ends of tilt axes without a resolution, in degrees.
+const ASSUMED_MAX_TILT: f32 = 64.0;
+
+/// The kind of a tablet tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ToolType {
+    /// A pen, `BTN_TOOL_PEN`.
+    Pen,
+    /// The eraser end of a pen, `BTN_TOOL_RUBBER`.
+    Eraser,
+    /// A brush, `BTN_TOOL_BRUSH`.
+    Brush,
+    /// A pencil, `BTN_TOOL_PENCIL`.
+    Pencil,
+    /// An airbrush, `BTN_TOOL_AIRBRUSH`.
+    Airbrush,
+    /// A mouse used on the tablet, `BTN_TOOL_MOUSE`.
+    Mouse,
+    /// A lens cursor, `BTN_TOOL_LENS`.
+    Lens,
+}
+
+impl ToolType {
+    /// Returns the key that reports the tool.
+    pub fn key(self) -> KeyCode {
+        TOOL_KEYS[self as usize].0
+    }
+}
+
+/// A change of the tool of a [`Tablet`].
+///
+/// The tilt is in degrees, positive to the right and towards the user, and the pressure and
+/// distance are in [0, 1]. They are `None` if the tablet doesn't have the axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToolEvent {
+    /// The tool came close enough to the tablet to be detected. The serial number and the id of
+    /// the tool are those reported by Wacom tablets in `MSC_SERIAL` and `ABS_MISC`.
+    ProximityIn {
+        tool: ToolType,
+        serial: Option<u32>,
+        tool_id: Option<i32>,
+    },
+    /// The tool moved, or its pressure, distance or tilt changed.
+    Axis {
+        tool: ToolType,
+        x: i32,
+        y: i32,
+        pressure: Option<f32>,
+        distance: Option<f32>,
+        tilt_x: Option<f32>,
+        tilt_y: Option<f32>,
+    },
+    /// The tip of the tool touched the tablet or was lifted from it.
+    Tip { tool: ToolType, down: bool },
+    /// A button of the tool, such as `BTN_STYLUS`, was pressed or released.
+    Button {
+        tool: ToolType,
+        button: KeyCode,
+        pressed: bool,
+    },
+    /// The tool left the proximity of the tablet.
+    ProximityOut { tool: ToolType, serial: Option<u32> },
+}
+
+/// A tool that is in proximity.
+#[derive(Debug, Clone, Copy)]
+struct Tool {
+    tool: ToolType,
+    serial: Option<u32>,
+}
+
+/// Follows the tools of a tablet and reports what they do.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct Tablet {
+    /// The range and current value of the axes of the tablet.
+    axes: HashMap<AbsoluteAxisCode, (AbsInfo, i32)>,
+    pressure: Option<AxisNormalizer>,
+    distance: Option<AxisNormalizer>,
+    keys: AttributeSet<KeyCode>,
+    tool: Option<Tool>,
+    tip_down: bool,
+    buttons: AttributeSet<KeyCode>,
+    /// The serial number reported in the current frame.
+    serial: Option<u32>,
+    axes_changed: bool,
+    events: Vec<ToolEvent>,
+}
+
+impl Tablet {
+    /// Creates a tracker for `device`, starting from its [cached state](Device::cached_state).
+    ///
+    /// Fails with `InvalidInput` if the device doesn't have any tools or doesn't report
+    /// `ABS_X` and `ABS_Y`.
+    pub fn new(device: &Device) -> io::Result<Self> {
+        let has_tools = device.supported_keys().map_or(false, |keys| {
+            TOOL_KEYS.iter().any(|(key, _)| keys.contains(*key))
+        });
+        let state = device.cached_state();
+        let axes: Vec<_> = match (device.supported_absolute_axes(), state.abs_vals()) {
+            (Some(supported), Some(vals)) => supported
+                .iter()
+                .map(|axis| (axis, AbsInfo(vals[axis.0 as usize])))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let has_position = [AbsoluteAxisCode::ABS_X, AbsoluteAxisCode::ABS_Y]
+            .iter()
+            .all(|code| axes.iter().any(|(axis, _)| axis == code));
+        if !has_tools || !has_position {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the device is not a tablet with tools",
+            ));
+        }
+
+        let mut tablet = Self::from_axes(&axes);
+        if let Some(keys) = state.key_vals() {
+            for key in keys.iter() {
+                tablet.keys.insert(key);
+            }
+        }
+        tablet.tool = tablet.held_tool().map(|tool| Tool { tool, serial: None });
+        if tablet.tool.is_some() {
+            tablet.tip_down = tablet.keys.contains(KeyCode::BTN_TOUCH);
+            tablet.buttons = tablet.held_buttons();
+        }
+        Ok(tablet)
+    }
+
+    /// Creates a tracker for a tablet with the given absolute axes, at the values of their
+    /// `AbsInfo`, and without a tool in proximity.
+    pub(crate) fn from_axes(axes: &[(AbsoluteAxisCode, AbsInfo)]) -> Self {
+        let normalizer = |code: AbsoluteAxisCode| {
+            axes.iter()
+                .find(|(axis, _)| *axis == code)
+                .map(|&(_, info)| AxisNormalizer::new(info).range(AxisRange::Unipolar))
+        };
+        Tablet {
+            axes: axes
+                .iter()
+                .map(|&(axis, info)| (axis, (info, info.value())))
+                .collect(),
+            pressure: normalizer(AbsoluteAxisCode::ABS_PRESSURE),
+            distance: normalizer(AbsoluteAxisCode::ABS_DISTANCE),
+            keys: AttributeSet::new(),
+            tool: None,
+            tip_down: false,
+            buttons: AttributeSet::new(),
+            serial: None,
+            axes_changed: false,
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns the tool that is in proximity.
+    pub fn tool(&self) -> Option<ToolType> {
+        self.tool.map(|tool| tool.tool)
+    }
+
+    /// Returns `true` if the tip of the tool touches the tablet.
+    pub fn is_tip_down(&self) -> bool {
+        self.tip_down
+    }
+
+    /// Updates the state of the tablet with a frame of the device, and returns what its tool did.
+    pub fn process_frame(&mut self, frame: Frame<'_>) -> impl Iterator<Item = ToolEvent> + '_ {
+        for ev in frame {
+            self.apply(ev);
+        }
+        self.update();
+        self.events.drain(..)
+    }
+
+    fn apply(&mut self, ev: InputEvent) {
+        match ev.event_type() {
+            EventType::KEY => {
+                let key = KeyCode(ev.code());
+                if ev.value() == 0 {
+                    self.keys.remove(key);
+                } else {
+                    self.keys.insert(key);
+                }
+            }
+            EventType::ABSOLUTE => {
+                if let Some((_, value)) = self.axes.get_mut(&AbsoluteAxisCode(ev.code())) {
+                    self.axes_changed |= *value != ev.value();
+                    *value = ev.value();
+                }
+            }
+            EventType::MISC if ev.code() == MiscCode::MSC_SERIAL.0 => {
+                // the serial is an unsigned number, even though events carry an `i32`
+                self.serial = Some(ev.value() as u32);
+            }
+            _ => {}
+        }
+    }
+
+    fn update(&mut self) {
+        let held = self.held_tool();
+        if let Some(tool) = self.tool {
+            if held != Some(tool.tool) {
+                self.leave(tool);
+            }
+        }
+        let serial = self.serial.take();
+        if let Some(tool) = held {
+            match &mut self.tool {
+                Some(current) => {
+                    if current.serial.is_none() {
+                        current.serial = serial;
+                    }
+                }
+                None => self.enter(tool, serial),
+            }
+        }
+        let current = match self.tool {
+            Some(current) => current.tool,
+            None => {
+                self.axes_changed = false;
+                return;
+            }
+        };
+
+        if std::mem::take(&mut self.axes_changed) {
+            let axes = self.axes(current);
+            self.events.push(axes);
+        }
+        let tip_down = self.keys.contains(KeyCode::BTN_TOUCH);
+        if tip_down && !self.tip_down {
+            self.tip_down = true;
+            self.events.push(ToolEvent::Tip {
+                tool: current,
+                down: true,
+            });
+        }
+        let buttons = self.held_buttons();
+        self.release_buttons(current, &buttons);
+        for button in buttons.iter() {
+            if !self.buttons.contains(button) {
+                self.events.push(ToolEvent::Button {
+                    tool: current,
+                    button,
+                    pressed: true,
+                });
+            }
+        }
+        self.buttons = buttons;
+        if !tip_down && self.tip_down {
+            self.tip_down = false;
+            self.events.push(ToolEvent::Tip {
+                tool: current,
+                down: false,
+            });
+        }
+    }
+
+    /// Brings `tool` into proximity and reports where it is.
+    fn enter(&mut self, tool: ToolType, serial: Option<u32>) {
+        let tool_id = match self.value(AbsoluteAxisCode::ABS_MISC) {
+            Some(0) | None => None,
+            id => id,
+        };
+        self.tool = Some(Tool { tool, serial });
+        self.tip_down = false;
+        self.buttons = AttributeSet::new();
+        self.events.push(ToolEvent::ProximityIn {
+            tool,
+            serial,
+            tool_id,
+        });
+        self.axes_changed = true;
+    }
+
+    /// Lifts the tip of `tool`, releases its buttons, and takes it out of proximity.
+    fn leave(&mut self, tool: Tool) {
+        if self.tip_down {
+            self.tip_down = false;
+            self.events.push(ToolEvent::Tip {
+                tool: tool.tool,
+                down: false,
+            });
+        }
+        self.release_buttons(tool.tool, &AttributeSet::new());
+        self.buttons = AttributeSet::new();
+        self.events.push(ToolEvent::ProximityOut {
+            tool: tool.tool,
+            serial: tool.serial,
+        });
+        self.tool = None;
+    }
+
+    /// Reports the buttons of `tool` that are pressed but not in `held` as released.
+    fn release_buttons(&mut self, tool: ToolType, held: &AttributeSetRef<KeyCode>) {
+        for button in self.buttons.iter() {
+            if !held.contains(button) {
+                self.events.push(ToolEvent::Button {
+                    tool,
+                    button,
+                    pressed: false,
+                });
+            }
+        }
+    }
+
+    /// Returns the first tool whose key is held.
+    fn held_tool(&self) -> Option<ToolType> {
+        TOOL_KEYS
+            .iter()
+            .find(|(key, _)| self.keys.contains(*key))
+            .map(|&(_, tool)| tool)
+    }
+
+    /// Returns the held keys that are buttons of the tool, rather than the tip or a tool.
+    fn held_buttons(&self) -> AttributeSet<KeyCode> {
+        self.keys
+            .iter()
+            .filter(|&key| key != KeyCode::BTN_TOUCH && TOOL_KEYS.iter().all(|(k, _)| *k != key))
+            .collect()
+    }
+
+    fn axes(&self, tool: ToolType) -> ToolEvent {
+        let normalize = |normalizer: &Option<AxisNormalizer>, code| {
+            let normalizer = normalizer.as_ref()?;
+            Some(normalizer.normalize(self.value(code)?))
+        };
+        ToolEvent::Axis {
+            tool,
+            x: self.value(AbsoluteAxisCode::ABS_X).unwrap_or(0),
+            y: self.value(AbsoluteAxisCode::ABS_Y).unwrap_or(0),
+            pressure: normalize(&self.pressure, AbsoluteAxisCode::ABS_PRESSURE),
+            distance: normalize(&self.distance, AbsoluteAxisCode::ABS_DISTANCE),
+            tilt_x: self.tilt(AbsoluteAxisCode::ABS_TILT_X),
+            tilt_y: self.tilt(AbsoluteAxisCode::ABS_TILT_Y),
+        }
+    }
+
+    fn value(&self, axis: AbsoluteAxisCode) -> Option<i32> {
+        self.axes.get(&axis).map(|&(_, value)| value)
+    }
+
+    /// Returns the tilt of `axis` in degrees, the way libinput computes it.
+    fn tilt(&self, axis: AbsoluteAxisCode) -> Option<f32> {
+        let &(info, value) = self.axes.get(&axis)?;
+        if info.resolution() > 0 && info.minimum() < 0 && info.maximum() > 0 {
+            return Some(value as f32 / info.resolution() as f32 * 180.0 / PI);
+        }
+        let range = (info.maximum() as f64 - info.minimum() as f64) as f32;
+        if range <= 0.0 {
+            return Some(0.0);
+        }
+        let fraction = (value as f64 - info.minimum() as f64) as f32 / range;
+        Some((fraction * 2.0 - 1.0) * ASSUMED_MAX_TILT)
+    }
+}
@@ -0,0 +1,112 @@
+use crate::axis::{
+    AxisNormalizer, AxisRange, Deadzone, ResponseCurve, ResponseTable, StickNormalizer,
+};
+use crate::AbsInfo;
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-5,
+        "{actual} is not close to {expected}"
+    );
+}
+
+#[test]
+pub fn test_normalize_range() -> std::io::Result<()> {
+    let axis = AxisNormalizer::new(AbsInfo::new(0, -100, 100, 0, 0, 0));
+    assert_close(axis.normalize(0), 0.0);
+    assert_close(axis.normalize(50), 0.5);
+    assert_close(axis.normalize(-100), -1.0);
+    assert_close(axis.normalize(-300), -1.0);
+
+    let trigger = AxisNormalizer::new(AbsInfo::new(0, 0, 255, 0, 0, 0)).range(AxisRange::Unipolar);
+    assert_close(trigger.normalize(0), 0.0);
+    assert_close(trigger.normalize(51), 0.2);
+    assert_close(trigger.normalize(255), 1.0);
+
+    // an axis without a range is always centered
+    let axis = AxisNormalizer::new(AbsInfo::new(0, 5, 5, 0, 0, 0));
+    assert_close(axis.normalize(5), 0.0);
+    Ok(())
+}
+
+#[test]
+pub fn test_deadzone() -> std::io::Result<()> {
+    // the flat of the axis is a deadzone of 0.1
+    let axis = AxisNormalizer::new(AbsInfo::new(0, 0, 200, 0, 10, 0));
+    assert_close(axis.effective_deadzone(), 0.1);
+    assert_close(axis.normalize(109), 0.0);
+    assert_close(axis.normalize(155), 0.5);
+    assert_close(axis.normalize(200), 1.0);
+
+    // the larger deadzone applies
+    let axis = axis.deadzone(0.2);
+    assert_close(axis.effective_deadzone(), 0.2);
+    assert_close(axis.normalize(115), 0.0);
+    assert_close(axis.normalize(40), -0.5);
+    assert_close(axis.clone().deadzone(0.05).effective_deadzone(), 0.1);
+
+    let trigger = AxisNormalizer::new(AbsInfo::new(0, 0, 100, 0, 0, 0))
+        .range(AxisRange::Unipolar)
+        .deadzone(0.5);
+    assert_close(trigger.normalize(40), 0.0);
+    assert_close(trigger.normalize(75), 0.5);
+    Ok(())
+}
+
+#[test]
+pub fn test_response_curves() -> std::io::Result<()> {
+    let info = AbsInfo::new(0, -100, 100, 0, 0, 0);
+    let axis = AxisNormalizer::new(info).curve(ResponseCurve::Exponential(2.0));
+    assert_close(axis.normalize(50), 0.25);
+    assert_close(axis.normalize(-50), -0.25);
+    assert_close(axis.normalize(100), 1.0);
+
+    let table = ResponseTable::new(vec![0.1, 0.5, 1.0])?;
+    let axis = AxisNormalizer::new(info).curve(ResponseCurve::Table(table));
+    assert_close(axis.normalize(25), 0.3);
+    assert_close(axis.normalize(-75), -0.75);
+    // the center stays at 0
+    assert_close(axis.normalize(0), 0.0);
+
+    assert!(ResponseTable::new(vec![0.5]).is_err());
+    assert!(ResponseTable::new(vec![0.0, 1.5]).is_err());
+    assert!(ResponseTable::new(vec![0.0, f32::NAN]).is_err());
+    Ok(())
+}
+
+#[test]
+pub fn test_stick() -> std::io::Result<()> {
+    let info = AbsInfo::new(0, -100, 100, 0, 0, 0);
+    let stick = StickNormalizer::new(AxisNormalizer::new(info), AxisNormalizer::new(info))
+        .deadzone(Deadzone::Radial(0.2));
+    // a diagonal inside the square of an axial deadzone moves the stick
+    let (x, y) = stick.normalize(30, 30);
+    assert!(x > 0.0 && y > 0.0);
+    assert_close(x, y);
+    assert_eq!(stick.normalize(10, -10), (0.0, 0.0));
+    // the distance is scaled past the deadzone, keeping the direction
+    let (x, y) = stick.normalize(0, -60);
+    assert_close(x, 0.0);
+    assert_close(y, -0.5);
+    // corners are within the unit circle
+    let (x, y) = stick.normalize(100, 100);
+    assert_close(x.hypot(y), 1.0);
+
+    let stick = stick.deadzone(Deadzone::Axial(0.2));
+    assert_eq!(stick.normalize(15, 15), (0.0, 0.0));
+    let (x, y) = stick.normalize(60, 10);
+    assert_close(x, 0.5);
+    assert_close(y, 0.0);
+    Ok(())
+}
+
+#[test]
+pub fn test_physical_units() -> std::io::Result<()> {
+    let axis = AxisNormalizer::new(AbsInfo::new(0, 0, 3000, 0, 0, 30));
+    assert_eq!(axis.physical(1500), Some(50.0));
+    assert_eq!(axis.physical_size(), Some(100.0));
+    let axis = AxisNormalizer::new(AbsInfo::new(0, 0, 3000, 0, 0, 0));
+    assert_eq!(axis.physical(1500), None);
+    assert_eq!(axis.physical_size(), None);
+    Ok(())
+}
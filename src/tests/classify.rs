@@ -0,0 +1,259 @@
+use crate::classify::{classify, Capabilities};
+use crate::{
+    AbsoluteAxisCode, AttributeSet, BusType, DeviceClasses, EventType, KeyCode, PropType,
+    RelativeAxisCode,
+};
+
+/// The capabilities of a fake device, owned so that they can be borrowed as `Capabilities`.
+#[derive(Default)]
+struct Caps {
+    keys: Vec<KeyCode>,
+    relative_axes: Vec<RelativeAxisCode>,
+    absolute_axes: Vec<AbsoluteAxisCode>,
+    properties: Vec<PropType>,
+    switches: bool,
+    bus_type: Option<BusType>,
+}
+
+impl Caps {
+    fn classify(&self) -> DeviceClasses {
+        let mut events = AttributeSet::<EventType>::new();
+        events.insert(EventType::SYNCHRONIZATION);
+        let keys: AttributeSet<_> = self.keys.iter().copied().collect();
+        let relative_axes: AttributeSet<_> = self.relative_axes.iter().copied().collect();
+        let absolute_axes: AttributeSet<_> = self.absolute_axes.iter().copied().collect();
+        let properties: AttributeSet<_> = self.properties.iter().copied().collect();
+        if !self.keys.is_empty() {
+            events.insert(EventType::KEY);
+        }
+        if !self.relative_axes.is_empty() {
+            events.insert(EventType::RELATIVE);
+        }
+        if !self.absolute_axes.is_empty() {
+            events.insert(EventType::ABSOLUTE);
+        }
+        if self.switches {
+            events.insert(EventType::SWITCH);
+        }
+        classify(&Capabilities {
+            events: &events,
+            keys: Some(&keys),
+            relative_axes: Some(&relative_axes),
+            absolute_axes: Some(&absolute_axes),
+            properties: &properties,
+            bus_type: self.bus_type.unwrap_or(BusType::BUS_USB),
+        })
+    }
+}
+
+#[test]
+pub fn test_classify_keyboard() -> std::io::Result<()> {
+    let caps = Caps {
+        keys: (1..=KeyCode::KEY_DELETE.0).map(KeyCode).collect(),
+        ..Default::default()
+    };
+    assert_eq!(
+        DeviceClasses::KEY | DeviceClasses::KEYBOARD,
+        caps.classify()
+    );
+
+    let power_button = Caps {
+        keys: vec![KeyCode::KEY_POWER],
+        switches: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        DeviceClasses::KEY | DeviceClasses::SWITCH,
+        power_button.classify()
+    );
+
+    // buttons alone don't make a device with keys
+    let buttons = Caps {
+        keys: vec![KeyCode::BTN_0],
+        ..Default::default()
+    };
+    assert_eq!(DeviceClasses::empty(), buttons.classify());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_classify_mouse() -> std::io::Result<()> {
+    let mut mouse = Caps {
+        keys: vec![KeyCode::BTN_LEFT, KeyCode::BTN_RIGHT, KeyCode::BTN_MIDDLE],
+        relative_axes: vec![
+            RelativeAxisCode::REL_X,
+            RelativeAxisCode::REL_Y,
+            RelativeAxisCode::REL_WHEEL,
+        ],
+        ..Default::default()
+    };
+    assert_eq!(DeviceClasses::MOUSE, mouse.classify());
+
+    mouse.bus_type = Some(BusType::BUS_I2C);
+    assert_eq!(
+        DeviceClasses::MOUSE | DeviceClasses::POINTING_STICK,
+        mouse.classify()
+    );
+
+    // the tablet emulation of virtual machines
+    let abs_mouse = Caps {
+        keys: vec![KeyCode::BTN_LEFT],
+        absolute_axes: vec![AbsoluteAxisCode::ABS_X, AbsoluteAxisCode::ABS_Y],
+        ..Default::default()
+    };
+    assert_eq!(DeviceClasses::MOUSE, abs_mouse.classify());
+
+    // any of the mouse buttons will do
+    let no_left_button = Caps {
+        keys: vec![KeyCode::BTN_RIGHT, KeyCode::BTN_SIDE],
+        relative_axes: vec![RelativeAxisCode::REL_X, RelativeAxisCode::REL_Y],
+        ..Default::default()
+    };
+    assert_eq!(DeviceClasses::MOUSE, no_left_button.classify());
+
+    let wheel = Caps {
+        relative_axes: vec![RelativeAxisCode::REL_WHEEL],
+        ..Default::default()
+    };
+    assert_eq!(DeviceClasses::KEY, wheel.classify());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_classify_touch() -> std::io::Result<()> {
+    let axes = vec![
+        AbsoluteAxisCode::ABS_X,
+        AbsoluteAxisCode::ABS_Y,
+        AbsoluteAxisCode::ABS_MT_SLOT,
+        AbsoluteAxisCode::ABS_MT_POSITION_X,
+        AbsoluteAxisCode::ABS_MT_POSITION_Y,
+        AbsoluteAxisCode::ABS_MT_TRACKING_ID,
+    ];
+
+    let touchpad = Caps {
+        keys: vec![
+            KeyCode::BTN_LEFT,
+            KeyCode::BTN_TOOL_FINGER,
+            KeyCode::BTN_TOUCH,
+        ],
+        absolute_axes: axes.clone(),
+        properties: vec![PropType::POINTER, PropType::BUTTONPAD],
+        ..Default::default()
+    };
+    assert_eq!(DeviceClasses::TOUCHPAD, touchpad.classify());
+
+    let touchscreen = Caps {
+        keys: vec![KeyCode::BTN_TOUCH],
+        absolute_axes: axes.clone(),
+        properties: vec![PropType::DIRECT],
+        ..Default::default()
+    };
+    assert_eq!(DeviceClasses::TOUCHSCREEN, touchscreen.classify());
+
+    // a direct device with fingers is still a touchscreen
+    let touchscreen = Caps {
+        keys: vec![KeyCode::BTN_TOOL_FINGER, KeyCode::BTN_TOUCH],
+        absolute_axes: axes,
+        properties: vec![PropType::DIRECT],
+        ..Default::default()
+    };
+    assert_eq!(DeviceClasses::TOUCHSCREEN, touchscreen.classify());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_classify_tablet() -> std::io::Result<()> {
+    let tablet = Caps {
+        keys: vec![
+            KeyCode::BTN_TOOL_PEN,
+            KeyCode::BTN_TOUCH,
+            KeyCode::BTN_STYLUS,
+        ],
+        absolute_axes: vec![
+            AbsoluteAxisCode::ABS_X,
+            AbsoluteAxisCode::ABS_Y,
+            AbsoluteAxisCode::ABS_PRESSURE,
+        ],
+        ..Default::default()
+    };
+    assert_eq!(DeviceClasses::TABLET, tablet.classify());
+
+    let pad = Caps {
+        keys: vec![KeyCode::BTN_0, KeyCode::BTN_1, KeyCode::BTN_2],
+        relative_axes: vec![RelativeAxisCode::REL_WHEEL],
+        ..Default::default()
+    };
+    assert_eq!(
+        DeviceClasses::TABLET | DeviceClasses::TABLET_PAD,
+        pad.classify()
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_classify_joystick() -> std::io::Result<()> {
+    let gamepad = Caps {
+        keys: vec![
+            KeyCode::BTN_SOUTH,
+            KeyCode::BTN_EAST,
+            KeyCode::BTN_START,
+            KeyCode::BTN_SELECT,
+        ],
+        absolute_axes: vec![
+            AbsoluteAxisCode::ABS_X,
+            AbsoluteAxisCode::ABS_Y,
+            AbsoluteAxisCode::ABS_RX,
+            AbsoluteAxisCode::ABS_RY,
+            AbsoluteAxisCode::ABS_HAT0X,
+            AbsoluteAxisCode::ABS_HAT0Y,
+        ],
+        ..Default::default()
+    };
+    assert_eq!(DeviceClasses::JOYSTICK, gamepad.classify());
+
+    // a keyboard with a stray joystick button
+    let mut keys: Vec<_> = (1..=KeyCode::KEY_DELETE.0).map(KeyCode).collect();
+    keys.push(KeyCode::BTN_TRIGGER);
+    keys.push(KeyCode::BTN_THUMB);
+    let keyboard = Caps {
+        keys,
+        ..Default::default()
+    };
+    assert_eq!(
+        DeviceClasses::KEY | DeviceClasses::KEYBOARD,
+        keyboard.classify()
+    );
+
+    let accelerometer = Caps {
+        absolute_axes: vec![
+            AbsoluteAxisCode::ABS_X,
+            AbsoluteAxisCode::ABS_Y,
+            AbsoluteAxisCode::ABS_Z,
+        ],
+        ..Default::default()
+    };
+    assert_eq!(DeviceClasses::ACCELEROMETER, accelerometer.classify());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_device_classes() -> std::io::Result<()> {
+    let mut classes = DeviceClasses::MOUSE | DeviceClasses::TOUCHPAD;
+    classes.insert(DeviceClasses::KEY);
+    classes.remove(DeviceClasses::MOUSE);
+    assert_eq!(
+        classes.iter().collect::<Vec<_>>(),
+        [DeviceClasses::KEY, DeviceClasses::TOUCHPAD]
+    );
+    assert_eq!("KEY | TOUCHPAD", format!("{:?}", classes));
+    assert_eq!("(empty)", format!("{:?}", DeviceClasses::empty()));
+    assert_eq!(11, DeviceClasses::all().iter().count());
+    assert_eq!(DeviceClasses::empty(), classes - DeviceClasses::all());
+
+    Ok(())
+}
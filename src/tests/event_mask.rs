@@ -0,0 +1,47 @@
+use crate::{AttributeSet, EventMask, EventType, KeyCode, SwitchCode};
+use std::collections::HashMap;
+
+#[test]
+pub fn test_raw_round_trip() -> std::io::Result<()> {
+    let keys: AttributeSet<KeyCode> = [KeyCode::KEY_A, KeyCode::BTN_LEFT].into_iter().collect();
+    let mask = EventMask::none()
+        .with_keys(&keys)
+        .with_type(EventType::SWITCH);
+
+    let mut raw = HashMap::new();
+    mask.for_each_raw(|type_, codes| {
+        raw.insert(type_, codes.to_vec());
+        Ok(())
+    })?;
+
+    let read_back = EventMask::from_raw(|type_, codes| {
+        codes.copy_from_slice(&raw[&type_]);
+        Ok(())
+    })?;
+
+    assert!(read_back.types().contains(EventType::KEY));
+    assert!(read_back.types().contains(EventType::SWITCH));
+    assert!(!read_back.types().contains(EventType::ABSOLUTE));
+    assert_eq!(
+        read_back.keys().unwrap().iter().collect::<Vec<_>>(),
+        [KeyCode::KEY_A, KeyCode::BTN_LEFT]
+    );
+    assert!(read_back.switches().is_none());
+    assert!(read_back.allows(EventType::SWITCH, SwitchCode::SW_LID.0));
+    assert!(!read_back.allows(EventType::KEY, KeyCode::KEY_B.0));
+    assert!(read_back.allows(EventType::SYNCHRONIZATION, 0));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_with_type_resets_codes() {
+    let keys: AttributeSet<KeyCode> = [KeyCode::KEY_A].into_iter().collect();
+    let mask = EventMask::none().with_keys(&keys).with_type(EventType::KEY);
+
+    assert!(mask.keys().is_none());
+    assert!(mask.allows(EventType::KEY, KeyCode::KEY_B.0));
+
+    let mask = mask.without_type(EventType::KEY);
+    assert!(!mask.allows(EventType::KEY, KeyCode::KEY_A.0));
+}
@@ -0,0 +1,45 @@
+//! Events and frames shared by the tests of the modules that process frames.
+
+use crate::{AbsoluteAxisCode, EventType, Frame, InputEvent, KeyCode};
+
+pub(super) fn key(key: KeyCode, value: i32) -> InputEvent {
+    InputEvent::new(EventType::KEY.0, key.0, value)
+}
+
+pub(super) fn abs(axis: AbsoluteAxisCode, value: i32) -> InputEvent {
+    InputEvent::new(EventType::ABSOLUTE.0, axis.0, value)
+}
+
+/// A frame of `events`, for tests that don't care about its time.
+pub(super) fn untimed_frame(events: &[InputEvent]) -> Frame<'_> {
+    let time = libc::timeval {
+        tv_sec: 0,
+        tv_usec: 0,
+    };
+    Frame::new(events, time, false)
+}
+
+/// A contact of a Type B multi-touch device put down in `slot`, with a tracking ID derived from the slot.
+pub(super) fn down(slot: i32, x: i32, y: i32) -> [InputEvent; 4] {
+    [
+        abs(AbsoluteAxisCode::ABS_MT_SLOT, slot),
+        abs(AbsoluteAxisCode::ABS_MT_TRACKING_ID, slot + 100),
+        abs(AbsoluteAxisCode::ABS_MT_POSITION_X, x),
+        abs(AbsoluteAxisCode::ABS_MT_POSITION_Y, y),
+    ]
+}
+
+pub(super) fn move_to(slot: i32, x: i32, y: i32) -> [InputEvent; 3] {
+    [
+        abs(AbsoluteAxisCode::ABS_MT_SLOT, slot),
+        abs(AbsoluteAxisCode::ABS_MT_POSITION_X, x),
+        abs(AbsoluteAxisCode::ABS_MT_POSITION_Y, y),
+    ]
+}
+
+pub(super) fn up(slot: i32) -> [InputEvent; 2] {
+    [
+        abs(AbsoluteAxisCode::ABS_MT_SLOT, slot),
+        abs(AbsoluteAxisCode::ABS_MT_TRACKING_ID, -1),
+    ]
+}
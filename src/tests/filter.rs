@@ -0,0 +1,94 @@
+use crate::classify::Capabilities;
+use crate::filter::{glob_match, DeviceInfo};
+use crate::{
+    AttributeSet, BusType, DeviceClasses, DeviceFilter, EventType, InputId, KeyCode, PropType,
+    RelativeAxisCode,
+};
+
+#[test]
+pub fn test_glob_match() -> std::io::Result<()> {
+    let matches = |pattern: &str, value: &str| glob_match(pattern.as_bytes(), value.as_bytes());
+
+    assert!(matches("", ""));
+    assert!(matches("*", ""));
+    assert!(matches("*", "Logitech USB Receiver"));
+    assert!(matches("Logitech*", "Logitech USB Receiver"));
+    assert!(matches("*USB*", "Logitech USB Receiver"));
+    assert!(matches("*Receiver", "Logitech USB Receiver"));
+    assert!(matches("Logitech?USB*", "Logitech USB Receiver"));
+    assert!(matches("*e*e*e*", "Receiver"));
+    assert!(!matches("Logitech", "Logitech USB Receiver"));
+    assert!(!matches("*Mouse", "Logitech USB Receiver"));
+    assert!(!matches("?", ""));
+    assert!(!matches("a*b", "ab-"));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_filter_matches() -> std::io::Result<()> {
+    let mut events = AttributeSet::<EventType>::new();
+    events.insert(EventType::KEY);
+    events.insert(EventType::RELATIVE);
+    let keys: AttributeSet<_> = [KeyCode::BTN_LEFT, KeyCode::BTN_RIGHT]
+        .into_iter()
+        .collect();
+    let relative_axes: AttributeSet<_> = [
+        RelativeAxisCode::REL_X,
+        RelativeAxisCode::REL_Y,
+        RelativeAxisCode::REL_WHEEL,
+    ]
+    .into_iter()
+    .collect();
+    let properties = AttributeSet::<PropType>::new();
+    let mouse = DeviceInfo {
+        name: Some("Logitech USB Receiver"),
+        phys: Some("usb-0000:00:14.0-2/input0"),
+        uniq: None,
+        input_id: InputId::new(BusType::BUS_USB, 0x046d, 0xc52b, 0x0111),
+        caps: Capabilities {
+            events: &events,
+            keys: Some(&keys),
+            relative_axes: Some(&relative_axes),
+            absolute_axes: None,
+            properties: &properties,
+            bus_type: BusType::BUS_USB,
+        },
+    };
+
+    assert!(DeviceFilter::new().matches_info(&mouse));
+    let filter = DeviceFilter::new()
+        .name("Logitech*")
+        .physical_path("usb-*")
+        .bus_type(BusType::BUS_USB)
+        .vendor(0x046d)
+        .product(0xc52b)
+        .with_event_type(EventType::RELATIVE)
+        .with_key(KeyCode::BTN_LEFT)
+        .with_relative_axis(RelativeAxisCode::REL_WHEEL)
+        .with_class(DeviceClasses::MOUSE);
+    assert!(filter.matches_info(&mouse));
+
+    assert!(!filter.clone().vendor(0x045e).matches_info(&mouse));
+    assert!(!filter.clone().name("*Keyboard").matches_info(&mouse));
+    assert!(filter.clone().unique_name("*").matches_info(&mouse));
+    assert!(!filter.clone().unique_name("?*").matches_info(&mouse));
+    assert!(!filter
+        .clone()
+        .with_event_type(EventType::ABSOLUTE)
+        .matches_info(&mouse));
+    assert!(!filter
+        .clone()
+        .with_relative_axis(RelativeAxisCode::REL_HWHEEL)
+        .matches_info(&mouse));
+    assert!(!filter
+        .clone()
+        .with_absolute_axis(crate::AbsoluteAxisCode::ABS_X)
+        .matches_info(&mouse));
+    assert!(!filter
+        .clone()
+        .with_class(DeviceClasses::KEYBOARD)
+        .matches_info(&mouse));
+
+    Ok(())
+}
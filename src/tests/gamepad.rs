@@ -0,0 +1,198 @@
+use super::events::{abs, key, untimed_frame};
+use crate::gamepad::{
+    Gamepad, GamepadAxis, GamepadButton, GamepadEvent, GamepadGuid, Mapping, MappingDb,
+};
+use crate::{AbsInfo, AbsoluteAxisCode, AttributeSet, BusType, InputEvent, InputId, KeyCode};
+
+/// A joystick with four buttons, two sticks and a hat, and the mapping of an Xbox controller.
+const MAPPING: &str = "030000005e0400008e02000014010000,Test pad,a:b2,b:b1,\
+    leftx:a0,lefty:a1~,lefttrigger:a2,righttrigger:+a3,+rightx:b3,-rightx:b0,\
+    dpup:h0.1,dpleft:h0.8,platform:Linux,";
+
+fn joystick(mapping: Option<&Mapping>, keys: &[KeyCode]) -> Gamepad {
+    let keys: AttributeSet<KeyCode> = keys.iter().copied().collect();
+    let axes = [
+        (AbsoluteAxisCode::ABS_X, AbsInfo::new(128, 0, 255, 0, 0, 0)),
+        (AbsoluteAxisCode::ABS_Y, AbsInfo::new(128, 0, 255, 0, 0, 0)),
+        (AbsoluteAxisCode::ABS_Z, AbsInfo::new(0, 0, 255, 0, 0, 0)),
+        (
+            AbsoluteAxisCode::ABS_RZ,
+            AbsInfo::new(0, -100, 100, 0, 0, 0),
+        ),
+        (AbsoluteAxisCode::ABS_HAT0X, AbsInfo::new(0, -1, 1, 0, 0, 0)),
+        (AbsoluteAxisCode::ABS_HAT0Y, AbsInfo::new(0, -1, 1, 0, 0, 0)),
+    ];
+    Gamepad::from_state(&keys, &axes, mapping)
+}
+
+fn process(gamepad: &mut Gamepad, events: &[InputEvent]) -> Vec<GamepadEvent> {
+    gamepad.process_frame(untimed_frame(events)).collect()
+}
+
+fn pressed(button: GamepadButton, pressed: bool) -> GamepadEvent {
+    GamepadEvent::Button { button, pressed }
+}
+
+fn moved(axis: GamepadAxis, value: f32) -> GamepadEvent {
+    GamepadEvent::Axis { axis, value }
+}
+
+#[test]
+pub fn test_guid() -> std::io::Result<()> {
+    let id = InputId::new(BusType::BUS_USB, 0x045e, 0x028e, 0x0114);
+    let guid = GamepadGuid::from_input_id(id);
+    assert_eq!(guid.to_string(), "030000005e0400008e02000014010000");
+    assert_eq!(guid.to_string().parse::<GamepadGuid>()?, guid);
+    assert!("030000005e0400008e020000".parse::<GamepadGuid>().is_err());
+    assert!("030000005e0400008e0200001401000g"
+        .parse::<GamepadGuid>()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+pub fn test_mapping_db() -> std::io::Result<()> {
+    let text = format!(
+        "# Linux\n\n{MAPPING}\n\
+         030000005e0400008e02000014010000,Windows pad,a:b0,platform:Windows,\n\
+         05000000aabb0000ccdd000000000000,Other pad,a:b0,hint:SDL_HINT:=1,\n\
+         xinput,XInput Controller,a:b0,platform:Windows,\n"
+    );
+    let db = MappingDb::parse(&text);
+    assert_eq!(db.len(), 2);
+    assert!(db.warnings().is_empty());
+
+    let guid: GamepadGuid = "030000005e0400008e02000014010000".parse()?;
+    let mapping = db.find(guid).unwrap();
+    assert_eq!(mapping.name(), "Test pad");
+    assert_eq!(mapping.platform(), Some("Linux"));
+    // the version doesn't have to match
+    let other_version: GamepadGuid = "030000005e0400008e02000010010000".parse()?;
+    assert_eq!(db.find(other_version).unwrap().name(), "Test pad");
+    let other_product: GamepadGuid = "030000005e0400008f02000014010000".parse()?;
+    assert!(db.find(other_product).is_none());
+
+    // invalid lines don't spoil the others
+    let db = MappingDb::parse(&format!(
+        "03000000aabb0000ccdd000000000000,Bad,a:z0\n{MAPPING}\n"
+    ));
+    assert_eq!(db.len(), 1);
+    assert_eq!(db.warnings(), ["line 1: `z0` is not a button, axis or hat"]);
+    assert!(Mapping::parse("not a guid,Pad,a:b0").is_err());
+    Ok(())
+}
+
+#[test]
+pub fn test_gamepad_mapping() -> std::io::Result<()> {
+    // the joystick buttons are numbered before the other keys
+    let keys = [
+        KeyCode::KEY_VOLUMEUP,
+        KeyCode::BTN_TRIGGER,
+        KeyCode::BTN_THUMB,
+        KeyCode::BTN_BASE,
+    ];
+    let mapping = Mapping::parse(MAPPING)?;
+    let mut gamepad = joystick(Some(&mapping), &keys);
+    assert_eq!(gamepad.name(), "Test pad");
+    assert!(gamepad.has_button(GamepadButton::South));
+    assert!(!gamepad.has_button(GamepadButton::North));
+    assert!(gamepad.has_axis(GamepadAxis::RightX));
+    assert_eq!(gamepad.axis(GamepadAxis::LeftTrigger), 0.0);
+
+    let events = process(
+        &mut gamepad,
+        &[key(KeyCode::BTN_BASE, 1), key(KeyCode::BTN_THUMB, 1)],
+    );
+    assert_eq!(
+        events,
+        [
+            pressed(GamepadButton::South, true),
+            pressed(GamepadButton::East, true)
+        ]
+    );
+    assert!(gamepad.button(GamepadButton::South));
+
+    // an inverted axis, and a full axis as a trigger
+    let events = process(
+        &mut gamepad,
+        &[
+            abs(AbsoluteAxisCode::ABS_Y, 0),
+            abs(AbsoluteAxisCode::ABS_Z, 255),
+        ],
+    );
+    assert_eq!(
+        events,
+        [
+            moved(GamepadAxis::LeftY, 1.0),
+            moved(GamepadAxis::LeftTrigger, 1.0)
+        ]
+    );
+
+    // a half axis as a trigger, ignoring the other half
+    let events = process(&mut gamepad, &[abs(AbsoluteAxisCode::ABS_RZ, -50)]);
+    assert!(events.is_empty());
+    let events = process(&mut gamepad, &[abs(AbsoluteAxisCode::ABS_RZ, 50)]);
+    assert_eq!(events, [moved(GamepadAxis::RightTrigger, 0.5)]);
+
+    // buttons as the halves of an axis
+    let events = process(&mut gamepad, &[key(KeyCode::KEY_VOLUMEUP, 1)]);
+    assert_eq!(events, [moved(GamepadAxis::RightX, 1.0)]);
+    let events = process(&mut gamepad, &[key(KeyCode::BTN_TRIGGER, 1)]);
+    assert_eq!(events, [moved(GamepadAxis::RightX, 0.0)]);
+
+    // a hat as the d-pad
+    let events = process(
+        &mut gamepad,
+        &[
+            abs(AbsoluteAxisCode::ABS_HAT0X, -1),
+            abs(AbsoluteAxisCode::ABS_HAT0Y, -1),
+        ],
+    );
+    assert_eq!(
+        events,
+        [
+            pressed(GamepadButton::DPadUp, true),
+            pressed(GamepadButton::DPadLeft, true)
+        ]
+    );
+    let events = process(&mut gamepad, &[abs(AbsoluteAxisCode::ABS_HAT0Y, 0)]);
+    assert_eq!(events, [pressed(GamepadButton::DPadUp, false)]);
+    Ok(())
+}
+
+#[test]
+pub fn test_kernel_layout() -> std::io::Result<()> {
+    let keys = [
+        KeyCode::BTN_SOUTH,
+        KeyCode::BTN_EAST,
+        KeyCode::BTN_TL2,
+        KeyCode::BTN_START,
+    ];
+    let mut gamepad = joystick(None, &keys).deadzone(0.2);
+    assert!(gamepad.has_button(GamepadButton::DPadDown));
+    assert!(!gamepad.has_axis(GamepadAxis::RightY));
+
+    let events = process(
+        &mut gamepad,
+        &[
+            key(KeyCode::BTN_START, 1),
+            key(KeyCode::BTN_TL2, 1),
+            abs(AbsoluteAxisCode::ABS_HAT0Y, 1),
+        ],
+    );
+    assert_eq!(
+        events,
+        [
+            pressed(GamepadButton::Start, true),
+            pressed(GamepadButton::DPadDown, true),
+            moved(GamepadAxis::LeftTrigger, 1.0)
+        ]
+    );
+
+    // the left stick has a radial deadzone
+    let events = process(&mut gamepad, &[abs(AbsoluteAxisCode::ABS_X, 140)]);
+    assert!(events.is_empty());
+    process(&mut gamepad, &[abs(AbsoluteAxisCode::ABS_X, 255)]);
+    assert!(gamepad.axis(GamepadAxis::LeftX) > 0.999);
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use super::events::{down, key, move_to, up};
+use crate::gestures::{GestureConfig, GestureEvent, GestureRecognizer};
+use crate::{AbsInfo, Frame, InputEvent, KeyCode};
+use std::time::Duration;
+
+use GestureEvent::*;
+
+/// A 100 x 100 mm touchpad with 10 units per mm and two slots.
+fn touchpad() -> GestureRecognizer {
+    let axis = AbsInfo::new(0, 0, 1000, 0, 0, 10);
+    GestureRecognizer::from_axes(axis, axis, 2, GestureConfig::default())
+}
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn frame(
+    recognizer: &mut GestureRecognizer,
+    time: Duration,
+    events: &[InputEvent],
+) -> Vec<GestureEvent> {
+    let time = libc::timeval {
+        tv_sec: time.as_secs() as libc::time_t,
+        tv_usec: time.subsec_micros() as libc::suseconds_t,
+    };
+    recognizer
+        .process_frame(Frame::new(events, time, false))
+        .collect()
+}
+
+#[test]
+pub fn test_gestures_tap() -> std::io::Result<()> {
+    let mut pad = touchpad();
+
+    assert_eq!(frame(&mut pad, ms(0), &down(0, 500, 500)), []);
+    assert_eq!(frame(&mut pad, ms(50), &up(0)), [Tap { fingers: 1 }]);
+    assert_eq!(frame(&mut pad, ms(150), &down(0, 505, 500)), []);
+    assert_eq!(
+        frame(&mut pad, ms(200), &up(0)),
+        [Tap { fingers: 1 }, DoubleTap { fingers: 1 }]
+    );
+    // a third tap starts over
+    assert_eq!(frame(&mut pad, ms(300), &down(0, 500, 500)), []);
+    assert_eq!(frame(&mut pad, ms(350), &up(0)), [Tap { fingers: 1 }]);
+
+    // too long for a tap
+    assert_eq!(frame(&mut pad, ms(1000), &down(0, 500, 500)), []);
+    assert_eq!(
+        frame(&mut pad, ms(1500), &up(0)),
+        [
+            HoldBegin { fingers: 1 },
+            HoldEnd {
+                fingers: 1,
+                cancelled: false
+            }
+        ]
+    );
+
+    // moving the pointer isn't a gesture
+    assert_eq!(frame(&mut pad, ms(2000), &down(0, 500, 500)), []);
+    assert_eq!(frame(&mut pad, ms(2020), &move_to(0, 600, 500)), []);
+    assert_eq!(frame(&mut pad, ms(2040), &up(0)), []);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_gestures_tool_keys() -> std::io::Result<()> {
+    let mut pad = touchpad();
+
+    // three fingers, but only two of them are tracked
+    let mut events = vec![key(KeyCode::BTN_TOOL_TRIPLETAP, 1)];
+    events.extend(down(0, 400, 500));
+    events.extend(down(1, 600, 500));
+    assert_eq!(frame(&mut pad, ms(0), &events), []);
+    assert_eq!(3, pad.fingers());
+
+    let mut events = vec![key(KeyCode::BTN_TOOL_TRIPLETAP, 0)];
+    events.extend(up(0));
+    events.extend(up(1));
+    assert_eq!(frame(&mut pad, ms(100), &events), [Tap { fingers: 3 }]);
+    assert_eq!(0, pad.fingers());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_gestures_swipe() -> std::io::Result<()> {
+    let mut pad = touchpad();
+
+    let mut events = vec![key(KeyCode::BTN_TOOL_DOUBLETAP, 1)];
+    events.extend(down(0, 400, 500));
+    events.extend(down(1, 600, 500));
+    assert_eq!(frame(&mut pad, ms(0), &events), []);
+
+    // below the threshold
+    let mut events = move_to(0, 420, 500).to_vec();
+    events.extend(move_to(1, 620, 500));
+    assert_eq!(frame(&mut pad, ms(10), &events), []);
+
+    let mut events = move_to(0, 450, 500).to_vec();
+    events.extend(move_to(1, 650, 500));
+    assert_eq!(
+        frame(&mut pad, ms(20), &events),
+        [
+            SwipeBegin { fingers: 2 },
+            SwipeUpdate {
+                fingers: 2,
+                dx: 5.0,
+                dy: 0.0
+            }
+        ]
+    );
+
+    let mut events = move_to(0, 450, 480).to_vec();
+    events.extend(move_to(1, 650, 480));
+    assert_eq!(
+        frame(&mut pad, ms(30), &events),
+        [SwipeUpdate {
+            fingers: 2,
+            dx: 0.0,
+            dy: -2.0
+        }]
+    );
+
+    // lifting one of the fingers cancels the swipe
+    let mut events = vec![
+        key(KeyCode::BTN_TOOL_DOUBLETAP, 0),
+        key(KeyCode::BTN_TOOL_FINGER, 1),
+    ];
+    events.extend(up(1));
+    assert_eq!(
+        frame(&mut pad, ms(40), &events),
+        [SwipeEnd {
+            fingers: 2,
+            cancelled: true
+        }]
+    );
+    // and what's left is neither a tap nor a hold
+    assert_eq!(None, pad.deadline());
+    let mut events = vec![key(KeyCode::BTN_TOOL_FINGER, 0)];
+    events.extend(up(0));
+    assert_eq!(frame(&mut pad, ms(50), &events), []);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_gestures_pinch() -> std::io::Result<()> {
+    let mut pad = touchpad();
+
+    let mut events = down(0, 400, 500).to_vec();
+    events.extend(down(1, 600, 500));
+    assert_eq!(frame(&mut pad, ms(0), &events), []);
+
+    // from 10 mm to 20 mm away from the center
+    let mut events = move_to(0, 300, 500).to_vec();
+    events.extend(move_to(1, 700, 500));
+    assert_eq!(
+        frame(&mut pad, ms(10), &events),
+        [
+            PinchBegin { fingers: 2 },
+            PinchUpdate {
+                fingers: 2,
+                scale: 2.0,
+                rotation: 0.0,
+                dx: 0.0,
+                dy: 0.0
+            }
+        ]
+    );
+
+    // a quarter turn clockwise, as the y axis points down
+    let mut events = move_to(0, 500, 300).to_vec();
+    events.extend(move_to(1, 500, 700));
+    assert_eq!(
+        frame(&mut pad, ms(20), &events),
+        [PinchUpdate {
+            fingers: 2,
+            scale: 2.0,
+            rotation: 90.0,
+            dx: 0.0,
+            dy: 0.0
+        }]
+    );
+
+    let mut events = up(0).to_vec();
+    events.extend(up(1));
+    assert_eq!(
+        frame(&mut pad, ms(30), &events),
+        [PinchEnd {
+            fingers: 2,
+            cancelled: false
+        }]
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_gestures_hold() -> std::io::Result<()> {
+    let mut pad = touchpad();
+
+    assert_eq!(None, pad.deadline());
+    let mut events = down(0, 400, 500).to_vec();
+    events.extend(down(1, 600, 500));
+    assert_eq!(frame(&mut pad, ms(1000), &events), []);
+    assert_eq!(Some(ms(1180)), pad.deadline());
+
+    assert_eq!(pad.process_timeout(ms(1100)).count(), 0);
+    assert_eq!(
+        pad.process_timeout(ms(1180)).collect::<Vec<_>>(),
+        [HoldBegin { fingers: 2 }]
+    );
+    assert_eq!(None, pad.deadline());
+
+    // moving ends the hold, and can go on to become a swipe
+    let mut events = move_to(0, 450, 500).to_vec();
+    events.extend(move_to(1, 650, 500));
+    assert_eq!(
+        frame(&mut pad, ms(1200), &events),
+        [HoldEnd {
+            fingers: 2,
+            cancelled: true
+        }]
+    );
+    let mut events = move_to(0, 500, 500).to_vec();
+    events.extend(move_to(1, 700, 500));
+    assert_eq!(
+        frame(&mut pad, ms(1210), &events),
+        [
+            SwipeBegin { fingers: 2 },
+            SwipeUpdate {
+                fingers: 2,
+                dx: 5.0,
+                dy: 0.0
+            }
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use crate::{BusType, DeviceIdentity};
+use std::path::PathBuf;
+
+fn keyboard() -> DeviceIdentity {
+    DeviceIdentity {
+        bus_type: BusType::BUS_USB,
+        vendor: 0x046d,
+        product: 0xc52b,
+        version: 0x0111,
+        name: Some("Logitech USB Receiver".to_owned()),
+        uniq: None,
+        phys: Some("usb-0000:00:14.0-2/input0".to_owned()),
+        parent: Some(PathBuf::from(
+            "/sys/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2:1.0/0003:046D:C52B.0001",
+        )),
+        by_id: Some(PathBuf::from(
+            "/dev/input/by-id/usb-Logitech_USB_Receiver-event-kbd",
+        )),
+        by_path: Some(PathBuf::from(
+            "/dev/input/by-path/pci-0000:00:14.0-usb-0:2:1.0-event-kbd",
+        )),
+    }
+}
+
+#[test]
+pub fn test_identity_eq() -> std::io::Result<()> {
+    let a = keyboard();
+
+    // the version and the location don't matter
+    let mut b = keyboard();
+    b.version = 0x0112;
+    b.phys = None;
+    b.parent = None;
+    b.by_path = None;
+    assert_eq!(a, b);
+
+    // a missing unique name is tolerated on either side
+    b.uniq = Some("1234".to_owned());
+    assert_eq!(a, b);
+    assert_eq!(b, a);
+    let mut c = keyboard();
+    c.uniq = Some("5678".to_owned());
+    assert_ne!(b, c);
+
+    let mut d = keyboard();
+    d.name = Some("Logitech USB Receiver Mouse".to_owned());
+    assert_ne!(a, d);
+    let mut e = keyboard();
+    e.product = 0xc52c;
+    assert_ne!(a, e);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_identity_rank() -> std::io::Result<()> {
+    let mut wanted = keyboard();
+    wanted.uniq = Some("1234".to_owned());
+
+    let same = wanted.clone();
+    let mut other_port = wanted.clone();
+    other_port.phys = Some("usb-0000:00:14.0-3/input0".to_owned());
+    other_port.parent = None;
+    other_port.by_path = None;
+    let mut without_uniq = wanted.clone();
+    without_uniq.uniq = None;
+    let mut without_links = without_uniq.clone();
+    without_links.by_id = None;
+    without_links.by_path = None;
+
+    assert!(wanted.rank(&same) > wanted.rank(&other_port));
+    assert!(wanted.rank(&other_port) > wanted.rank(&without_uniq));
+    assert!(wanted.rank(&without_uniq) > wanted.rank(&without_links));
+    // only the location is left in common
+    assert_eq!(
+        [false, false, false, true, true],
+        wanted.rank(&without_links)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use crate::keymap::{Keymap, Keysym};
+use crate::{KeyCode, ModifierState};
+
+fn with(keys: &[KeyCode]) -> ModifierState {
+    let mut modifiers = ModifierState::new();
+    for &key in keys {
+        modifiers.set_held(key, true);
+    }
+    modifiers
+}
+
+#[test]
+pub fn test_builtin_keymaps() -> std::io::Result<()> {
+    for name in Keymap::builtin_names() {
+        assert!(Keymap::builtin(name).is_some(), "{name}");
+    }
+    assert!(Keymap::builtin("xx").is_none());
+
+    let none = ModifierState::new();
+    let shift = with(&[KeyCode::KEY_RIGHTSHIFT]);
+    let altgr = with(&[KeyCode::KEY_RIGHTALT]);
+
+    let mut us = Keymap::builtin("us").unwrap();
+    assert_eq!(us.translate(&none, KeyCode::KEY_A), Some('a'));
+    assert_eq!(us.translate(&shift, KeyCode::KEY_A), Some('A'));
+    assert_eq!(us.translate(&shift, KeyCode::KEY_1), Some('!'));
+    assert_eq!(us.translate(&none, KeyCode::KEY_ENTER), Some('\n'));
+    assert_eq!(us.translate(&none, KeyCode::KEY_SPACE), Some(' '));
+    // there is no AltGr level, and Ctrl doesn't change the character
+    assert_eq!(us.translate(&altgr, KeyCode::KEY_Q), Some('q'));
+    let ctrl = with(&[KeyCode::KEY_LEFTCTRL]);
+    assert_eq!(us.translate(&ctrl, KeyCode::KEY_C), Some('c'));
+    assert_eq!(us.translate(&none, KeyCode::KEY_LEFTSHIFT), None);
+    assert_eq!(us.translate(&none, KeyCode::KEY_F1), None);
+
+    let mut de = Keymap::builtin("de").unwrap();
+    assert_eq!(de.translate(&none, KeyCode::KEY_Y), Some('z'));
+    assert_eq!(de.translate(&none, KeyCode::KEY_Z), Some('y'));
+    assert_eq!(de.translate(&shift, KeyCode::KEY_SEMICOLON), Some('Ö'));
+    assert_eq!(de.translate(&altgr, KeyCode::KEY_Q), Some('@'));
+    assert_eq!(de.translate(&altgr, KeyCode::KEY_E), Some('€'));
+    assert_eq!(de.translate(&altgr, KeyCode::KEY_1), Some('1'));
+    assert_eq!(
+        de.lookup(&none, KeyCode::KEY_GRAVE),
+        Some(Keysym::Dead('^'))
+    );
+
+    let mut fr = Keymap::builtin("fr").unwrap();
+    assert_eq!(fr.translate(&none, KeyCode::KEY_Q), Some('a'));
+    assert_eq!(fr.translate(&none, KeyCode::KEY_2), Some('é'));
+    assert_eq!(fr.translate(&shift, KeyCode::KEY_2), Some('2'));
+    assert_eq!(fr.translate(&altgr, KeyCode::KEY_0), Some('@'));
+
+    let mut dvorak = Keymap::builtin("dvorak").unwrap();
+    assert_eq!(dvorak.translate(&none, KeyCode::KEY_S), Some('o'));
+    assert_eq!(dvorak.translate(&shift, KeyCode::KEY_Q), Some('"'));
+
+    let mut gb = Keymap::builtin("gb").unwrap();
+    assert_eq!(gb.translate(&shift, KeyCode::KEY_3), Some('£'));
+    assert_eq!(gb.translate(&shift, KeyCode::KEY_A), Some('A'));
+    Ok(())
+}
+
+#[test]
+pub fn test_keymap_locks() -> std::io::Result<()> {
+    let mut us = Keymap::builtin("us").unwrap();
+    let mut caps = ModifierState::new();
+    caps.set_caps_lock(true);
+    assert_eq!(us.translate(&caps, KeyCode::KEY_A), Some('A'));
+    assert_eq!(us.translate(&caps, KeyCode::KEY_1), Some('1'));
+    caps.set_held(KeyCode::KEY_LEFTSHIFT, true);
+    assert_eq!(us.translate(&caps, KeyCode::KEY_A), Some('a'));
+
+    let mut de = Keymap::builtin("de").unwrap();
+    let mut caps = ModifierState::new();
+    caps.set_caps_lock(true);
+    assert_eq!(de.translate(&caps, KeyCode::KEY_APOSTROPHE), Some('Ä'));
+    assert_eq!(de.translate(&caps, KeyCode::KEY_MINUS), Some('ß'));
+
+    let mut num = ModifierState::new();
+    assert_eq!(us.translate(&num, KeyCode::KEY_KP1), None);
+    assert_eq!(us.translate(&num, KeyCode::KEY_KPPLUS), Some('+'));
+    num.set_num_lock(true);
+    assert_eq!(us.translate(&num, KeyCode::KEY_KP1), Some('1'));
+    assert_eq!(de.translate(&num, KeyCode::KEY_KPDOT), Some(','));
+    Ok(())
+}
+
+#[test]
+pub fn test_dead_keys() -> std::io::Result<()> {
+    let none = ModifierState::new();
+    let shift = with(&[KeyCode::KEY_LEFTSHIFT]);
+    let mut de = Keymap::builtin("de").unwrap();
+
+    assert_eq!(de.translate(&none, KeyCode::KEY_EQUAL), None);
+    assert_eq!(de.pending_dead_key(), Some('´'));
+    // pressing Shift doesn't cancel the dead key
+    assert_eq!(de.translate(&shift, KeyCode::KEY_LEFTSHIFT), None);
+    assert_eq!(de.translate(&shift, KeyCode::KEY_E), Some('É'));
+    assert_eq!(de.pending_dead_key(), None);
+
+    assert_eq!(de.translate(&none, KeyCode::KEY_GRAVE), None);
+    assert_eq!(de.translate(&none, KeyCode::KEY_SPACE), Some('^'));
+    assert_eq!(de.translate(&none, KeyCode::KEY_GRAVE), None);
+    assert_eq!(de.translate(&none, KeyCode::KEY_GRAVE), Some('^'));
+    // no such character, so the accent is dropped
+    assert_eq!(de.translate(&none, KeyCode::KEY_GRAVE), None);
+    assert_eq!(de.translate(&none, KeyCode::KEY_X), Some('x'));
+
+    assert_eq!(de.translate(&none, KeyCode::KEY_GRAVE), None);
+    de.reset();
+    assert_eq!(de.translate(&none, KeyCode::KEY_O), Some('o'));
+
+    let mut fr = Keymap::builtin("fr").unwrap();
+    assert_eq!(fr.translate(&none, KeyCode::KEY_LEFTBRACE), None);
+    assert_eq!(fr.translate(&none, KeyCode::KEY_E), Some('ê'));
+    assert_eq!(fr.translate(&shift, KeyCode::KEY_LEFTBRACE), None);
+    assert_eq!(fr.translate(&none, KeyCode::KEY_U), Some('ü'));
+    Ok(())
+}
+
+#[test]
+pub fn test_keymap_parse() -> std::io::Result<()> {
+    let mut keymap = Keymap::parse(
+        "# swap two keys\n\
+         include us\n\
+         \n\
+         KEY_A b B\n\
+         KEY_B U+0061 U+0041 dead:~ none\n",
+    )?;
+    let none = ModifierState::new();
+    assert_eq!(keymap.translate(&none, KeyCode::KEY_A), Some('b'));
+    assert_eq!(keymap.translate(&none, KeyCode::KEY_B), Some('a'));
+    assert_eq!(keymap.translate(&none, KeyCode::KEY_C), Some('c'));
+    let altgr = with(&[KeyCode::KEY_RIGHTALT]);
+    assert_eq!(keymap.translate(&altgr, KeyCode::KEY_B), None);
+    assert_eq!(keymap.translate(&none, KeyCode::KEY_N), Some('ñ'));
+
+    for text in [
+        "KEY_NOPE a",
+        "KEY_A",
+        "KEY_A ab",
+        "KEY_A a b c d e",
+        "KEY_A U+D800",
+        "KEY_A dead:",
+        "include xx",
+    ] {
+        let err = Keymap::parse(&format!("include us\n{text}")).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("line 2: "), "{err}");
+    }
+    Ok(())
+}
@@ -1 +1,18 @@
 mod attribute_set;
+mod axis;
+mod classify;
+mod event_mask;
+mod events;
+mod filter;
+mod gamepad;
+mod gestures;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod identity;
+mod keymap;
+mod modifiers;
+mod pointer;
+mod repeat;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod sysfs;
+mod tablet;
+mod touchpad;
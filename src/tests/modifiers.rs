@@ -0,0 +1,75 @@
+use crate::{AttributeSet, KeyCode, KeyEvent, LedCode, ModifierState};
+
+fn key(key: KeyCode, value: i32) -> KeyEvent {
+    KeyEvent::new(key, value)
+}
+
+#[test]
+pub fn test_modifier_state() -> std::io::Result<()> {
+    let mut modifiers = ModifierState::new();
+    assert!(ModifierState::is_modifier(KeyCode::KEY_RIGHTMETA));
+    assert!(!ModifierState::is_modifier(KeyCode::KEY_CAPSLOCK));
+
+    modifiers.set_held(KeyCode::KEY_RIGHTALT, true);
+    modifiers.set_held(KeyCode::KEY_A, true);
+    assert!(modifiers.is_held(KeyCode::KEY_RIGHTALT));
+    assert!(!modifiers.is_held(KeyCode::KEY_A));
+    assert!(modifiers.alt() && modifiers.altgr());
+    assert!(!modifiers.shift() && !modifiers.ctrl() && !modifiers.meta());
+    modifiers.set_held(KeyCode::KEY_RIGHTALT, false);
+    modifiers.set_held(KeyCode::KEY_LEFTALT, true);
+    assert!(modifiers.alt() && !modifiers.altgr());
+
+    modifiers.set_scroll_lock(true);
+    assert!(modifiers.scroll_lock() && !modifiers.caps_lock() && !modifiers.num_lock());
+    Ok(())
+}
+
+#[test]
+pub fn test_modifier_events() -> std::io::Result<()> {
+    let mut modifiers = ModifierState::new();
+    assert!(!modifiers.process_event(key(KeyCode::KEY_RIGHTSHIFT, 1)));
+    assert!(modifiers.shift());
+    assert!(modifiers.is_held(KeyCode::KEY_RIGHTSHIFT));
+    assert!(!modifiers.is_held(KeyCode::KEY_LEFTSHIFT));
+    assert!(!modifiers.process_event(key(KeyCode::KEY_A, 1)));
+    assert!(!modifiers.process_event(key(KeyCode::KEY_RIGHTSHIFT, 0)));
+    assert!(!modifiers.shift());
+
+    // locks toggle when pressed, but not when repeated or released
+    assert!(modifiers.process_event(key(KeyCode::KEY_CAPSLOCK, 1)));
+    assert!(!modifiers.process_event(key(KeyCode::KEY_CAPSLOCK, 2)));
+    assert!(!modifiers.process_event(key(KeyCode::KEY_CAPSLOCK, 0)));
+    assert!(modifiers.caps_lock());
+    assert!(modifiers.process_event(key(KeyCode::KEY_NUMLOCK, 1)));
+    assert_eq!(
+        modifiers.led_states(),
+        [
+            (LedCode::LED_CAPSL, true),
+            (LedCode::LED_NUML, true),
+            (LedCode::LED_SCROLLL, false)
+        ]
+    );
+    assert!(modifiers.process_event(key(KeyCode::KEY_CAPSLOCK, 1)));
+    assert!(!modifiers.caps_lock());
+    assert_eq!(
+        modifiers.leds().iter().collect::<Vec<_>>(),
+        [LedCode::LED_NUML]
+    );
+    Ok(())
+}
+
+#[test]
+pub fn test_modifier_seed() -> std::io::Result<()> {
+    let keys = AttributeSet::from_iter([KeyCode::KEY_LEFTCTRL, KeyCode::KEY_A]);
+    let leds = AttributeSet::from_iter([LedCode::LED_SCROLLL, LedCode::LED_MUTE]);
+    let modifiers = ModifierState::from_state(&keys, &leds);
+    assert!(modifiers.ctrl() && modifiers.is_held(KeyCode::KEY_LEFTCTRL));
+    assert!(!modifiers.shift());
+    assert!(modifiers.scroll_lock() && !modifiers.caps_lock());
+    assert_eq!(
+        modifiers.leds().iter().collect::<Vec<_>>(),
+        [LedCode::LED_SCROLLL]
+    );
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use std::time::Duration;
+
+use crate::pointer::{AccelCurve, AccelProfile, Accelerator, ScrollAccumulator, ScrollMode};
+use crate::{InputEvent, RelativeAxisCode, RelativeAxisEvent};
+
+const REL_WHEEL: RelativeAxisCode = RelativeAxisCode::REL_WHEEL;
+const REL_WHEEL_HI_RES: RelativeAxisCode = RelativeAxisCode::REL_WHEEL_HI_RES;
+const REL_HWHEEL: RelativeAxisCode = RelativeAxisCode::REL_HWHEEL;
+const REL_HWHEEL_HI_RES: RelativeAxisCode = RelativeAxisCode::REL_HWHEEL_HI_RES;
+
+/// A relative event of `code` at `ms` milliseconds.
+fn rel(code: RelativeAxisCode, value: i32, ms: u64) -> RelativeAxisEvent {
+    let mut raw = InputEvent::from(RelativeAxisEvent::new(code, value)).0;
+    raw.time = crate::clock::duration_to_timeval(Duration::from_millis(ms));
+    RelativeAxisEvent::from_event(InputEvent(raw))
+}
+
+/// Feeds `dx` every `interval` milliseconds and returns the accelerated `REL_X` values.
+fn run(accel: &mut Accelerator, dx: i32, interval: u64, frames: u64) -> Vec<i32> {
+    (1..=frames)
+        .map(|i| {
+            accel
+                .accelerate([rel(RelativeAxisCode::REL_X, dx, 1000 + i * interval)])
+                .filter(|ev| ev.code() == RelativeAxisCode::REL_X)
+                .map(|ev| ev.value())
+                .sum()
+        })
+        .collect()
+}
+
+#[test]
+pub fn test_flat_profile() -> std::io::Result<()> {
+    let mut accel = Accelerator::new(AccelProfile::Flat);
+    assert_eq!(run(&mut accel, 3, 8, 3), [3, 3, 3]);
+
+    // fractions are carried over to the next frame
+    let mut accel = Accelerator::new(AccelProfile::Flat).speed(0.5);
+    assert_eq!(run(&mut accel, 1, 8, 4), [1, 2, 1, 2]);
+    let mut accel = Accelerator::new(AccelProfile::Flat).speed(-0.5);
+    assert_eq!(run(&mut accel, -1, 8, 4), [0, -1, 0, -1]);
+
+    // the speed is clamped
+    assert_eq!(
+        Accelerator::new(AccelProfile::Flat).speed(3.0).get_speed(),
+        1.0
+    );
+    Ok(())
+}
+
+#[test]
+pub fn test_adaptive_profile() -> std::io::Result<()> {
+    // 2 units in 8ms are between the slow and the fast threshold
+    let mut accel = Accelerator::new(AccelProfile::Adaptive);
+    assert_eq!(run(&mut accel, 2, 8, 3), [2, 2, 2]);
+
+    // fast movements are accelerated up to the maximum factor
+    let mut accel = Accelerator::new(AccelProfile::Adaptive);
+    assert_eq!(run(&mut accel, 40, 8, 3), [80, 80, 80]);
+
+    // slow movements are decelerated
+    let mut accel = Accelerator::new(AccelProfile::Adaptive);
+    let slow = run(&mut accel, 1, 100, 10);
+    assert!(slow.iter().sum::<i32>() < 10);
+
+    // the velocity is measured at 1000 dpi
+    let mut accel = Accelerator::new(AccelProfile::Adaptive).dpi(8000);
+    assert_eq!(run(&mut accel, 16, 8, 3), [16, 16, 16]);
+    Ok(())
+}
+
+#[test]
+pub fn test_custom_profile() -> std::io::Result<()> {
+    assert!(AccelCurve::new(0.0, vec![0.0, 1.0]).is_err());
+    assert!(AccelCurve::new(1.0, vec![0.0]).is_err());
+    assert!(AccelCurve::new(1.0, vec![0.0, -1.0]).is_err());
+
+    let curve = AccelCurve::new(1.0, vec![0.0, 2.0, 4.0])?;
+    assert_eq!(curve.output(0.5), 1.0);
+    // extrapolated past the last point
+    assert_eq!(curve.output(3.0), 6.0);
+
+    let mut accel = Accelerator::new(AccelProfile::Custom(curve)).speed(1.0);
+    assert_eq!(run(&mut accel, 1, 8, 2), [2, 2]);
+    assert_eq!(run(&mut accel, 40, 8, 2), [80, 80]);
+    Ok(())
+}
+
+#[test]
+pub fn test_accelerate_frame() -> std::io::Result<()> {
+    let mut accel = Accelerator::new(AccelProfile::Flat).speed(1.0);
+    let out: Vec<_> = accel
+        .accelerate([
+            rel(RelativeAxisCode::REL_X, 1, 500),
+            rel(RelativeAxisCode::REL_WHEEL, -1, 500),
+            rel(RelativeAxisCode::REL_X, 2, 500),
+            rel(RelativeAxisCode::REL_Y, 0, 500),
+        ])
+        .map(|ev| (ev.code(), ev.value(), ev.clock_time()))
+        .collect();
+    let time = Duration::from_millis(500);
+    assert_eq!(
+        out,
+        [
+            (RelativeAxisCode::REL_X, 6, time),
+            (RelativeAxisCode::REL_WHEEL, -1, time),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+pub fn test_scroll_hi_res() -> std::io::Result<()> {
+    let mut scroll = ScrollAccumulator::new(ScrollMode::HiRes);
+    // the legacy events are ignored, and a detent is counted halfway to it
+    let delta = scroll.process_frame([rel(REL_WHEEL_HI_RES, 30, 10), rel(REL_WHEEL, 1, 10)]);
+    assert_eq!((delta.vertical(), delta.vertical_detents()), (0.25, 0));
+    let delta = scroll.process_frame([rel(REL_WHEEL_HI_RES, 30, 20)]);
+    assert_eq!((delta.vertical_hi_res(), delta.vertical_detents()), (30, 1));
+    let delta = scroll.process_frame([rel(REL_WHEEL_HI_RES, 120, 30)]);
+    assert_eq!(delta.vertical_detents(), 1);
+    let delta = scroll.process_frame([rel(REL_WHEEL_HI_RES, 30, 40)]);
+    assert_eq!(delta.vertical_detents(), 0);
+
+    let detents: Vec<_> = (0..8)
+        .map(|i| {
+            scroll
+                .process_frame([rel(REL_WHEEL_HI_RES, 30, 41 + i)])
+                .vertical_detents()
+        })
+        .collect();
+    assert_eq!(detents, [0, 0, 1, 0, 0, 0, 1, 0]);
+
+    // changing the direction starts over
+    let delta = scroll.process_frame([rel(REL_WHEEL_HI_RES, -40, 50)]);
+    assert_eq!(delta.vertical_detents(), 0);
+    let delta = scroll.process_frame([rel(REL_WHEEL_HI_RES, -20, 60)]);
+    assert_eq!(delta.vertical_detents(), -1);
+
+    let delta = scroll.process_frame([rel(RelativeAxisCode::REL_X, 5, 70)]);
+    assert!(delta.is_empty());
+    assert_eq!(delta.events().count(), 0);
+    Ok(())
+}
+
+#[test]
+pub fn test_scroll_legacy() -> std::io::Result<()> {
+    // the high-resolution events are synthesized, with the time of the frame
+    let mut scroll = ScrollAccumulator::new(ScrollMode::Legacy);
+    let delta = scroll.process_frame([rel(REL_HWHEEL, -2, 10)]);
+    assert_eq!(delta.horizontal(), -2.0);
+    let events: Vec<_> = delta.events().collect();
+    assert_eq!(
+        events
+            .iter()
+            .map(|ev| (ev.code(), ev.value()))
+            .collect::<Vec<_>>(),
+        [(REL_HWHEEL, -2), (REL_HWHEEL_HI_RES, -240)]
+    );
+    assert!(events
+        .iter()
+        .all(|ev| ev.timestamp() == rel(REL_WHEEL, 0, 10).timestamp()));
+
+    // in the automatic mode, the high-resolution events take over once there are some
+    let mut scroll = ScrollAccumulator::new(ScrollMode::Auto);
+    let delta = scroll.process_frame([rel(REL_WHEEL, 1, 10)]);
+    assert_eq!(delta.vertical_hi_res(), 120);
+    let delta = scroll.process_frame([rel(REL_WHEEL_HI_RES, 60, 20), rel(REL_WHEEL, 1, 20)]);
+    assert_eq!((delta.vertical_hi_res(), delta.vertical_detents()), (60, 1));
+    let delta = scroll.process_frame([rel(REL_WHEEL, 1, 30)]);
+    assert!(delta.is_empty());
+    // the other wheel still goes by its legacy events
+    let delta = scroll.process_frame([rel(REL_HWHEEL, 1, 40)]);
+    assert_eq!(delta.horizontal_detents(), 1);
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use crate::repeat::KeyRepeater;
+use crate::{AutoRepeat, Clock, EventType, InputEvent, KeyCode, KeyEvent};
+use std::time::Duration;
+
+fn key(key: KeyCode, value: i32, ms: u64) -> InputEvent {
+    let mut event = InputEvent::new(EventType::KEY.0, key.0, value);
+    event.0.time = crate::clock::duration_to_timeval(Duration::from_millis(ms));
+    event
+}
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn repeater() -> KeyRepeater {
+    KeyRepeater::new(AutoRepeat {
+        delay: 250,
+        period: 33,
+    })
+}
+
+#[test]
+pub fn test_key_repeat() -> std::io::Result<()> {
+    let mut repeater = repeater();
+    repeater.process_event(key(KeyCode::KEY_A, 1, 1000));
+    assert_eq!(repeater.deadline(), Some(ms(1250)));
+    assert_eq!(repeater.process_timeout(ms(1200)), None);
+
+    let repeat = repeater.process_timeout(ms(1250)).unwrap();
+    assert_eq!(repeat.destructure(), (KeyCode::KEY_A, 2));
+    assert_eq!(repeat.clock_time(), ms(1250));
+    let repeat = repeater.process_timeout(ms(1300)).unwrap();
+    assert_eq!(repeat.clock_time(), ms(1283));
+    assert_eq!(repeater.deadline(), Some(ms(1316)));
+
+    // only one repeat when late, and the next a period later
+    let repeat = repeater.process_timeout(ms(2000)).unwrap();
+    assert_eq!(repeat.clock_time(), ms(1316));
+    assert_eq!(repeater.deadline(), Some(ms(2033)));
+    assert_eq!(repeater.process_timeout(ms(2010)), None);
+
+    repeater.process_event(key(KeyCode::KEY_A, 0, 2020));
+    assert_eq!(repeater.deadline(), None);
+    assert_eq!(repeater.process_timeout(ms(3000)), None);
+    Ok(())
+}
+
+#[test]
+pub fn test_key_repeat_cancel() -> std::io::Result<()> {
+    let mut repeater = repeater();
+    repeater.process_event(key(KeyCode::KEY_A, 1, 1000));
+    repeater.process_event(key(KeyCode::KEY_B, 1, 1100));
+    assert_eq!(repeater.repeating(), Some(KeyCode::KEY_B));
+    assert_eq!(repeater.deadline(), Some(ms(1350)));
+    // releasing another key doesn't matter, and the device's own repeats are ignored
+    repeater.process_event(key(KeyCode::KEY_A, 0, 1200));
+    repeater.process_event(key(KeyCode::KEY_B, 2, 1300));
+    assert_eq!(repeater.deadline(), Some(ms(1350)));
+    // the first key doesn't repeat again once the second is released
+    repeater.process_event(key(KeyCode::KEY_B, 0, 1400));
+    assert_eq!(repeater.repeating(), None);
+
+    // buttons don't repeat, and neither does a new key cancelled by hand
+    repeater.process_event(key(KeyCode::BTN_LEFT, 1, 2000));
+    assert_eq!(repeater.repeating(), None);
+    repeater.process_event(key(KeyCode::KEY_C, 1, 2000));
+    repeater.cancel();
+    assert_eq!(repeater.process_timeout(ms(3000)), None);
+
+    let mut repeater = KeyRepeater::new(AutoRepeat {
+        delay: 250,
+        period: 0,
+    });
+    repeater.process_event(key(KeyCode::KEY_A, 1, 1000));
+    assert_eq!(repeater.repeating(), None);
+    Ok(())
+}
+
+#[test]
+pub fn test_key_repeat_wait() -> std::io::Result<()> {
+    let mut repeater = KeyRepeater::new(AutoRepeat {
+        delay: 20,
+        period: 10,
+    })
+    .clock(Clock::Monotonic);
+    assert_eq!(repeater.wait(None), None);
+
+    // synthesized events happen now
+    let start = Clock::Monotonic.now();
+    repeater.process_event(*KeyEvent::new(KeyCode::KEY_A, 1));
+    assert!(repeater.timeout().unwrap() <= ms(20));
+    assert_eq!(repeater.wait(Some(ms(1))), None);
+    let repeat = repeater.wait(None).unwrap();
+    // timestamps only have microseconds
+    assert!(repeat.clock_time() + Duration::from_micros(1) > start + ms(20));
+    assert!(Clock::Monotonic.now() >= repeat.clock_time());
+    Ok(())
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+pub async fn test_key_repeat_tokio() -> std::io::Result<()> {
+    let mut repeater = KeyRepeater::new(AutoRepeat {
+        delay: 20,
+        period: 10,
+    })
+    .clock(Clock::Monotonic);
+    repeater.process_event(*KeyEvent::new(KeyCode::KEY_A, 1));
+    let first = repeater.next_repeat().await;
+    let second = repeater.next_repeat().await;
+    // a late wakeup delays the following repeats rather than bunching them up
+    assert!(second.clock_time() >= first.clock_time() + ms(10));
+    Ok(())
+}
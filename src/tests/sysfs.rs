@@ -0,0 +1,96 @@
+use crate::{BusType, EventType, InputId, KeyCode, SysfsInfo};
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+
+/// A fake sysfs tree with a USB keyboard whose event node is `13:66`.
+fn fake_sysfs() -> std::io::Result<PathBuf> {
+    let root = std::env::temp_dir().join(format!("evdev-sysfs-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+
+    let usb = root.join("devices/pci0000:00/0000:00:14.0/usb1/1-2");
+    let interface = usb.join("1-2:1.0");
+    let hid = interface.join("0003:046D:C52B.0001");
+    let input = hid.join("input/input3");
+    let event = input.join("event2");
+    fs::create_dir_all(event.join("power"))?;
+    fs::create_dir_all(input.join("capabilities"))?;
+    fs::create_dir_all(root.join("dev/char"))?;
+
+    let device = |dir: &Path, subsystem: &str| -> std::io::Result<()> {
+        fs::write(dir.join("uevent"), "")?;
+        symlink(root.join(subsystem), dir.join("subsystem"))
+    };
+    device(&usb, "bus/usb")?;
+    fs::write(usb.join("idVendor"), "046d\n")?;
+    device(&interface, "bus/usb")?;
+    device(&hid, "bus/hid")?;
+    symlink(root.join("bus/hid/drivers/hid-generic"), hid.join("driver"))?;
+    device(&input, "class/input")?;
+    symlink("../..", input.join("device"))?;
+    device(&event, "class/input")?;
+    symlink("..", event.join("device"))?;
+    symlink(&event, root.join("dev/char/13:66"))?;
+
+    fs::write(
+        input.join("modalias"),
+        "input:b0003v046DpC52Be0111-e0,1,4,11,14,\n",
+    )?;
+    fs::write(input.join("properties"), "0\n")?;
+    fs::write(input.join("name"), "Logitech USB Receiver\n")?;
+    fs::write(input.join("phys"), "usb-0000:00:14.0-2/input0\n")?;
+    fs::write(input.join("uniq"), "\n")?;
+    fs::create_dir(input.join("id"))?;
+    for (name, value) in [
+        ("bustype", "0003"),
+        ("vendor", "046d"),
+        ("product", "c52b"),
+        ("version", "0111"),
+    ] {
+        fs::write(input.join("id").join(name), format!("{}\n", value))?;
+    }
+    fs::write(input.join("capabilities/ev"), "120013\n")?;
+    // KEY_ESC in the lowest word, KEY_MAX in the highest
+    let mut key = vec!["8000000000000000"];
+    key.extend(std::iter::repeat("0").take(10));
+    key.push("2");
+    fs::write(input.join("capabilities/key"), key.join(" ") + "\n")?;
+
+    Ok(root)
+}
+
+#[test]
+pub fn test_sysfs_info() -> std::io::Result<()> {
+    let root = fake_sysfs()?;
+    let info = SysfsInfo::from_devnum_in(libc::makedev(13, 66), &root)?;
+
+    assert!(info.syspath().ends_with("input/input3/event2"));
+    assert!(info.input_syspath().ends_with("input/input3"));
+    assert_eq!("input:b0003v046DpC52Be0111-e0,1,4,11,14,", info.modalias()?);
+    assert_eq!(Some("Logitech USB Receiver".to_owned()), info.name()?);
+    assert_eq!(
+        Some("usb-0000:00:14.0-2/input0".to_owned()),
+        info.physical_path()?
+    );
+    assert_eq!(None, info.unique_name()?);
+    assert_eq!(
+        InputId::new(BusType::BUS_USB, 0x046d, 0xc52b, 0x0111),
+        info.input_id()?
+    );
+    assert_eq!(Some("hid-generic".to_owned()), info.driver()?);
+    assert!(info.usb_device().unwrap().ends_with("usb1/1-2"));
+    assert_eq!(None, info.bluetooth_device());
+
+    let ev = info.capabilities_ev()?;
+    assert!(ev.contains(EventType::KEY));
+    assert!(ev.contains(EventType::LED));
+    assert!(!ev.contains(EventType::RELATIVE));
+    let keys = info.capabilities_key()?;
+    assert_eq!(
+        keys.iter().collect::<Vec<_>>(),
+        [KeyCode::KEY_ESC, KeyCode(0x2ff)]
+    );
+    assert_eq!(0, info.properties()?.iter().count());
+
+    fs::remove_dir_all(root)
+}
@@ -0,0 +1,216 @@
+use super::events::{abs, key, untimed_frame};
+use crate::tablet::{Tablet, ToolEvent, ToolType};
+use crate::{AbsInfo, AbsoluteAxisCode, EventType, InputEvent, KeyCode, MiscCode};
+
+/// A Wacom-style tablet with tilt in units per radian.
+fn wacom(tilt_resolution: i32) -> Tablet {
+    let axes = [
+        (
+            AbsoluteAxisCode::ABS_X,
+            AbsInfo::new(0, 0, 10000, 0, 0, 100),
+        ),
+        (AbsoluteAxisCode::ABS_Y, AbsInfo::new(0, 0, 6000, 0, 0, 100)),
+        (
+            AbsoluteAxisCode::ABS_PRESSURE,
+            AbsInfo::new(0, 0, 1000, 0, 0, 0),
+        ),
+        (
+            AbsoluteAxisCode::ABS_DISTANCE,
+            AbsInfo::new(0, 0, 50, 0, 0, 0),
+        ),
+        (
+            AbsoluteAxisCode::ABS_TILT_X,
+            AbsInfo::new(0, -64, 64, 0, 0, tilt_resolution),
+        ),
+        (
+            AbsoluteAxisCode::ABS_TILT_Y,
+            AbsInfo::new(0, -64, 64, 0, 0, tilt_resolution),
+        ),
+        (
+            AbsoluteAxisCode::ABS_MISC,
+            AbsInfo::new(0, 0, i32::MAX, 0, 0, 0),
+        ),
+    ];
+    Tablet::from_axes(&axes)
+}
+
+fn serial(serial: i32) -> InputEvent {
+    InputEvent::new(EventType::MISC.0, MiscCode::MSC_SERIAL.0, serial)
+}
+
+fn process(tablet: &mut Tablet, events: &[InputEvent]) -> Vec<ToolEvent> {
+    tablet.process_frame(untimed_frame(events)).collect()
+}
+
+#[test]
+pub fn test_tool_events() -> std::io::Result<()> {
+    let mut tablet = wacom(0);
+    let pen = ToolType::Pen;
+    let events = process(
+        &mut tablet,
+        &[
+            abs(AbsoluteAxisCode::ABS_X, 5000),
+            abs(AbsoluteAxisCode::ABS_Y, 3000),
+            abs(AbsoluteAxisCode::ABS_DISTANCE, 25),
+            abs(AbsoluteAxisCode::ABS_MISC, 0x822),
+            key(KeyCode::BTN_TOOL_PEN, 1),
+            serial(-2),
+        ],
+    );
+    assert_eq!(
+        events,
+        [
+            ToolEvent::ProximityIn {
+                tool: pen,
+                serial: Some(u32::MAX - 1),
+                tool_id: Some(0x822),
+            },
+            ToolEvent::Axis {
+                tool: pen,
+                x: 5000,
+                y: 3000,
+                pressure: Some(0.0),
+                distance: Some(0.5),
+                tilt_x: Some(0.0),
+                tilt_y: Some(0.0),
+            },
+        ]
+    );
+    assert_eq!(tablet.tool(), Some(pen));
+
+    // the tip goes down after the pressure is reported
+    let events = process(
+        &mut tablet,
+        &[
+            abs(AbsoluteAxisCode::ABS_PRESSURE, 250),
+            key(KeyCode::BTN_TOUCH, 1),
+            key(KeyCode::BTN_STYLUS, 1),
+            serial(-2),
+        ],
+    );
+    assert!(matches!(
+        events[0],
+        ToolEvent::Axis {
+            pressure: Some(p),
+            ..
+        } if p == 0.25
+    ));
+    assert_eq!(
+        events[1..],
+        [
+            ToolEvent::Tip {
+                tool: pen,
+                down: true
+            },
+            ToolEvent::Button {
+                tool: pen,
+                button: KeyCode::BTN_STYLUS,
+                pressed: true
+            },
+        ]
+    );
+    assert!(tablet.is_tip_down());
+
+    // nothing changed
+    assert!(process(&mut tablet, &[serial(-2)]).is_empty());
+
+    // leaving proximity lifts the tip and releases the buttons first
+    let events = process(
+        &mut tablet,
+        &[
+            abs(AbsoluteAxisCode::ABS_MISC, 0),
+            key(KeyCode::BTN_TOOL_PEN, 0),
+            serial(-2),
+        ],
+    );
+    assert_eq!(
+        events,
+        [
+            ToolEvent::Tip {
+                tool: pen,
+                down: false
+            },
+            ToolEvent::Button {
+                tool: pen,
+                button: KeyCode::BTN_STYLUS,
+                pressed: false
+            },
+            ToolEvent::ProximityOut {
+                tool: pen,
+                serial: Some(u32::MAX - 1)
+            },
+        ]
+    );
+    assert_eq!(tablet.tool(), None);
+    assert!(!tablet.is_tip_down());
+
+    // events without a tool are ignored
+    assert!(process(&mut tablet, &[abs(AbsoluteAxisCode::ABS_X, 10)]).is_empty());
+    Ok(())
+}
+
+#[test]
+pub fn test_tool_switch_and_tilt() -> std::io::Result<()> {
+    // 57 units per radian are about 1 unit per degree
+    let mut tablet = wacom(57);
+    process(
+        &mut tablet,
+        &[
+            key(KeyCode::BTN_TOOL_PEN, 1),
+            abs(AbsoluteAxisCode::ABS_TILT_X, 57),
+        ],
+    );
+    let events = process(
+        &mut tablet,
+        &[
+            key(KeyCode::BTN_TOOL_PEN, 0),
+            key(KeyCode::BTN_TOOL_RUBBER, 1),
+            abs(AbsoluteAxisCode::ABS_TILT_Y, -57),
+        ],
+    );
+    assert_eq!(events.len(), 3);
+    assert_eq!(
+        events[..2],
+        [
+            ToolEvent::ProximityOut {
+                tool: ToolType::Pen,
+                serial: None
+            },
+            ToolEvent::ProximityIn {
+                tool: ToolType::Eraser,
+                serial: None,
+                tool_id: None
+            },
+        ]
+    );
+    match events[2] {
+        ToolEvent::Axis {
+            tool: ToolType::Eraser,
+            tilt_x: Some(x),
+            tilt_y: Some(y),
+            ..
+        } => {
+            assert!((x - 57.29578).abs() < 1e-3);
+            assert!((y + 57.29578).abs() < 1e-3);
+        }
+        ev => panic!("unexpected {ev:?}"),
+    }
+
+    // without a resolution, the axis spans -64 to 64 degrees
+    let mut tablet = wacom(0);
+    let events = process(
+        &mut tablet,
+        &[
+            key(KeyCode::BTN_TOOL_PENCIL, 1),
+            abs(AbsoluteAxisCode::ABS_TILT_X, 32),
+        ],
+    );
+    assert!(matches!(
+        events[1],
+        ToolEvent::Axis {
+            tilt_x: Some(x),
+            ..
+        } if x == 32.0
+    ));
+    Ok(())
+}
@@ -0,0 +1,254 @@
+use super::events::{abs, down, move_to, up};
+use crate::touchpad::{TouchpadConfig, TouchpadConverter};
+use crate::{
+    AbsInfo, AbsoluteAxisCode, EventType, Frame, InputEvent, KeyCode, RelativeAxisCode,
+    SynchronizationCode,
+};
+use std::time::Duration;
+
+/// A 100 x 70 mm clickpad with 10 units per mm and three slots.
+fn clickpad() -> TouchpadConverter {
+    let x = AbsInfo::new(0, 0, 1000, 0, 0, 10);
+    let y = AbsInfo::new(0, 0, 700, 0, 0, 10);
+    TouchpadConverter::from_axes(x, y, 3, true, TouchpadConfig::default())
+}
+
+fn click(value: i32) -> InputEvent {
+    InputEvent::new(EventType::KEY.0, KeyCode::BTN_LEFT.0, value)
+}
+
+fn rel(axis: RelativeAxisCode, value: i32) -> (EventType, u16, i32) {
+    (EventType::RELATIVE, axis.0, value)
+}
+
+fn key(key: KeyCode, value: i32) -> (EventType, u16, i32) {
+    (EventType::KEY, key.0, value)
+}
+
+fn syn() -> (EventType, u16, i32) {
+    (
+        EventType::SYNCHRONIZATION,
+        SynchronizationCode::SYN_REPORT.0,
+        0,
+    )
+}
+
+fn frame(
+    converter: &mut TouchpadConverter,
+    ms: u64,
+    events: &[InputEvent],
+) -> Vec<(EventType, u16, i32)> {
+    let time = crate::clock::duration_to_timeval(Duration::from_millis(ms));
+    converter
+        .process_frame(Frame::new(events, time, false))
+        .map(|ev| {
+            assert_eq!(ev.clock_time(), Duration::from_millis(ms));
+            (ev.event_type(), ev.code(), ev.value())
+        })
+        .collect()
+}
+
+#[test]
+pub fn test_touchpad_motion() -> std::io::Result<()> {
+    let mut tp = clickpad();
+    assert_eq!(frame(&mut tp, 0, &down(0, 500, 300)), []);
+    // 1 mm is 39.37 units at 1000 dpi, the fractions add up
+    assert_eq!(
+        frame(&mut tp, 10, &move_to(0, 510, 300)),
+        [rel(RelativeAxisCode::REL_X, 39)]
+    );
+    assert_eq!(
+        frame(&mut tp, 20, &move_to(0, 520, 290)),
+        [
+            rel(RelativeAxisCode::REL_X, 39),
+            rel(RelativeAxisCode::REL_Y, -39)
+        ]
+    );
+    assert_eq!(
+        frame(&mut tp, 30, &move_to(0, 530, 290)),
+        [rel(RelativeAxisCode::REL_X, 40)]
+    );
+    // too far for a tap
+    assert_eq!(frame(&mut tp, 40, &up(0)), []);
+    Ok(())
+}
+
+#[test]
+pub fn test_touchpad_tap() -> std::io::Result<()> {
+    let mut tp = clickpad();
+    frame(&mut tp, 0, &down(0, 500, 300));
+    assert_eq!(
+        frame(&mut tp, 100, &up(0)),
+        [key(KeyCode::BTN_LEFT, 1), syn(), key(KeyCode::BTN_LEFT, 0)]
+    );
+
+    frame(&mut tp, 1000, &down(0, 400, 300));
+    frame(&mut tp, 1010, &down(1, 600, 300));
+    frame(&mut tp, 1050, &up(1));
+    assert_eq!(
+        frame(&mut tp, 1060, &up(0)),
+        [
+            key(KeyCode::BTN_RIGHT, 1),
+            syn(),
+            key(KeyCode::BTN_RIGHT, 0)
+        ]
+    );
+
+    // too long
+    frame(&mut tp, 2000, &down(0, 500, 300));
+    assert_eq!(frame(&mut tp, 2300, &up(0)), []);
+
+    let x = AbsInfo::new(0, 0, 1000, 0, 0, 10);
+    let config = TouchpadConfig::new().tap_to_click(false);
+    let mut tp = TouchpadConverter::from_axes(x, x, 3, true, config);
+    frame(&mut tp, 0, &down(0, 500, 300));
+    assert_eq!(frame(&mut tp, 100, &up(0)), []);
+    Ok(())
+}
+
+#[test]
+pub fn test_touchpad_scroll() -> std::io::Result<()> {
+    let mut tp = clickpad();
+    frame(&mut tp, 0, &[down(0, 400, 300), down(1, 600, 300)].concat());
+    assert_eq!(
+        frame(
+            &mut tp,
+            10,
+            &[move_to(0, 400, 325), move_to(1, 600, 325)].concat()
+        ),
+        [rel(RelativeAxisCode::REL_WHEEL_HI_RES, -60)]
+    );
+    assert_eq!(
+        frame(
+            &mut tp,
+            20,
+            &[move_to(0, 410, 350), move_to(1, 610, 350)].concat()
+        ),
+        [
+            rel(RelativeAxisCode::REL_WHEEL, -1),
+            rel(RelativeAxisCode::REL_WHEEL_HI_RES, -60),
+            rel(RelativeAxisCode::REL_HWHEEL_HI_RES, 24),
+        ]
+    );
+
+    let x = AbsInfo::new(0, 0, 1000, 0, 0, 10);
+    let config = TouchpadConfig::new().natural_scroll(true);
+    let mut tp = TouchpadConverter::from_axes(x, x, 3, true, config);
+    frame(&mut tp, 0, &[down(0, 400, 300), down(1, 600, 300)].concat());
+    assert_eq!(
+        frame(
+            &mut tp,
+            10,
+            &[move_to(0, 400, 350), move_to(1, 600, 350)].concat()
+        ),
+        [
+            rel(RelativeAxisCode::REL_WHEEL, 1),
+            rel(RelativeAxisCode::REL_WHEEL_HI_RES, 120),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+pub fn test_touchpad_palm() -> std::io::Result<()> {
+    let mut tp = clickpad();
+
+    // a large contact, in a slot of its own as the kernel keeps the values of a slot for the
+    // next contact
+    frame(
+        &mut tp,
+        0,
+        &[
+            &down(2, 500, 300)[..],
+            &[abs(AbsoluteAxisCode::ABS_MT_TOUCH_MAJOR, 250)],
+        ]
+        .concat(),
+    );
+    assert_eq!(frame(&mut tp, 10, &move_to(2, 550, 300)), []);
+    // a finger next to the palm still moves the pointer
+    frame(&mut tp, 20, &down(1, 300, 300));
+    assert_eq!(
+        frame(&mut tp, 30, &move_to(1, 300, 310)),
+        [rel(RelativeAxisCode::REL_Y, 39)]
+    );
+    frame(&mut tp, 40, &[up(2), up(1)].concat());
+
+    // reported as a palm
+    frame(
+        &mut tp,
+        100,
+        &[
+            &down(2, 500, 300)[..],
+            &[abs(AbsoluteAxisCode::ABS_MT_TOOL_TYPE, 2)],
+        ]
+        .concat(),
+    );
+    assert_eq!(frame(&mut tp, 110, &move_to(2, 550, 300)), []);
+    assert_eq!(frame(&mut tp, 120, &up(2)), []);
+
+    // resting at the edge, and moving out of it too late
+    frame(&mut tp, 1000, &down(0, 20, 300));
+    assert_eq!(frame(&mut tp, 1100, &move_to(0, 30, 300)), []);
+    assert_eq!(frame(&mut tp, 1300, &move_to(0, 100, 300)), []);
+    assert_eq!(frame(&mut tp, 1310, &move_to(0, 110, 300)), []);
+    frame(&mut tp, 1320, &up(0));
+
+    // a finger that quickly moves out of the edge zone
+    frame(&mut tp, 2000, &down(0, 20, 300));
+    assert_eq!(frame(&mut tp, 2010, &move_to(0, 60, 300)), []);
+    assert_eq!(
+        frame(&mut tp, 2020, &move_to(0, 70, 300)),
+        [rel(RelativeAxisCode::REL_X, 39)]
+    );
+    Ok(())
+}
+
+#[test]
+pub fn test_touchpad_buttons() -> std::io::Result<()> {
+    let mut tp = clickpad();
+    assert!(tp.is_clickpad());
+
+    // a thumb resting on the right button, which doesn't move the pointer
+    frame(&mut tp, 0, &down(0, 800, 680));
+    assert_eq!(frame(&mut tp, 10, &move_to(0, 810, 680)), []);
+    assert_eq!(
+        frame(&mut tp, 20, &[click(1)]),
+        [key(KeyCode::BTN_RIGHT, 1)]
+    );
+    assert_eq!(
+        frame(&mut tp, 30, &[click(0)]),
+        [key(KeyCode::BTN_RIGHT, 0)]
+    );
+    frame(&mut tp, 40, &up(0));
+
+    // dragging with another finger while the thumb holds the left button
+    frame(&mut tp, 100, &down(0, 200, 680));
+    assert_eq!(
+        frame(&mut tp, 110, &[click(1)]),
+        [key(KeyCode::BTN_LEFT, 1)]
+    );
+    frame(&mut tp, 120, &down(1, 500, 300));
+    assert_eq!(
+        frame(&mut tp, 130, &move_to(1, 510, 300)),
+        [rel(RelativeAxisCode::REL_X, 39)]
+    );
+    assert_eq!(
+        frame(&mut tp, 140, &[&up(1)[..], &[click(0)]].concat()),
+        [key(KeyCode::BTN_LEFT, 0)]
+    );
+    // a click isn't a tap
+    assert_eq!(frame(&mut tp, 150, &up(0)), []);
+
+    // the buttons of a touchpad with physical buttons are passed through
+    let x = AbsInfo::new(0, 0, 1000, 0, 0, 10);
+    let mut tp = TouchpadConverter::from_axes(x, x, 3, false, TouchpadConfig::default());
+    assert_eq!(
+        frame(
+            &mut tp,
+            0,
+            &[InputEvent::new(EventType::KEY.0, KeyCode::BTN_RIGHT.0, 1)]
+        ),
+        [key(KeyCode::BTN_RIGHT, 1)]
+    );
+    Ok(())
+}
@@ -0,0 +1,537 @@
+//! Driving a relative pointer from a touchpad.
+//!
+//! A [`TouchpadConverter`] follows the contacts of a Type B multi-touch touchpad and turns its
+//! [`Frame`]s into the events of a mouse: one finger moves the pointer with `REL_X`/`REL_Y`, two
+//! fingers scroll with `REL_WHEEL_HI_RES`/`REL_HWHEEL_HI_RES` (and the legacy `REL_WHEEL`/
+//! `REL_HWHEEL` for every full notch), and tapping with one, two or three fingers clicks the
+//! left, right or middle button. On a clickpad, i.e. a device with the
+//! [`BUTTONPAD`](PropType::BUTTONPAD) property whose whole surface is a button, the bottom of the
+//! touchpad is divided into a left and a right software button.
+//!
+//! Contacts that are not fingers meant to move the pointer are ignored:
+//!
+//! - palms, which the device reports with `ABS_MT_TOOL_TYPE` set to `MT_TOOL_PALM` or which are
+//!   larger than the [palm size](TouchpadConfig::palm_size) on `ABS_MT_TOUCH_MAJOR`,
+//! - contacts that begin in the zones along the left and right edges, unless they quickly move out
+//!   of them, as they are usually the palm of a hand resting next to the touchpad,
+//! - thumbs resting on the software buttons of a clickpad, until they move out of the button area.
+//!
+//! The events returned for a frame are meant to be passed to [`VirtualDevice::emit`] as a whole.
+//! A tap is returned as a button press, a `SYN_REPORT` and the release, so that the click spans two
+//! frames. All events carry the timestamp of the frame, so the motion can be fed through a
+//! [`pointer::Accelerator`](crate::pointer::Accelerator) as well.
+//!
+//! [`VirtualDevice::emit`]: crate::uinput::VirtualDevice::emit
+//!
+//! ```no_run
+//! use evdev::touchpad::TouchpadConverter;
+//! use evdev::uinput::VirtualDevice;
+//! use evdev::Device;
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let mut device = Device::open("/dev/input/event0")?;
+//! let mut converter = TouchpadConverter::new(&device)?;
+//! let mut mouse = VirtualDevice::builder()?
+//!     .name("Touchpad mouse")
+//!     .with_keys(&TouchpadConverter::keys())?
+//!     .with_relative_axes(&TouchpadConverter::relative_axes())?
+//!     .build()?;
+//! device.grab()?;
+//! loop {
+//!     let mut frames = device.fetch_frames()?;
+//!     while let Some(frame) = frames.next_frame() {
+//!         let events: Vec<_> = converter.process_frame(frame).collect();
+//!         if !events.is_empty() {
+//!             mouse.emit(&events)?;
+//!         }
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::io;
+use std::time::Duration;
+
+use crate::compat::input_event;
+use crate::multitouch::MtTracker;
+use crate::{
+    AbsoluteAxisCode, AttributeSet, Device, EventType, Frame, InputEvent, KeyCode, PropType,
+    RelativeAxisCode, SynchronizationCode,
+};
+
+/// The value of `ABS_MT_TOOL_TYPE` for a palm.
+const MT_TOOL_PALM: i32 = 2;
+
+/// How long a contact that began in an edge zone has to leave it to count as a finger.
+const EDGE_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// The `REL_*_HI_RES` units that make one notch of a wheel.
+const HI_RES_PER_NOTCH: i32 = 120;
+
+/// The buttons a tap with one, two and three fingers clicks.
+const TAP_BUTTONS: [KeyCode; 3] = [KeyCode::BTN_LEFT, KeyCode::BTN_RIGHT, KeyCode::BTN_MIDDLE];
+
+/// The settings of a [`TouchpadConverter`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TouchpadConfig {
+    dpi: f64,
+    tap_to_click: bool,
+    tap_time: Duration,
+    tap_distance: f64,
+    scroll_distance: f64,
+    natural_scroll: bool,
+    button_area_height: f64,
+    palm_size: f64,
+    edge_width: f64,
+}
+
+impl Default for TouchpadConfig {
+    fn default() -> Self {
+        TouchpadConfig {
+            dpi: 1000.0,
+            tap_to_click: true,
+            tap_time: Duration::from_millis(180),
+            tap_distance: 1.5,
+            scroll_distance: 5.0,
+            natural_scroll: false,
+            button_area_height: 10.0,
+            palm_size: 20.0,
+            edge_width: 5.0,
+        }
+    }
+}
+
+impl TouchpadConfig {
+    /// Creates the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How many units of `REL_X`/`REL_Y` a finger moving by an inch makes. Defaults to 1000, as
+    /// for a 1000 dpi mouse.
+    pub fn dpi(mut self, dpi: f64) -> Self {
+        self.dpi = dpi;
+        self
+    }
+
+    /// Whether tapping clicks a button. Defaults to `true`.
+    pub fn tap_to_click(mut self, enabled: bool) -> Self {
+        self.tap_to_click = enabled;
+        self
+    }
+
+    /// The longest time fingers may touch the touchpad to count as a tap. Defaults to 180 ms.
+    pub fn tap_time(mut self, time: Duration) -> Self {
+        self.tap_time = time;
+        self
+    }
+
+    /// How far, in millimetres, fingers may move during a tap. Defaults to 1.5 mm.
+    pub fn tap_distance(mut self, mm: f64) -> Self {
+        self.tap_distance = mm;
+        self
+    }
+
+    /// How far, in millimetres, two fingers have to move to scroll by one notch of a wheel.
+    /// Defaults to 5 mm.
+    pub fn scroll_distance(mut self, mm: f64) -> Self {
+        self.scroll_distance = mm;
+        self
+    }
+
+    /// Whether the content follows the fingers when scrolling, rather than scrolling the way the
+    /// fingers move. Defaults to `false`.
+    pub fn natural_scroll(mut self, enabled: bool) -> Self {
+        self.natural_scroll = enabled;
+        self
+    }
+
+    /// The height, in millimetres, of the software buttons at the bottom of a clickpad. Defaults
+    /// to 10 mm.
+    pub fn button_area_height(mut self, mm: f64) -> Self {
+        self.button_area_height = mm;
+        self
+    }
+
+    /// The smallest `ABS_MT_TOUCH_MAJOR`, in millimetres, that is taken for a palm. Defaults to
+    /// 20 mm.
+    pub fn palm_size(mut self, mm: f64) -> Self {
+        self.palm_size = mm;
+        self
+    }
+
+    /// The width, in millimetres, of the zones along the left and right edges where contacts are
+    /// taken for palms. Defaults to 5 mm.
+    pub fn edge_width(mut self, mm: f64) -> Self {
+        self.edge_width = mm;
+        self
+    }
+}
+
+/// A contact in one of the slots of the touchpad.
+#[derive(Debug, Clone, Copy, Default)]
+struct Contact {
+    /// The tracking ID of the contact as of the last frame, which changes when a new contact is
+    /// put down in the slot.
+    tracking_id: Option<i32>,
+    start: (f64, f64),
+    start_time: Duration,
+    /// Where the contact was in the last frame, in millimetres.
+    last: (f64, f64),
+    palm: bool,
+    edge: bool,
+    thumb: bool,
+}
+
+impl Contact {
+    fn is_active(&self) -> bool {
+        self.tracking_id.is_some()
+    }
+
+    fn is_finger(&self) -> bool {
+        self.is_active() && !self.palm && !self.edge && !self.thumb
+    }
+}
+
+/// A possible tap, from the moment the first finger touched the touchpad.
+#[derive(Debug, Clone, Copy)]
+struct Tap {
+    since: Duration,
+    max_fingers: usize,
+    moved: bool,
+}
+
+/// Turns the frames of a touchpad into the events of a mouse.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct TouchpadConverter {
+    config: TouchpadConfig,
+    touch: MtTracker,
+    buttonpad: bool,
+    contacts: Vec<Contact>,
+    /// The changes of the physical buttons since the last frame.
+    buttons: Vec<(KeyCode, bool)>,
+    /// Which of the physical buttons in `TAP_BUTTONS` are down.
+    buttons_down: [bool; 3],
+    /// The button a click of the clickpad pressed.
+    clicked: Option<KeyCode>,
+    tap: Option<Tap>,
+    motion_remainder: (f64, f64),
+    scroll_remainder: (f64, f64),
+    /// The `REL_*_HI_RES` units scrolled since the last full notch.
+    notch_remainder: (i32, i32),
+    time: libc::timeval,
+    events: Vec<InputEvent>,
+}
+
+impl TouchpadConverter {
+    /// Creates a converter for `device` with the default configuration.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] if the device doesn't have the
+    /// `ABS_MT_POSITION_X` and `ABS_MT_POSITION_Y` axes.
+    pub fn new(device: &Device) -> io::Result<Self> {
+        Self::with_config(device, TouchpadConfig::default())
+    }
+
+    /// Creates a converter for `device` with the given configuration.
+    ///
+    /// Software buttons are used if the device has the [`BUTTONPAD`](PropType::BUTTONPAD)
+    /// property, and the physical buttons are passed through otherwise.
+    pub fn with_config(device: &Device, config: TouchpadConfig) -> io::Result<Self> {
+        let buttonpad = device.properties().contains(PropType::BUTTONPAD);
+        Ok(Self::from_tracker(
+            MtTracker::new(device)?,
+            buttonpad,
+            config,
+        ))
+    }
+
+    /// Creates a converter for a touchpad with the given position axes and number of slots.
+    #[cfg(test)]
+    pub(crate) fn from_axes(
+        x: crate::AbsInfo,
+        y: crate::AbsInfo,
+        num_slots: usize,
+        buttonpad: bool,
+        config: TouchpadConfig,
+    ) -> Self {
+        Self::from_tracker(MtTracker::from_axes(x, y, num_slots), buttonpad, config)
+    }
+
+    fn from_tracker(touch: MtTracker, buttonpad: bool, config: TouchpadConfig) -> Self {
+        let num_slots = touch.slots().num_slots();
+        TouchpadConverter {
+            config,
+            touch,
+            buttonpad,
+            contacts: vec![Contact::default(); num_slots],
+            buttons: Vec::new(),
+            buttons_down: [false; 3],
+            clicked: None,
+            tap: None,
+            motion_remainder: (0.0, 0.0),
+            scroll_remainder: (0.0, 0.0),
+            notch_remainder: (0, 0),
+            time: libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns the keys the converter emits, to set up the virtual device with.
+    pub fn keys() -> AttributeSet<KeyCode> {
+        AttributeSet::from_iter(TAP_BUTTONS)
+    }
+
+    /// Returns the relative axes the converter emits, to set up the virtual device with.
+    pub fn relative_axes() -> AttributeSet<RelativeAxisCode> {
+        AttributeSet::from_iter([
+            RelativeAxisCode::REL_X,
+            RelativeAxisCode::REL_Y,
+            RelativeAxisCode::REL_WHEEL,
+            RelativeAxisCode::REL_HWHEEL,
+            RelativeAxisCode::REL_WHEEL_HI_RES,
+            RelativeAxisCode::REL_HWHEEL_HI_RES,
+        ])
+    }
+
+    /// Returns `true` if the touchpad is a clickpad with software buttons.
+    pub fn is_clickpad(&self) -> bool {
+        self.buttonpad
+    }
+
+    /// Processes the next frame of the touchpad and returns the mouse events it amounts to.
+    ///
+    /// The events don't include the final `SYN_REPORT`, which [`VirtualDevice::emit`] adds.
+    ///
+    /// [`VirtualDevice::emit`]: crate::uinput::VirtualDevice::emit
+    pub fn process_frame(&mut self, frame: Frame<'_>) -> impl Iterator<Item = InputEvent> + '_ {
+        for ev in frame {
+            self.apply(ev);
+        }
+        self.time = frame.time();
+        self.update(frame.clock_time());
+        self.events.drain(..)
+    }
+
+    fn apply(&mut self, ev: InputEvent) {
+        self.touch.apply(ev);
+        if ev.event_type() != EventType::KEY {
+            return;
+        }
+        let key = KeyCode(ev.code());
+        if let Some(i) = TAP_BUTTONS.iter().position(|&k| k == key) {
+            if ev.value() != 2 {
+                self.buttons_down[i] = ev.value() != 0;
+                self.buttons.push((key, ev.value() != 0));
+            }
+        }
+    }
+
+    fn in_edge_zone(&self, x: f64) -> bool {
+        x < self.config.edge_width || x > self.touch.x.size - self.config.edge_width
+    }
+
+    fn in_button_area(&self, y: f64) -> bool {
+        self.buttonpad && y > self.touch.y.size - self.config.button_area_height
+    }
+
+    fn update(&mut self, now: Duration) {
+        // classify the contacts and measure how far the fingers moved
+        let mut moved = Vec::new();
+        for i in 0..self.contacts.len() {
+            let contact = self.contacts[i];
+            let tracking_id = self.touch.slots().tracking_id(i);
+            if tracking_id.is_none() {
+                self.contacts[i] = Contact::default();
+                continue;
+            }
+            let pos = self.touch.position(i);
+            let palm = self.touch.get(i, AbsoluteAxisCode::ABS_MT_TOOL_TYPE) == MT_TOOL_PALM
+                || self.touch.get(i, AbsoluteAxisCode::ABS_MT_TOUCH_MAJOR) as f64
+                    / self.touch.x.units_per_mm
+                    >= self.config.palm_size;
+            let mut updated = contact;
+            if tracking_id != contact.tracking_id {
+                updated.tracking_id = tracking_id;
+                updated.start = pos;
+                updated.start_time = now;
+                updated.palm = palm;
+                updated.edge = self.in_edge_zone(pos.0);
+                updated.thumb = self.in_button_area(pos.1);
+            } else {
+                // once a palm, always a palm
+                updated.palm |= palm;
+                if updated.edge
+                    && !self.in_edge_zone(pos.0)
+                    && now <= contact.start_time + EDGE_TIMEOUT
+                {
+                    updated.edge = false;
+                }
+                if updated.thumb && !self.in_button_area(pos.1) {
+                    updated.thumb = false;
+                }
+                if contact.is_finger() && updated.is_finger() {
+                    moved.push((pos.0 - contact.last.0, pos.1 - contact.last.1));
+                }
+            }
+            updated.last = pos;
+            self.contacts[i] = updated;
+        }
+
+        let fingers = self.contacts.iter().filter(|c| c.is_finger());
+        let tap_moved = fingers
+            .clone()
+            .any(|c| (c.last.0 - c.start.0).hypot(c.last.1 - c.start.1) > self.config.tap_distance);
+        let fingers = fingers.count();
+        let tracked = self.contacts.iter().filter(|c| c.is_active()).count();
+        let tools = self.touch.tool_fingers();
+        // touchpads that track fewer contacts than they detect report the rest as tools
+        let tap_fingers = fingers + tools.saturating_sub(tracked);
+
+        // clicking, or fingers that touch while a button is held, don't make a tap
+        let mut clicked = self.buttons_down.contains(&true);
+        for (button, down) in std::mem::take(&mut self.buttons) {
+            clicked |= down;
+            self.button(button, down);
+        }
+
+        match fingers {
+            1 if moved.len() == 1 => self.motion(moved[0]),
+            2 if moved.len() == 2 => self.scroll((
+                (moved[0].0 + moved[1].0) / 2.0,
+                (moved[0].1 + moved[1].1) / 2.0,
+            )),
+            _ => {}
+        }
+        if fingers != 1 {
+            self.motion_remainder = (0.0, 0.0);
+        }
+        if fingers != 2 {
+            self.scroll_remainder = (0.0, 0.0);
+            self.notch_remainder = (0, 0);
+        }
+
+        self.tap = match self.tap {
+            None if tap_fingers == 0 => None,
+            None => Some(Tap {
+                since: now,
+                max_fingers: tap_fingers,
+                moved: tap_moved || clicked,
+            }),
+            Some(tap) if tap_fingers == 0 => {
+                if self.config.tap_to_click && !tap.moved && now <= tap.since + self.config.tap_time
+                {
+                    if let Some(&button) = TAP_BUTTONS.get(tap.max_fingers - 1) {
+                        self.push(EventType::KEY, button.0, 1);
+                        self.push(
+                            EventType::SYNCHRONIZATION,
+                            SynchronizationCode::SYN_REPORT.0,
+                            0,
+                        );
+                        self.push(EventType::KEY, button.0, 0);
+                    }
+                }
+                None
+            }
+            Some(tap) => Some(Tap {
+                since: tap.since,
+                max_fingers: tap.max_fingers.max(tap_fingers),
+                moved: tap.moved || tap_moved || clicked,
+            }),
+        };
+    }
+
+    /// Passes on a physical button, or presses the software button under the fingers.
+    fn button(&mut self, button: KeyCode, down: bool) {
+        if !self.buttonpad {
+            self.push(EventType::KEY, button.0, down as i32);
+            return;
+        }
+        if button != KeyCode::BTN_LEFT {
+            return;
+        }
+        if down {
+            let right_half = self.touch.x.size / 2.0;
+            let soft_button = self
+                .contacts
+                .iter()
+                .filter(|c| c.is_active() && self.in_button_area(c.last.1))
+                .map(|c| {
+                    if c.last.0 > right_half {
+                        KeyCode::BTN_RIGHT
+                    } else {
+                        KeyCode::BTN_LEFT
+                    }
+                })
+                .next()
+                .unwrap_or(KeyCode::BTN_LEFT);
+            if let Some(old) = self.clicked.replace(soft_button) {
+                self.push(EventType::KEY, old.0, 0);
+            }
+            self.push(EventType::KEY, soft_button.0, 1);
+        } else if let Some(soft_button) = self.clicked.take() {
+            self.push(EventType::KEY, soft_button.0, 0);
+        }
+    }
+
+    fn motion(&mut self, (dx, dy): (f64, f64)) {
+        let units_per_mm = self.config.dpi / 25.4;
+        let x = dx * units_per_mm + self.motion_remainder.0;
+        let y = dy * units_per_mm + self.motion_remainder.1;
+        let (ox, oy) = (x.trunc(), y.trunc());
+        self.motion_remainder = (x - ox, y - oy);
+        if ox != 0.0 {
+            self.push(EventType::RELATIVE, RelativeAxisCode::REL_X.0, ox as i32);
+        }
+        if oy != 0.0 {
+            self.push(EventType::RELATIVE, RelativeAxisCode::REL_Y.0, oy as i32);
+        }
+    }
+
+    fn scroll(&mut self, (dx, dy): (f64, f64)) {
+        let units_per_mm = HI_RES_PER_NOTCH as f64 / self.config.scroll_distance;
+        // moving the fingers down scrolls down, which is a negative wheel value
+        let direction = if self.config.natural_scroll {
+            -1.0
+        } else {
+            1.0
+        };
+        let h = dx * units_per_mm * direction + self.scroll_remainder.0;
+        let v = -dy * units_per_mm * direction + self.scroll_remainder.1;
+        let (oh, ov) = (h.trunc(), v.trunc());
+        self.scroll_remainder = (h - oh, v - ov);
+        let (oh, ov) = (oh as i32, ov as i32);
+        self.notch_remainder.0 += oh;
+        self.notch_remainder.1 += ov;
+        let notches = (
+            self.notch_remainder.0 / HI_RES_PER_NOTCH,
+            self.notch_remainder.1 / HI_RES_PER_NOTCH,
+        );
+        self.notch_remainder.0 -= notches.0 * HI_RES_PER_NOTCH;
+        self.notch_remainder.1 -= notches.1 * HI_RES_PER_NOTCH;
+
+        for (code, value) in [
+            (RelativeAxisCode::REL_WHEEL, notches.1),
+            (RelativeAxisCode::REL_WHEEL_HI_RES, ov),
+            (RelativeAxisCode::REL_HWHEEL, notches.0),
+            (RelativeAxisCode::REL_HWHEEL_HI_RES, oh),
+        ] {
+            if value != 0 {
+                self.push(EventType::RELATIVE, code.0, value);
+            }
+        }
+    }
+
+    fn push(&mut self, type_: EventType, code: u16, value: i32) {
+        self.events.push(InputEvent(input_event {
+            time: self.time,
+            type_: type_.0,
+            code,
+            value,
+        }));
+    }
+}
@@ -6,8 +6,9 @@ use crate::compat::{input_event, input_id, uinput_abs_setup, uinput_setup, UINPU
 use crate::ff::FFEffectData;
 use crate::inputid::{BusType, InputId};
 use crate::{
-    sys, AttributeSetRef, FFEffectCode, InputEvent, KeyCode, MiscCode, PropType, RelativeAxisCode,
-    SwitchCode, SynchronizationEvent, UInputCode, UInputEvent, UinputAbsSetup,
+    sys, AttributeSetRef, FFEffectCode, InputEvent, KeyCode, LedCode, MiscCode, PropType,
+    RelativeAxisCode, SoundCode, SwitchCode, SynchronizationEvent, UInputCode, UInputEvent,
+    UinputAbsSetup,
 };
 use std::ffi::{CStr, OsStr};
 use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
@@ -16,7 +17,7 @@ use std::path::{Path, PathBuf};
 use std::{fs, io};
 
 const UINPUT_PATH: &str = "/dev/uinput";
-const SYSFS_PATH: &str = "/sys/devices/virtual/input";
+const SYSFS_PATH: &str = "devices/virtual/input";
 const DEV_PATH: &str = "/dev/input";
 
 /// A builder struct for creating a new uinput virtual device.
@@ -166,6 +167,48 @@ impl<'a> VirtualDeviceBuilder<'a> {
         Ok(self)
     }
 
+    /// Set the LEDs of this device.
+    pub fn with_leds(self, leds: &AttributeSetRef<LedCode>) -> io::Result<Self> {
+        unsafe {
+            sys::ui_set_evbit(
+                self.fd.as_raw_fd(),
+                crate::EventType::LED.0 as nix::sys::ioctl::ioctl_param_type,
+            )?;
+        }
+
+        for bit in leds.iter() {
+            unsafe {
+                sys::ui_set_ledbit(
+                    self.fd.as_raw_fd(),
+                    bit.0 as nix::sys::ioctl::ioctl_param_type,
+                )?;
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Set the sounds that can be played by this device.
+    pub fn with_sounds(self, sounds: &AttributeSetRef<SoundCode>) -> io::Result<Self> {
+        unsafe {
+            sys::ui_set_evbit(
+                self.fd.as_raw_fd(),
+                crate::EventType::SOUND.0 as nix::sys::ioctl::ioctl_param_type,
+            )?;
+        }
+
+        for bit in sounds.iter() {
+            unsafe {
+                sys::ui_set_sndbit(
+                    self.fd.as_raw_fd(),
+                    bit.0 as nix::sys::ioctl::ioctl_param_type,
+                )?;
+            }
+        }
+
+        Ok(self)
+    }
+
     /// Set the force-feedback effects that can be emitted by this device.
     pub fn with_ff(self, ff: &AttributeSetRef<FFEffectCode>) -> io::Result<Self> {
         unsafe {
@@ -288,7 +331,7 @@ impl VirtualDevice {
 
         let syspath = OsStr::from_bytes(&syspath);
 
-        Ok(Path::new(SYSFS_PATH).join(syspath))
+        Ok(Path::new(crate::SYSFS_ROOT).join(SYSFS_PATH).join(syspath))
     }
 
     /// Get the syspaths of the corresponding device nodes in /dev/input.
@@ -562,12 +605,63 @@ impl Drop for FFEraseEvent {
     }
 }
 
+#[cfg(any(feature = "tokio", feature = "async-io"))]
+mod stream_core {
+    use super::*;
+
+    use crate::async_stream::AsyncSource;
+    use std::task::{ready, Context, Poll};
+
+    /// The runtime-independent part of the virtual device event streams.
+    pub(super) struct VirtualEventStreamCore<S> {
+        device: S,
+        index: usize,
+    }
+
+    impl<S: AsyncSource<Device = VirtualDevice>> VirtualEventStreamCore<S> {
+        pub(super) fn new(device: VirtualDevice) -> io::Result<Self> {
+            Ok(Self {
+                device: S::new(device)?,
+                index: 0,
+            })
+        }
+
+        pub(super) fn device(&self) -> &VirtualDevice {
+            self.device.get_ref()
+        }
+
+        pub(super) fn device_mut(&mut self) -> &mut VirtualDevice {
+            self.device.get_mut()
+        }
+
+        /// Yields the events written to the device, including the force feedback requests that
+        /// are to be answered with [`VirtualDevice::process_ff_upload`] and
+        /// [`VirtualDevice::process_ff_erase`].
+        pub(super) fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<InputEvent>> {
+            loop {
+                if let Some(&ev) = self.device.get_ref().event_buf.get(self.index) {
+                    self.index += 1;
+                    return Poll::Ready(Ok(InputEvent::from(ev)));
+                }
+
+                self.device.get_mut().event_buf.clear();
+                self.index = 0;
+
+                ready!(self
+                    .device
+                    .poll_read_with(cx, |device| device.fill_events()))?;
+            }
+        }
+    }
+}
+
 #[cfg(feature = "tokio")]
 mod tokio_stream {
+    use super::stream_core::VirtualEventStreamCore;
     use super::*;
 
     use std::future::poll_fn;
-    use std::task::{ready, Context, Poll};
+    use std::task::{Context, Poll};
     use tokio::io::unix::AsyncFd;
 
     /// An asynchronous stream of input events.
@@ -577,27 +671,25 @@ mod tokio_stream {
     /// There's also a lower-level [`Self::poll_event`] function if you need to fetch an event from
     /// inside a `Future::poll` impl.
     pub struct VirtualEventStream {
-        device: AsyncFd<VirtualDevice>,
-        index: usize,
+        inner: VirtualEventStreamCore<AsyncFd<VirtualDevice>>,
     }
     impl Unpin for VirtualEventStream {}
 
     impl VirtualEventStream {
         pub(crate) fn new(device: VirtualDevice) -> io::Result<Self> {
-            use nix::fcntl;
-            fcntl::fcntl(device.as_raw_fd(), fcntl::F_SETFL(fcntl::OFlag::O_NONBLOCK))?;
-            let device = AsyncFd::new(device)?;
-            Ok(Self { device, index: 0 })
+            Ok(Self {
+                inner: VirtualEventStreamCore::new(device)?,
+            })
         }
 
         /// Returns a reference to the underlying device
         pub fn device(&self) -> &VirtualDevice {
-            self.device.get_ref()
+            self.inner.device()
         }
 
         /// Returns a mutable reference to the underlying device.
         pub fn device_mut(&mut self) -> &mut VirtualDevice {
-            self.device.get_mut()
+            self.inner.device_mut()
         }
 
         /// Try to wait for the next event in this stream. Any errors are likely to be fatal, i.e.
@@ -608,28 +700,7 @@ mod tokio_stream {
 
         /// A lower-level function for directly polling this stream.
         pub fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<InputEvent>> {
-            'outer: loop {
-                if let Some(&ev) = self.device.get_ref().event_buf.get(self.index) {
-                    self.index += 1;
-                    return Poll::Ready(Ok(InputEvent::from(ev)));
-                }
-
-                self.device.get_mut().event_buf.clear();
-                self.index = 0;
-
-                loop {
-                    let mut guard = ready!(self.device.poll_read_ready_mut(cx))?;
-
-                    let res = guard.try_io(|device| device.get_mut().fill_events());
-                    match res {
-                        Ok(res) => {
-                            let _ = res?;
-                            continue 'outer;
-                        }
-                        Err(_would_block) => continue,
-                    }
-                }
-            }
+            self.inner.poll_event(cx)
         }
     }
 
@@ -646,3 +717,65 @@ mod tokio_stream {
 }
 #[cfg(feature = "tokio")]
 pub use tokio_stream::VirtualEventStream;
+
+#[cfg(feature = "async-io")]
+pub(crate) mod async_io_stream {
+    use super::stream_core::VirtualEventStreamCore;
+    use super::*;
+
+    use crate::async_stream::AsyncIo;
+    use std::future::poll_fn;
+    use std::task::{Context, Poll};
+
+    /// An asynchronous stream of the events written to a virtual device, driven by the
+    /// `async-io` reactor.
+    ///
+    /// This can be used by calling [`stream.next_event().await?`](Self::next_event), or if you
+    /// need to pass it as a stream somewhere, the [`futures::Stream`](futures_core::Stream)
+    /// implementation. There's also a lower-level [`Self::poll_event`] function if you need to
+    /// fetch an event from inside a `Future::poll` impl.
+    pub struct VirtualEventStream {
+        inner: VirtualEventStreamCore<AsyncIo<VirtualDevice>>,
+    }
+    impl Unpin for VirtualEventStream {}
+
+    impl VirtualEventStream {
+        /// Puts `device` into nonblocking mode and registers it with the reactor.
+        pub fn new(device: VirtualDevice) -> io::Result<Self> {
+            Ok(Self {
+                inner: VirtualEventStreamCore::new(device)?,
+            })
+        }
+
+        /// Returns a reference to the underlying device
+        pub fn device(&self) -> &VirtualDevice {
+            self.inner.device()
+        }
+
+        /// Returns a mutable reference to the underlying device.
+        pub fn device_mut(&mut self) -> &mut VirtualDevice {
+            self.inner.device_mut()
+        }
+
+        /// Try to wait for the next event in this stream. Any errors are likely to be fatal, i.e.
+        /// any calls afterwards will likely error as well.
+        pub async fn next_event(&mut self) -> io::Result<InputEvent> {
+            poll_fn(|cx| self.poll_event(cx)).await
+        }
+
+        /// A lower-level function for directly polling this stream.
+        pub fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<InputEvent>> {
+            self.inner.poll_event(cx)
+        }
+    }
+
+    impl futures_core::Stream for VirtualEventStream {
+        type Item = io::Result<InputEvent>;
+        fn poll_next(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Option<Self::Item>> {
+            self.get_mut().poll_event(cx).map(Some)
+        }
+    }
+}
@@ -0,0 +1,55 @@
+#![cfg(all(feature = "device-test", feature = "async-io"))]
+
+mod common;
+
+use common::{get_test_device, key_click, key_event};
+use evdev::async_io::{EventStream, VirtualEventStream};
+use evdev::{EventType, InputEvent, KeyCode, LedCode};
+
+#[test]
+pub fn test_async_io_events() -> std::io::Result<()> {
+    let (input, mut output) = get_test_device()?;
+    let mut events = EventStream::new(input)?;
+
+    output.emit(&[key_event(KeyCode::KEY_A, 1)])?;
+    output.emit(&key_click(KeyCode::KEY_B))?;
+    async_io::block_on(async {
+        let ev = events.next_event().await?;
+        assert_eq!(EventType::KEY, ev.event_type());
+        assert_eq!(KeyCode::KEY_A.code(), ev.code());
+        assert_eq!(
+            EventType::SYNCHRONIZATION,
+            events.next_event().await?.event_type()
+        );
+
+        // the device state follows the stream
+        let state = events.device().cached_state();
+        assert!(state.key_vals().unwrap().contains(KeyCode::KEY_A));
+        assert!(!state.key_vals().unwrap().contains(KeyCode::KEY_B));
+
+        let frame = events.next_frame().await?;
+        assert!(!frame.is_synthetic());
+        let values: Vec<_> = frame.iter().map(|ev| (ev.code(), ev.value())).collect();
+        assert_eq!(
+            values,
+            [(KeyCode::KEY_B.code(), 1), (KeyCode::KEY_B.code(), 0)]
+        );
+        Ok(())
+    })
+}
+
+#[test]
+pub fn test_async_io_virtual_events() -> std::io::Result<()> {
+    let (mut input, output) = get_test_device()?;
+    let mut requests = VirtualEventStream::new(output)?;
+
+    let led = InputEvent::new(EventType::LED.0, LedCode::LED_CAPSL.0, 1);
+    input.send_events(&[led])?;
+    async_io::block_on(async {
+        let ev = requests.next_event().await?;
+        assert_eq!(EventType::LED, ev.event_type());
+        assert_eq!(LedCode::LED_CAPSL.0, ev.code());
+        assert_eq!(1, ev.value());
+        Ok(())
+    })
+}
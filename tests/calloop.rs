@@ -0,0 +1,79 @@
+#![cfg(all(feature = "device-test", feature = "calloop"))]
+
+mod common;
+
+use calloop::EventLoop;
+use common::{get_test_device, key_click, key_event};
+use evdev::calloop::{DeviceSource, FrameSource};
+use evdev::{EventType, InputEvent, KeyCode};
+use std::time::Duration;
+
+const TIMEOUT: Option<Duration> = Some(Duration::from_secs(1));
+
+#[test]
+pub fn test_calloop_device_source() -> Result<(), Box<dyn std::error::Error>> {
+    let (input, mut output) = get_test_device()?;
+
+    let mut event_loop = EventLoop::<Vec<InputEvent>>::try_new()?;
+    event_loop.handle().insert_source(
+        DeviceSource::new(input)?,
+        |event, device, received: &mut Vec<InputEvent>| {
+            let event = event.unwrap();
+            if event.event_type() == EventType::KEY {
+                // the cached state includes the delivered event
+                let pressed = device
+                    .cached_state()
+                    .key_vals()
+                    .unwrap()
+                    .contains(KeyCode::new(event.code()));
+                assert_eq!(event.value() == 1, pressed);
+            }
+            received.push(event);
+        },
+    )?;
+
+    output.emit(&[key_event(KeyCode::KEY_A, 1)])?;
+    let mut received = Vec::new();
+    event_loop.dispatch(TIMEOUT, &mut received)?;
+    let keys: Vec<_> = received
+        .iter()
+        .filter(|ev| ev.event_type() == EventType::KEY)
+        .map(|ev| (ev.code(), ev.value()))
+        .collect();
+    assert_eq!(keys, [(KeyCode::KEY_A.code(), 1)]);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_calloop_frame_source() -> Result<(), Box<dyn std::error::Error>> {
+    let (input, mut output) = get_test_device()?;
+
+    let mut event_loop = EventLoop::<Vec<Vec<InputEvent>>>::try_new()?;
+    event_loop.handle().insert_source(
+        FrameSource::new(input)?,
+        |frame, _device, received: &mut Vec<Vec<InputEvent>>| {
+            let frame = frame.unwrap();
+            assert!(!frame.is_synthetic());
+            received.push(frame.into_events());
+        },
+    )?;
+
+    output.emit(&key_click(KeyCode::KEY_B))?;
+    output.emit(&[key_event(KeyCode::KEY_C, 1)])?;
+    let mut received = Vec::new();
+    event_loop.dispatch(TIMEOUT, &mut received)?;
+    let codes: Vec<Vec<_>> = received
+        .iter()
+        .map(|frame| frame.iter().map(|ev| ev.code()).collect())
+        .collect();
+    assert_eq!(
+        codes,
+        [
+            vec![KeyCode::KEY_B.code(), KeyCode::KEY_B.code()],
+            vec![KeyCode::KEY_C.code()]
+        ]
+    );
+
+    Ok(())
+}
@@ -1,7 +1,10 @@
 #![allow(dead_code)]
 
 use evdev::uinput::VirtualDevice;
-use evdev::{AttributeSet, BusType, Device, EventType, InputEvent, InputId, KeyCode, SwitchCode};
+use evdev::{
+    AbsInfo, AbsoluteAxisCode, AttributeSet, BusType, Device, EventType, InputEvent, InputId,
+    KeyCode, LedCode, SoundCode, SwitchCode, UinputAbsSetup,
+};
 use std::path::PathBuf;
 use std::thread;
 use std::time::Duration;
@@ -15,7 +18,13 @@ pub fn key_event(key: KeyCode, key_state: i32) -> InputEvent {
 }
 
 pub fn get_test_device() -> std::io::Result<(Device, VirtualDevice)> {
-    let (name, output) = get_device()?;
+    get_named_test_device("test device")
+}
+
+/// Like [`get_test_device`], for tests that look the device up by its name, which must not be
+/// shared with the devices of other tests running at the same time.
+pub fn get_named_test_device(name: &str) -> std::io::Result<(Device, VirtualDevice)> {
+    let (name, output) = get_named_device(name)?;
 
     let mut input = Device::open(&name)?;
 
@@ -25,6 +34,10 @@ pub fn get_test_device() -> std::io::Result<(Device, VirtualDevice)> {
 }
 
 pub fn get_device() -> std::io::Result<(PathBuf, VirtualDevice)> {
+    get_named_device("test device")
+}
+
+pub fn get_named_device(name: &str) -> std::io::Result<(PathBuf, VirtualDevice)> {
     let mut keys: AttributeSet<KeyCode> = AttributeSet::new();
     for code in 1..59 {
         let key = KeyCode::new(code);
@@ -39,11 +52,20 @@ pub fn get_device() -> std::io::Result<(PathBuf, VirtualDevice)> {
     sw.insert(SwitchCode::SW_LID);
     sw.insert(SwitchCode::SW_TABLET_MODE);
 
+    let mut leds: AttributeSet<LedCode> = AttributeSet::new();
+    leds.insert(LedCode::LED_NUML);
+    leds.insert(LedCode::LED_CAPSL);
+
+    let mut snd: AttributeSet<SoundCode> = AttributeSet::new();
+    snd.insert(SoundCode::SND_BELL);
+
     let mut device = VirtualDevice::builder()?
         .input_id(InputId::new(BusType::BUS_USB, 0x1234, 0x5678, 0x111))
-        .name("test device")
+        .name(name)
         .with_keys(&keys)?
         .with_switches(&sw)?
+        .with_leds(&leds)?
+        .with_sounds(&snd)?
         .build()?;
 
     // Fetch name.
@@ -57,6 +79,76 @@ pub fn get_device() -> std::io::Result<(PathBuf, VirtualDevice)> {
     Ok((d.first().unwrap().clone(), device))
 }
 
+pub fn abs_event(axis: AbsoluteAxisCode, value: i32) -> InputEvent {
+    InputEvent::new(EventType::ABSOLUTE.0, axis.0, value)
+}
+
+/// A Type B multi-touch device with two slots.
+pub fn get_mt_test_device() -> std::io::Result<(Device, VirtualDevice)> {
+    let mut keys: AttributeSet<KeyCode> = AttributeSet::new();
+    keys.insert(KeyCode::BTN_TOUCH);
+
+    let mut builder = VirtualDevice::builder()?
+        .input_id(InputId::new(BusType::BUS_USB, 0x1234, 0x5679, 0x111))
+        .name("test touch device")
+        .with_keys(&keys)?;
+    for (axis, max) in [
+        (AbsoluteAxisCode::ABS_X, 1000),
+        (AbsoluteAxisCode::ABS_Y, 1000),
+        (AbsoluteAxisCode::ABS_MT_SLOT, 1),
+        (AbsoluteAxisCode::ABS_MT_TRACKING_ID, 0xffff),
+        (AbsoluteAxisCode::ABS_MT_POSITION_X, 1000),
+        (AbsoluteAxisCode::ABS_MT_POSITION_Y, 1000),
+    ] {
+        let info = AbsInfo::new(0, 0, max, 0, 0, 0);
+        builder = builder.with_absolute_axis(&UinputAbsSetup::new(axis, info))?;
+    }
+    let mut device = builder.build()?;
+
+    let path = device.enumerate_dev_nodes_blocking()?.next().unwrap()?;
+
+    thread::sleep(Duration::from_millis(100)); // To avoid permission denied.
+
+    let mut input = Device::open(path)?;
+    input.grab()?;
+
+    Ok((input, device))
+}
+
+/// A pen tablet with pressure.
+pub fn get_tablet_test_device() -> std::io::Result<(Device, VirtualDevice)> {
+    let keys: AttributeSet<KeyCode> = [
+        KeyCode::BTN_TOOL_PEN,
+        KeyCode::BTN_TOUCH,
+        KeyCode::BTN_STYLUS,
+    ]
+    .into_iter()
+    .collect();
+
+    let mut builder = VirtualDevice::builder()?
+        .input_id(InputId::new(BusType::BUS_USB, 0x1234, 0x567a, 0x111))
+        .name("test tablet device")
+        .with_keys(&keys)?;
+    for (axis, max) in [
+        (AbsoluteAxisCode::ABS_X, 1000),
+        (AbsoluteAxisCode::ABS_Y, 1000),
+        (AbsoluteAxisCode::ABS_PRESSURE, 100),
+    ] {
+        let info = AbsInfo::new(0, 0, max, 0, 0, 0);
+        builder = builder.with_absolute_axis(&UinputAbsSetup::new(axis, info))?;
+    }
+    let mut device = builder.build()?;
+
+    let path = device.enumerate_dev_nodes_blocking()?.next().unwrap()?;
+
+    thread::sleep(Duration::from_millis(100)); // To avoid permission denied.
+
+    let mut input = Device::open(path)?;
+    input.grab()?;
+
+    Ok((input, device))
+}
+
 pub fn final_dot_state(start_state: i32, events: impl Iterator<Item = InputEvent>) -> i32 {
     events.fold(start_state, |state, ev| {
         if ev.event_type() == EventType::KEY && ev.code() == KeyCode::KEY_DOT.code() {
@@ -71,7 +163,7 @@ pub fn final_dot_state(start_state: i32, events: impl Iterator<Item = InputEvent
     })
 }
 
-pub fn final_event_state(key: KeyCode, events: &Vec<InputEvent>) -> Option<i32> {
+pub fn final_event_state(key: KeyCode, events: &[InputEvent]) -> Option<i32> {
     events.iter().fold(None, |state, ev| {
         if ev.event_type() == EventType::KEY && ev.code() == key.code() {
             if ev.value() == 0 {
@@ -84,3 +176,12 @@ pub fn final_event_state(key: KeyCode, events: &Vec<InputEvent>) -> Option<i32>
         }
     })
 }
+
+/// The last value of `(type_, code)` among `events`, if there is one.
+pub fn final_value(type_: EventType, code: u16, events: &[InputEvent]) -> Option<i32> {
+    events
+        .iter()
+        .rev()
+        .find(|ev| ev.event_type() == type_ && ev.code() == code)
+        .map(|ev| ev.value())
+}
@@ -2,8 +2,38 @@
 
 mod common;
 
-use common::{final_dot_state, final_event_state, get_test_device, key_click, key_event};
-use evdev::{EventType, InputEvent, KeyCode, SwitchCode};
+use common::{
+    abs_event, final_dot_state, final_event_state, final_value, get_mt_test_device,
+    get_test_device, key_click, key_event,
+};
+use evdev::uinput::VirtualDevice;
+use evdev::{
+    AbsoluteAxisCode, Device, EventMask, EventType, InputEvent, KeyCode, LedCode, LedEvent,
+    SoundCode, SoundEvent, SwitchCode, SwitchEvent, SynchronizationCode,
+};
+
+/// Overflows the kernel buffer, emits `events` and returns what a consumer sees after the
+/// resulting SYN_DROPPED.
+fn events_after_drop(
+    input: &mut Device,
+    output: &mut VirtualDevice,
+    events: &[InputEvent],
+) -> std::io::Result<Vec<InputEvent>> {
+    // Make overflow.
+    for _ in 0..30 {
+        output.emit(&key_click(KeyCode::KEY_DOT))?;
+    }
+    for ev in events {
+        output.emit(&[*ev])?;
+    }
+
+    input.fetch_events()?.for_each(drop);
+
+    // Something unrelated to get the next fetch going.
+    output.emit(&key_click(KeyCode::KEY_DOT))?;
+
+    Ok(input.fetch_events()?.collect())
+}
 
 #[test]
 pub fn test_compensate_keys() -> std::io::Result<()> {
@@ -49,15 +79,12 @@ pub fn test_compensate_with_key_down() -> std::io::Result<()> {
         output.emit(&key_click(KeyCode::KEY_DOT))?;
     }
 
-    assert_eq!(0, input.fetch_events()?.into_iter().count());
+    assert_eq!(0, input.fetch_events()?.count());
 
     // Press and release.
     output.emit(&key_click(KeyCode::KEY_DOT))?;
 
-    let events = input
-        .fetch_events()?
-        .into_iter()
-        .collect::<Vec<InputEvent>>();
+    let events = input.fetch_events()?.collect::<Vec<InputEvent>>();
 
     assert_eq!(Some(1), final_event_state(KeyCode::KEY_A, &events));
     assert_eq!(Some(1), final_event_state(KeyCode::KEY_B, &events));
@@ -95,3 +122,201 @@ pub fn test_compensate_with_switch_down() -> std::io::Result<()> {
 
     Ok(())
 }
+
+#[test]
+pub fn test_compensate_switches() -> std::io::Result<()> {
+    let (mut input, mut output) = get_test_device()?;
+
+    let events = events_after_drop(
+        &mut input,
+        &mut output,
+        &[
+            *SwitchEvent::new(SwitchCode::SW_LID, 1),
+            *SwitchEvent::new(SwitchCode::SW_TABLET_MODE, 1),
+            *SwitchEvent::new(SwitchCode::SW_TABLET_MODE, 0),
+        ],
+    )?;
+
+    assert_eq!(
+        Some(1),
+        final_value(EventType::SWITCH, SwitchCode::SW_LID.0, &events)
+    );
+    let switches = input.cached_state().switch_vals().unwrap();
+    assert!(switches.contains(SwitchCode::SW_LID));
+    assert!(!switches.contains(SwitchCode::SW_TABLET_MODE));
+
+    // The lid was already reported as closed, so it must not be compensated again.
+    let events = events_after_drop(&mut input, &mut output, &[])?;
+    assert_eq!(
+        None,
+        final_value(EventType::SWITCH, SwitchCode::SW_LID.0, &events)
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_compensate_respects_event_mask() -> std::io::Result<()> {
+    let (mut input, mut output) = get_test_device()?;
+
+    input.set_event_mask(&EventMask::all().without_type(EventType::SWITCH))?;
+
+    let events = events_after_drop(
+        &mut input,
+        &mut output,
+        &[*SwitchEvent::new(SwitchCode::SW_LID, 1)],
+    )?;
+
+    assert!(events.iter().all(|ev| ev.event_type() != EventType::SWITCH));
+    // The state itself is still synchronized.
+    let switches = input.cached_state().switch_vals().unwrap();
+    assert!(switches.contains(SwitchCode::SW_LID));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_compensate_leds() -> std::io::Result<()> {
+    let (mut input, mut output) = get_test_device()?;
+
+    let events = events_after_drop(
+        &mut input,
+        &mut output,
+        &[
+            *LedEvent::new(LedCode::LED_CAPSL, 1),
+            *LedEvent::new(LedCode::LED_NUML, 1),
+            *LedEvent::new(LedCode::LED_NUML, 0),
+        ],
+    )?;
+
+    assert_eq!(
+        Some(1),
+        final_value(EventType::LED, LedCode::LED_CAPSL.0, &events)
+    );
+    let leds = input.cached_state().led_vals().unwrap();
+    assert!(leds.contains(LedCode::LED_CAPSL));
+    assert!(!leds.contains(LedCode::LED_NUML));
+
+    let events = events_after_drop(&mut input, &mut output, &[])?;
+    assert_eq!(
+        None,
+        final_value(EventType::LED, LedCode::LED_CAPSL.0, &events)
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_compensate_sounds() -> std::io::Result<()> {
+    let (mut input, mut output) = get_test_device()?;
+
+    let events = events_after_drop(
+        &mut input,
+        &mut output,
+        &[*SoundEvent::new(SoundCode::SND_BELL, 1)],
+    )?;
+
+    assert_eq!(
+        Some(1),
+        final_value(EventType::SOUND, SoundCode::SND_BELL.0, &events)
+    );
+    assert!(input
+        .cached_state()
+        .snd_vals()
+        .unwrap()
+        .contains(SoundCode::SND_BELL));
+
+    let events = events_after_drop(
+        &mut input,
+        &mut output,
+        &[*SoundEvent::new(SoundCode::SND_BELL, 0)],
+    )?;
+    assert_eq!(
+        Some(0),
+        final_value(EventType::SOUND, SoundCode::SND_BELL.0, &events)
+    );
+    assert!(!input
+        .cached_state()
+        .snd_vals()
+        .unwrap()
+        .contains(SoundCode::SND_BELL));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_compensate_mt_slots() -> std::io::Result<()> {
+    let (mut input, mut output) = get_mt_test_device()?;
+
+    // Two contacts.
+    output.emit(&[
+        abs_event(AbsoluteAxisCode::ABS_MT_SLOT, 0),
+        abs_event(AbsoluteAxisCode::ABS_MT_TRACKING_ID, 1),
+        abs_event(AbsoluteAxisCode::ABS_MT_POSITION_X, 10),
+        abs_event(AbsoluteAxisCode::ABS_MT_SLOT, 1),
+        abs_event(AbsoluteAxisCode::ABS_MT_TRACKING_ID, 2),
+        abs_event(AbsoluteAxisCode::ABS_MT_POSITION_X, 20),
+    ])?;
+    input.fetch_events()?.for_each(drop);
+
+    let mt = input.cached_state().mt_vals().unwrap();
+    assert_eq!(2, mt.num_slots());
+    assert_eq!(Some(1), mt.tracking_id(0));
+    assert_eq!(Some(2), mt.tracking_id(1));
+    assert_eq!(1, mt.current_slot());
+
+    // Make overflow.
+    for x in 0..300 {
+        output.emit(&[abs_event(AbsoluteAxisCode::ABS_MT_POSITION_X, x)])?;
+    }
+    // Lift the first finger and replace the second one.
+    output.emit(&[
+        abs_event(AbsoluteAxisCode::ABS_MT_SLOT, 0),
+        abs_event(AbsoluteAxisCode::ABS_MT_TRACKING_ID, -1),
+        abs_event(AbsoluteAxisCode::ABS_MT_SLOT, 1),
+        abs_event(AbsoluteAxisCode::ABS_MT_TRACKING_ID, 3),
+        abs_event(AbsoluteAxisCode::ABS_MT_POSITION_X, 30),
+    ])?;
+
+    input.fetch_events()?.for_each(drop);
+
+    // Something unrelated to get the next fetch going.
+    output.emit(&[key_event(KeyCode::BTN_TOUCH, 1)])?;
+
+    let events = input.fetch_events()?.collect::<Vec<InputEvent>>();
+
+    let mt = input.cached_state().mt_vals().unwrap();
+    assert_eq!(None, mt.tracking_id(0));
+    assert_eq!(Some(3), mt.tracking_id(1));
+    assert_eq!(Some(30), mt.get(1, AbsoluteAxisCode::ABS_MT_POSITION_X));
+    assert_eq!(1, mt.current_slot());
+
+    // The replaced contact in slot 1 is ended in a frame of its own before the new one begins.
+    let first_report = events
+        .iter()
+        .position(|ev| {
+            ev.event_type() == EventType::SYNCHRONIZATION
+                && ev.code() == SynchronizationCode::SYN_REPORT.0
+        })
+        .unwrap();
+    let is_abs = |ev: &InputEvent, axis: AbsoluteAxisCode, value: i32| {
+        ev.event_type() == EventType::ABSOLUTE && ev.code() == axis.0 && ev.value() == value
+    };
+    assert!(events[..first_report].iter().any(|ev| is_abs(
+        ev,
+        AbsoluteAxisCode::ABS_MT_TRACKING_ID,
+        -1
+    )));
+    assert!(!events[..first_report].iter().any(|ev| is_abs(
+        ev,
+        AbsoluteAxisCode::ABS_MT_TRACKING_ID,
+        3
+    )));
+    assert!(events[first_report..].iter().any(|ev| is_abs(
+        ev,
+        AbsoluteAxisCode::ABS_MT_TRACKING_ID,
+        3
+    )));
+
+    Ok(())
+}
@@ -2,8 +2,20 @@
 
 mod common;
 
-use common::{get_test_device, key_event};
-use evdev::KeyCode;
+use common::{
+    abs_event, get_mt_test_device, get_named_test_device, get_tablet_test_device, get_test_device,
+    key_event,
+};
+use evdev::axis::AxisNormalizer;
+use evdev::gamepad::{Gamepad, GamepadButton, GamepadEvent, GamepadGuid, MappingDb};
+use evdev::gestures::{GestureEvent, GestureRecognizer};
+use evdev::tablet::{Tablet, ToolEvent, ToolType};
+use evdev::touchpad::TouchpadConverter;
+use evdev::{
+    AbsoluteAxisCode, AttributeSet, Clock, Device, DeviceClasses, DeviceFilter, DeviceIdentity,
+    EventMask, EventType, InputEvent, KeyCode, LedCode, ModifierState, RelativeAxisCode, SoundCode,
+};
+use std::time::{Duration, Instant};
 
 #[test]
 pub fn test_get_key_state() -> Result<(), Box<dyn std::error::Error>> {
@@ -23,3 +35,381 @@ pub fn test_get_key_state() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+pub fn test_fetch_frames() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut input, mut output) = get_test_device()?;
+
+    output.emit(&[key_event(KeyCode::KEY_DOT, 1)])?;
+    output.emit(&[key_event(KeyCode::KEY_DOT, 0)])?;
+
+    let mut frames = input.fetch_frames()?;
+    let mut values = vec![];
+    while let Some(frame) = frames.next_frame() {
+        assert!(!frame.is_synthetic());
+        assert_eq!(1, frame.len());
+        values.extend(frame.iter().map(|ev| ev.value()));
+    }
+    assert_eq!(values, [1, 0]);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_set_clock() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut input, mut output) = get_test_device()?;
+
+    input.set_clock(Clock::Monotonic)?;
+    assert_eq!(Clock::Monotonic, input.clock());
+
+    let before = Instant::now();
+    output.emit(&[key_event(KeyCode::KEY_DOT, 1)])?;
+    let after = Instant::now();
+
+    let event = input
+        .fetch_events()?
+        .find(|ev| ev.event_type() == EventType::KEY)
+        .unwrap();
+    let instant = Clock::Monotonic.to_instant(event.clock_time()).unwrap();
+    // the conversion isn't exact, so allow for some slack
+    let slack = Duration::from_millis(10);
+    assert!(before - slack <= instant && instant <= after + slack);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_revoke() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut input, mut output) = get_test_device()?;
+
+    input.revoke()?;
+    assert!(!input.is_grabbed());
+
+    output.emit(&[key_event(KeyCode::KEY_DOT, 1)])?;
+
+    let err = input.fetch_events().err().unwrap();
+    assert_eq!(Some(libc::ENODEV), err.raw_os_error());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_event_mask() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut input, mut output) = get_test_device()?;
+
+    let keys: AttributeSet<KeyCode> = [KeyCode::KEY_A].into_iter().collect();
+    input.set_event_mask(&EventMask::none().with_keys(&keys))?;
+
+    let mask = input.get_event_mask()?;
+    assert!(mask.types().contains(EventType::KEY));
+    assert!(!mask.types().contains(EventType::SWITCH));
+    assert_eq!(
+        mask.keys().unwrap().iter().collect::<Vec<_>>(),
+        [KeyCode::KEY_A]
+    );
+
+    output.emit(&[key_event(KeyCode::KEY_DOT, 1)])?;
+    output.emit(&[key_event(KeyCode::KEY_A, 1)])?;
+
+    let keys: Vec<_> = input
+        .fetch_events()?
+        .filter(|ev| ev.event_type() == EventType::KEY)
+        .map(|ev| ev.code())
+        .collect();
+    assert_eq!(keys, [KeyCode::KEY_A.code()]);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_sysfs_info() -> Result<(), Box<dyn std::error::Error>> {
+    let (input, mut output) = get_test_device()?;
+
+    let info = input.sysfs_info()?;
+    assert_eq!(info.input_syspath(), output.get_syspath()?.canonicalize()?);
+    assert!(info.modalias()?.starts_with("input:"));
+    assert_eq!(None, info.driver()?);
+    assert_eq!(None, info.usb_device());
+    assert!(info.capabilities_key()?.contains(KeyCode::KEY_DOT));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_classify() -> Result<(), Box<dyn std::error::Error>> {
+    let (input, _output) = get_test_device()?;
+
+    assert_eq!(
+        input.classify(),
+        DeviceClasses::KEY | DeviceClasses::KEYBOARD | DeviceClasses::SWITCH
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_enumerate_matching() -> Result<(), Box<dyn std::error::Error>> {
+    let (input, _output) = get_test_device()?;
+
+    let filter = DeviceFilter::new()
+        .name("test*")
+        .vendor(0x1234)
+        .product(0x5678)
+        .with_key(KeyCode::KEY_DOT)
+        .with_class(DeviceClasses::KEYBOARD);
+    assert!(filter.matches(&input));
+
+    let mut found = false;
+    for (_, device) in evdev::enumerate_matching(&filter) {
+        let device = device?;
+        assert!(filter.matches(&device));
+        found |= device.sysfs_info()?.syspath() == input.sysfs_info()?.syspath();
+    }
+    assert!(found);
+
+    let filter = filter.with_class(DeviceClasses::MOUSE);
+    assert_eq!(0, evdev::enumerate_matching(&filter).count());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_identity() -> Result<(), Box<dyn std::error::Error>> {
+    // other tests' devices would share the identity of the usual test device
+    let (input, _output) = get_named_test_device("test identity device")?;
+
+    let identity = input.identity()?;
+    assert_eq!(Some("test identity device"), identity.name());
+    assert_eq!(0x1234, identity.input_id().vendor());
+    // uinput devices have no hardware parent, so udev doesn't create links for them
+    assert_eq!(None, identity.by_path());
+
+    let syspath = input.sysfs_info()?.syspath().to_owned();
+    let mut found = false;
+    for path in identity.find_nodes()? {
+        let device = Device::open(&path)?;
+        assert_eq!(identity, device.identity()?);
+        assert_eq!(identity, DeviceIdentity::from_path(&path)?);
+        found |= device.sysfs_info()?.syspath() == syspath;
+    }
+    assert!(found);
+
+    let device = Device::open_by_identity(&identity)?;
+    assert_eq!(identity, device.identity()?);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_gesture_recognizer() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut input, mut output) = get_mt_test_device()?;
+    // the test device doesn't have a resolution, so it's taken to be 100 mm wide
+    let mut recognizer = GestureRecognizer::new(&input)?;
+
+    let touch = |slot, x| {
+        [
+            abs_event(AbsoluteAxisCode::ABS_MT_SLOT, slot),
+            abs_event(AbsoluteAxisCode::ABS_MT_POSITION_X, x),
+            abs_event(AbsoluteAxisCode::ABS_MT_POSITION_Y, 500),
+        ]
+    };
+    let mut events = vec![];
+    for slot in 0..2 {
+        events.push(abs_event(AbsoluteAxisCode::ABS_MT_SLOT, slot));
+        events.push(abs_event(AbsoluteAxisCode::ABS_MT_TRACKING_ID, slot));
+    }
+    events.extend(touch(0, 400));
+    events.extend(touch(1, 600));
+    output.emit(&events)?;
+    let mut moved = touch(0, 450).to_vec();
+    moved.extend(touch(1, 650));
+    output.emit(&moved)?;
+
+    let mut gestures = vec![];
+    let mut frames = input.fetch_frames()?;
+    while let Some(frame) = frames.next_frame() {
+        gestures.extend(recognizer.process_frame(frame));
+    }
+    assert_eq!(
+        gestures,
+        [
+            GestureEvent::SwipeBegin { fingers: 2 },
+            GestureEvent::SwipeUpdate {
+                fingers: 2,
+                dx: 5.0,
+                dy: 0.0
+            }
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_touchpad_converter() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut input, mut output) = get_mt_test_device()?;
+    let mut converter = TouchpadConverter::new(&input)?;
+    assert!(!converter.is_clickpad());
+
+    output.emit(&[
+        abs_event(AbsoluteAxisCode::ABS_MT_SLOT, 0),
+        abs_event(AbsoluteAxisCode::ABS_MT_TRACKING_ID, 1),
+        abs_event(AbsoluteAxisCode::ABS_MT_POSITION_X, 500),
+        abs_event(AbsoluteAxisCode::ABS_MT_POSITION_Y, 500),
+    ])?;
+    // 1 mm to the right
+    output.emit(&[abs_event(AbsoluteAxisCode::ABS_MT_POSITION_X, 510)])?;
+
+    let mut events = vec![];
+    let mut frames = input.fetch_frames()?;
+    while let Some(frame) = frames.next_frame() {
+        events.extend(converter.process_frame(frame));
+    }
+    let events: Vec<_> = events
+        .iter()
+        .map(|ev| (ev.event_type(), ev.code(), ev.value()))
+        .collect();
+    assert_eq!(
+        events,
+        [(EventType::RELATIVE, RelativeAxisCode::REL_X.0, 39)]
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_modifier_state_from_device() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut input, mut output) = get_test_device()?;
+    output.emit(&[key_event(KeyCode::KEY_LEFTSHIFT, 1)])?;
+    input.send_events(&[InputEvent::new(EventType::LED.0, LedCode::LED_CAPSL.0, 1)])?;
+    // wait for the key press to arrive
+    input.fetch_events()?.count();
+
+    let modifiers = ModifierState::from_device(&input)?;
+    assert!(modifiers.shift());
+    assert!(modifiers.caps_lock());
+    assert!(!modifiers.num_lock());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_axis_normalizer_from_device() -> Result<(), Box<dyn std::error::Error>> {
+    let (input, _output) = get_mt_test_device()?;
+
+    let axis = AxisNormalizer::from_device(&input, AbsoluteAxisCode::ABS_X)?;
+    assert_eq!(axis.info().maximum(), 1000);
+    assert_eq!(axis.normalize(750), 0.5);
+
+    let err = AxisNormalizer::from_device(&input, AbsoluteAxisCode::ABS_Z).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_gamepad_mapping() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut input, mut output) = get_test_device()?;
+    // not a gamepad, so it needs a mapping
+    assert!(Gamepad::new(&input, &MappingDb::new()).is_err());
+
+    // the first key is KEY_ESC
+    let guid = GamepadGuid::from_device(&input);
+    let db = MappingDb::parse(&format!("{guid},Keyboard pad,a:b0,platform:Linux"));
+    let mut gamepad = Gamepad::new(&input, &db)?;
+    assert_eq!(gamepad.name(), "Keyboard pad");
+
+    output.emit(&[key_event(KeyCode::KEY_ESC, 1)])?;
+    let mut events = vec![];
+    let mut frames = input.fetch_frames()?;
+    while let Some(frame) = frames.next_frame() {
+        events.extend(gamepad.process_frame(frame));
+    }
+    assert_eq!(
+        events,
+        [GamepadEvent::Button {
+            button: GamepadButton::South,
+            pressed: true
+        }]
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_tablet_tool_events() -> Result<(), Box<dyn std::error::Error>> {
+    let (input, _output) = get_test_device()?;
+    assert!(Tablet::new(&input).is_err());
+
+    let (mut input, mut output) = get_tablet_test_device()?;
+    let mut tablet = Tablet::new(&input)?;
+    assert_eq!(tablet.tool(), None);
+
+    output.emit(&[
+        abs_event(AbsoluteAxisCode::ABS_X, 100),
+        abs_event(AbsoluteAxisCode::ABS_Y, 200),
+        key_event(KeyCode::BTN_TOOL_PEN, 1),
+    ])?;
+    output.emit(&[
+        abs_event(AbsoluteAxisCode::ABS_PRESSURE, 50),
+        key_event(KeyCode::BTN_TOUCH, 1),
+    ])?;
+
+    let mut events = vec![];
+    let mut frames = input.fetch_frames()?;
+    while let Some(frame) = frames.next_frame() {
+        events.extend(tablet.process_frame(frame));
+    }
+    let pen = ToolType::Pen;
+    let axis = |pressure| ToolEvent::Axis {
+        tool: pen,
+        x: 100,
+        y: 200,
+        pressure: Some(pressure),
+        distance: None,
+        tilt_x: None,
+        tilt_y: None,
+    };
+    assert_eq!(
+        events,
+        [
+            ToolEvent::ProximityIn {
+                tool: pen,
+                serial: None,
+                tool_id: None
+            },
+            axis(0.0),
+            axis(0.5),
+            ToolEvent::Tip {
+                tool: pen,
+                down: true
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_set_leds() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut input, _output) = get_test_device()?;
+    assert!(input.set_led(LedCode::LED_SCROLLL, true).is_err());
+    assert!(input.beep(SoundCode::SND_TONE, 440).is_err());
+    input.beep(SoundCode::SND_BELL, 1)?;
+
+    input.set_led(LedCode::LED_NUML, true)?;
+    input.fetch_events()?.count();
+    assert!(input
+        .cached_state()
+        .led_vals()
+        .unwrap()
+        .contains(LedCode::LED_NUML));
+
+    input.set_leds(&AttributeSet::from_iter([LedCode::LED_CAPSL]))?;
+    input.fetch_events()?.count();
+    let leds = input.cached_state().led_vals().unwrap();
+    assert!(leds.contains(LedCode::LED_CAPSL));
+    assert!(!leds.contains(LedCode::LED_NUML));
+
+    Ok(())
+}
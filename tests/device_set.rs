@@ -0,0 +1,81 @@
+#![cfg(feature = "device-test")]
+
+mod common;
+
+use common::{get_test_device, key_click, key_event};
+use evdev::raw_stream::RawDevice;
+use evdev::{DeviceSet, EventType, KeyCode, SetEvent, SetFrame};
+use std::time::Duration;
+
+const TIMEOUT: Option<Duration> = Some(Duration::from_secs(1));
+
+#[test]
+pub fn test_device_set_events() -> std::io::Result<()> {
+    let (first, mut first_output) = get_test_device()?;
+    let (second, mut second_output) = get_test_device()?;
+
+    let mut set = DeviceSet::new()?;
+    let first = set.insert(first)?;
+    let second = set.insert(second)?;
+    assert_eq!(2, set.len());
+
+    assert_eq!(0, set.fetch_events(Some(Duration::ZERO))?.count());
+
+    second_output.emit(&[key_event(KeyCode::KEY_A, 1)])?;
+    let keys: Vec<_> = set
+        .fetch_events(TIMEOUT)?
+        .filter_map(|event| match event {
+            SetEvent::Event(key, ev) if ev.event_type() == EventType::KEY => Some((key, ev.code())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(keys, [(second, KeyCode::KEY_A.code())]);
+
+    first_output.emit(&key_click(KeyCode::KEY_B))?;
+    let mut frames = 0;
+    for frame in set.fetch_frames(TIMEOUT)? {
+        match frame {
+            SetFrame::Frame(key, frame) => {
+                assert_eq!(first, key);
+                // the press and the release were reported together
+                assert_eq!(2, frame.len());
+                frames += 1;
+            }
+            SetFrame::Error(_, err) => panic!("{}", err),
+            SetFrame::Removed(..) => panic!("no device was removed"),
+        }
+    }
+    assert_eq!(1, frames);
+
+    let device = set.remove(first).unwrap();
+    assert!(set.get(first).is_none());
+    assert_eq!(Some("test device"), device.name());
+    assert_eq!(1, set.len());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_device_set_removes_gone_devices() -> std::io::Result<()> {
+    let (path, output) = common::get_device()?;
+
+    let mut set = DeviceSet::<RawDevice>::new()?;
+    let key = set.insert(RawDevice::open(path)?)?;
+
+    drop(output);
+
+    let removed: Vec<_> = set
+        .fetch_events(TIMEOUT)?
+        .filter_map(|event| match event {
+            SetEvent::Removed(key, device) => Some((key, device)),
+            SetEvent::Event(..) => None,
+            SetEvent::Error(_, err) => panic!("{}", err),
+        })
+        .collect();
+    assert_eq!(1, removed.len());
+    assert_eq!(key, removed[0].0);
+    assert!(set.is_empty());
+    assert!(set.get(key).is_none());
+
+    Ok(())
+}
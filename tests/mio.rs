@@ -0,0 +1,35 @@
+#![cfg(all(feature = "device-test", feature = "mio"))]
+
+mod common;
+
+use common::{get_test_device, key_click};
+use evdev::{EventType, KeyCode};
+use mio::{Events, Interest, Poll, Token};
+use std::time::Duration;
+
+#[test]
+pub fn test_mio_source() -> std::io::Result<()> {
+    let (mut input, mut output) = get_test_device()?;
+
+    let mut poll = Poll::new()?;
+    poll.registry()
+        .register(&mut input, Token(7), Interest::READABLE)?;
+    // registering made the device nonblocking
+    let err = input.fetch_events().err().unwrap();
+    assert_eq!(std::io::ErrorKind::WouldBlock, err.kind());
+
+    output.emit(&key_click(KeyCode::KEY_A))?;
+    let mut events = Events::with_capacity(4);
+    poll.poll(&mut events, Some(Duration::from_secs(1)))?;
+    let tokens: Vec<_> = events.iter().map(|event| event.token()).collect();
+    assert_eq!(tokens, [Token(7)]);
+
+    let keys = input
+        .fetch_events()?
+        .filter(|ev| ev.event_type() == EventType::KEY)
+        .count();
+    assert_eq!(2, keys);
+
+    poll.registry().deregister(&mut input)?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "device-test")]
+
+mod common;
+
+use common::get_device;
+use evdev::{DeviceMonitor, MonitorEvent};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Waits up to a second for the next event of a non-blocking monitor.
+fn next_event(monitor: &mut DeviceMonitor) -> std::io::Result<MonitorEvent> {
+    let deadline = Instant::now() + Duration::from_secs(1);
+    loop {
+        match monitor.next().unwrap() {
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock && Instant::now() < deadline => {
+                thread::sleep(Duration::from_millis(10));
+            }
+            res => return res,
+        }
+    }
+}
+
+#[test]
+pub fn test_monitor_add_remove() -> std::io::Result<()> {
+    let mut monitor = DeviceMonitor::new()?;
+    monitor.set_nonblocking(true)?;
+
+    let (path, output) = get_device()?;
+
+    match next_event(&mut monitor)? {
+        MonitorEvent::Added(added, device) => {
+            assert_eq!(path, added);
+            assert_eq!(Some("test device"), device.name());
+        }
+        event => panic!("expected the device to be added, got {event:?}"),
+    }
+
+    drop(output);
+
+    match next_event(&mut monitor)? {
+        MonitorEvent::Removed(removed) => assert_eq!(path, removed),
+        event => panic!("expected the device to be removed, got {event:?}"),
+    }
+
+    Ok(())
+}